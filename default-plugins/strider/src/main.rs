@@ -10,7 +10,15 @@ register_plugin!(State);
 impl ZellijPlugin for State {
     fn load(&mut self) {
         refresh_directory(self);
-        subscribe(&[EventType::Key, EventType::Mouse]);
+        subscribe(&[
+            EventType::Key,
+            EventType::Mouse,
+            EventType::FileSystemCreate,
+            EventType::FileSystemUpdate,
+            EventType::FileSystemDelete,
+        ]);
+        // files are opened in an editor pane
+        request_permission(&[PermissionType::RunCommands]);
     }
 
     fn update(&mut self, event: Event) {
@@ -78,6 +86,15 @@ impl ZellijPlugin for State {
                 },
                 _ => {},
             },
+            Event::FileSystemCreate(paths)
+            | Event::FileSystemUpdate(paths)
+            | Event::FileSystemDelete(paths) => {
+                if self.is_showing_any_of(&paths) {
+                    refresh_directory(self);
+                    let last_entry = self.files.len().saturating_sub(1);
+                    *self.selected_mut() = min(self.selected(), last_entry);
+                }
+            },
             _ => {
                 dbg!("Unknown event {:?}", event);
             },
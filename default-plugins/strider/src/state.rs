@@ -30,6 +30,16 @@ impl State {
     pub fn scroll(&self) -> usize {
         self.cursor_hist.get(&self.path).unwrap_or(&(0, 0)).1
     }
+    /// Whether any of the given paths is an entry of the directory being displayed, or a direct
+    /// child of one of its entries (which changes the entry count shown next to it)
+    pub fn is_showing_any_of(&self, paths: &[PathBuf]) -> bool {
+        let current_dir = Path::new(ROOT).join(&self.path);
+        paths.iter().any(|p| {
+            let parent = p.parent();
+            parent == Some(current_dir.as_path())
+                || parent.and_then(|p| p.parent()) == Some(current_dir.as_path())
+        })
+    }
     pub fn toggle_hidden_files(&mut self) {
         self.hide_hidden_files = !self.hide_hidden_files;
     }
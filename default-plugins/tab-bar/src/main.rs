@@ -5,6 +5,9 @@ use std::cmp::{max, min};
 use std::convert::TryInto;
 
 use zellij_tile::prelude::*;
+// renders into the mock host in tests
+#[cfg(test)]
+use zellij_tile::mock::println;
 
 use crate::line::tab_line;
 use crate::tab::tab_style;
@@ -136,3 +139,7 @@ impl ZellijPlugin for State {
         self.should_render = false;
     }
 }
+
+#[cfg(test)]
+#[path = "./unit/main_test.rs"]
+mod main_test;
@@ -0,0 +1,56 @@
+use super::*;
+use zellij_tile::mock::{HostCall, MockHost};
+
+fn tabs(count: usize, active_position: usize) -> Vec<TabInfo> {
+    (0..count)
+        .map(|position| TabInfo {
+            position,
+            name: format!("Tab #{}", position + 1),
+            active: position == active_position,
+            ..Default::default()
+        })
+        .collect()
+}
+
+#[test]
+fn loads_as_unselectable() {
+    let mut host = MockHost::<State>::new();
+    host.load();
+    assert!(!host.is_selectable(), "tab bar is not selectable");
+    assert!(host.subscriptions().contains(&EventType::TabUpdate));
+}
+
+#[test]
+fn scrolling_up_switches_to_the_next_tab() {
+    let mut host = MockHost::<State>::new();
+    host.load();
+    host.send_event(Event::TabUpdate(tabs(3, 0)));
+    host.take_calls();
+    host.send_event(Event::Mouse(Mouse::ScrollUp(1)));
+    assert_eq!(host.take_calls(), vec![HostCall::SwitchTabTo(2)]);
+}
+
+#[test]
+fn renders_all_tab_names() {
+    let mut host = MockHost::<State>::new();
+    host.load();
+    host.send_event(Event::TabUpdate(tabs(2, 1)));
+    let output = host.render(1, 80);
+    assert!(
+        output.contains("Tab #1"),
+        "first tab rendered: {:?}",
+        output
+    );
+    assert!(
+        output.contains("Tab #2"),
+        "second tab rendered: {:?}",
+        output
+    );
+}
+
+#[test]
+fn does_not_receive_events_it_did_not_subscribe_to() {
+    let mut host = MockHost::<State>::new();
+    host.load();
+    assert!(!host.send_event(Event::Timer(1.0)));
+}
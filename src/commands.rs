@@ -1,9 +1,8 @@
 use crate::install::populate_data_dir;
 use crate::sessions::kill_session as kill_session_impl;
 use crate::sessions::{
-    assert_session, assert_session_ne, get_active_session, get_sessions,
-    get_sessions_sorted_by_mtime, match_session_name, print_sessions, print_sessions_with_index,
-    session_exists, ActiveSession, SessionNameMatch,
+    assert_session, assert_session_ne, get_active_session, match_session_name, print_sessions,
+    print_sessions_with_index, session_exists, ActiveSession, SessionNameMatch,
 };
 use dialoguer::Confirm;
 use miette::{Report, Result};
@@ -16,13 +15,14 @@ use zellij_client::start_client as start_client_impl;
 use zellij_client::{os_input_output::get_client_os_input, ClientInfo};
 use zellij_server::os_input_output::get_server_os_input;
 use zellij_server::start_server as start_server_impl;
-use zellij_utils::input::actions::Action;
+use zellij_utils::input::actions::{Action, ActionTarget};
 use zellij_utils::input::config::ConfigError;
 use zellij_utils::input::options::Options;
 use zellij_utils::nix;
 use zellij_utils::{
     cli::{CliArgs, Command, SessionCommand, Sessions},
     envs,
+    sessions::{get_sessions, get_sessions_sorted_by_mtime},
     setup::{get_default_data_dir, Setup},
 };
 
@@ -125,6 +125,7 @@ fn find_indexed_session(
 pub(crate) fn send_action_to_session(
     cli_action: zellij_utils::cli::CliAction,
     requested_session_name: Option<String>,
+    target: ActionTarget,
 ) {
     match get_active_session() {
         ActiveSession::None => {
@@ -142,13 +143,13 @@ pub(crate) fn send_action_to_session(
                     std::process::exit(1);
                 }
             }
-            attach_with_cli_client(cli_action, &session_name);
+            attach_with_cli_client(cli_action, &session_name, target);
         },
         ActiveSession::Many => {
             let existing_sessions = get_sessions().unwrap();
             if let Some(session_name) = requested_session_name {
                 if existing_sessions.contains(&session_name) {
-                    attach_with_cli_client(cli_action, &session_name);
+                    attach_with_cli_client(cli_action, &session_name, target);
                 } else {
                     eprintln!(
                         "Session '{}' not found. The following sessions are active:",
@@ -158,7 +159,7 @@ pub(crate) fn send_action_to_session(
                     std::process::exit(1);
                 }
             } else if let Ok(session_name) = envs::get_session_name() {
-                attach_with_cli_client(cli_action, &session_name);
+                attach_with_cli_client(cli_action, &session_name, target);
             } else {
                 eprintln!("Please specify the session name to send actions to. The following sessions are active:");
                 print_sessions(existing_sessions);
@@ -236,11 +237,24 @@ pub(crate) fn convert_old_theme_file(old_theme_file: PathBuf) {
     }
 }
 
-fn attach_with_cli_client(cli_action: zellij_utils::cli::CliAction, session_name: &str) {
+fn attach_with_cli_client(
+    cli_action: zellij_utils::cli::CliAction,
+    session_name: &str,
+    target: ActionTarget,
+) {
     let os_input = get_os_input(zellij_client::os_input_output::get_client_os_input);
     match Action::actions_from_cli(cli_action) {
         Ok(actions) => {
-            zellij_client::cli_client::start_cli_client(Box::new(os_input), session_name, actions);
+            if !target.is_empty() && !actions.iter().all(|action| action.is_targetable()) {
+                eprintln!("This action can not be run on a specific pane or tab, it always runs on the focused one");
+                std::process::exit(2);
+            }
+            zellij_client::cli_client::start_cli_client(
+                Box::new(os_input),
+                session_name,
+                actions,
+                target,
+            );
             std::process::exit(0);
         },
         Err(e) => {
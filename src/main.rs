@@ -7,6 +7,7 @@ mod tests;
 use zellij_utils::{
     clap::Parser,
     cli::{CliAction, CliArgs, Command, Sessions},
+    input::actions::{ActionTarget, TabSelector},
     logging::*,
 };
 
@@ -15,8 +16,16 @@ fn main() {
     let opts = CliArgs::parse();
 
     {
-        if let Some(Command::Sessions(Sessions::Action(cli_action))) = opts.command {
-            commands::send_action_to_session(cli_action, opts.session);
+        if let Some(Command::Sessions(Sessions::Action {
+            pane_id,
+            tab,
+            tab_name,
+            action,
+        })) = opts.command
+        {
+            let tab = tab.or_else(|| tab_name.map(TabSelector::Name));
+            let target = ActionTarget { pane_id, tab };
+            commands::send_action_to_session(action, opts.session, target);
             std::process::exit(0);
         }
         if let Some(Command::Sessions(Sessions::Run {
@@ -26,6 +35,8 @@ fn main() {
             floating,
             name,
             close_on_exit,
+            wait,
+            print_output,
         })) = opts.command
         {
             let command_cli_action = CliAction::NewPane {
@@ -35,8 +46,14 @@ fn main() {
                 floating,
                 name,
                 close_on_exit,
+                wait,
+                print_output,
             };
-            commands::send_action_to_session(command_cli_action, opts.session);
+            commands::send_action_to_session(
+                command_cli_action,
+                opts.session,
+                ActionTarget::default(),
+            );
             std::process::exit(0);
         }
         if let Some(Command::Sessions(Sessions::Edit {
@@ -52,7 +69,36 @@ fn main() {
                 line_number,
                 floating,
             };
-            commands::send_action_to_session(command_cli_action, opts.session);
+            commands::send_action_to_session(
+                command_cli_action,
+                opts.session,
+                ActionTarget::default(),
+            );
+            std::process::exit(0);
+        }
+        if let Some(Command::Sessions(Sessions::Pipe { plugin, name })) = opts.command {
+            let command_cli_action = CliAction::Pipe { plugin, name };
+            commands::send_action_to_session(
+                command_cli_action,
+                opts.session,
+                ActionTarget::default(),
+            );
+            std::process::exit(0);
+        }
+        if let Some(Command::Sessions(Sessions::Subscribe {
+            events,
+            output_pane_id,
+        })) = opts.command
+        {
+            let command_cli_action = CliAction::Subscribe {
+                events,
+                output_pane_id,
+            };
+            commands::send_action_to_session(
+                command_cli_action,
+                opts.session,
+                ActionTarget::default(),
+            );
             std::process::exit(0);
         }
         if let Some(Command::Sessions(Sessions::ConvertConfig { old_config_file })) = opts.command {
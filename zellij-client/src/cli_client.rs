@@ -1,15 +1,21 @@
 //! The `[cli_client]` is used to attach to a running server session
 //! and dispatch actions, that are specified through the command line.
+use std::io::{self, BufRead, Write};
 use std::process;
 use std::{fs, path::PathBuf};
 
 use crate::os_input_output::ClientOsApi;
 use zellij_utils::{
-    input::actions::Action,
+    input::actions::{Action, ActionTarget},
     ipc::{ClientToServerMsg, ServerToClientMsg},
 };
 
-pub fn start_cli_client(os_input: Box<dyn ClientOsApi>, session_name: &str, actions: Vec<Action>) {
+pub fn start_cli_client(
+    os_input: Box<dyn ClientOsApi>,
+    session_name: &str,
+    actions: Vec<Action>,
+    target: ActionTarget,
+) {
     let zellij_ipc_pipe: PathBuf = {
         let mut sock_dir = zellij_utils::consts::ZELLIJ_SOCK_DIR.clone();
         fs::create_dir_all(&sock_dir).unwrap();
@@ -18,13 +24,112 @@ pub fn start_cli_client(os_input: Box<dyn ClientOsApi>, session_name: &str, acti
         sock_dir
     };
     os_input.connect_to_server(&*zellij_ipc_pipe);
+    let mut is_piping = false;
+    let mut is_querying = false;
+    let mut is_subscribed = false;
+    let mut is_waiting = false;
     for action in actions {
-        let msg = ClientToServerMsg::Action(action, None);
+        if let Action::WaitForCommand(_) = action {
+            // we exit with the exit code of the command once the server reports it
+            is_waiting = true;
+        }
+        if let Action::Subscribe(..) = action {
+            // events keep coming until the user interrupts us or the session ends
+            is_subscribed = true;
+        }
+        if matches!(
+            action,
+            Action::ListTabs(_) | Action::ListPanes(_) | Action::ListClients(_)
+        ) {
+            // the reply arrives after the input thread is unblocked, so wait for it explicitly
+            is_querying = true;
+        }
+        if let Action::PipeToPlugin(location, name, None) = action {
+            // stdin is streamed from its own thread so that the replies of the plugin can be
+            // printed while it is still being read
+            is_piping = true;
+            let os_input = os_input.box_clone();
+            std::thread::Builder::new()
+                .name("pipe_stdin".to_string())
+                .spawn(move || {
+                    for line in io::stdin().lock().lines() {
+                        let line = match line {
+                            Ok(line) => line,
+                            Err(e) => {
+                                log::error!("Failed to read from stdin: {}", e);
+                                break;
+                            },
+                        };
+                        let action =
+                            Action::PipeToPlugin(location.clone(), name.clone(), Some(line));
+                        os_input.send_to_server(ClientToServerMsg::Action(action, None));
+                    }
+                    let action = Action::PipeToPlugin(location, name, None);
+                    os_input.send_to_server(ClientToServerMsg::Action(action, None));
+                })
+                .unwrap();
+            continue;
+        }
+        let msg = if target.is_empty() {
+            ClientToServerMsg::Action(action, None)
+        } else {
+            ClientToServerMsg::TargetedAction(action, target.clone())
+        };
         os_input.send_to_server(msg);
     }
     loop {
         match os_input.recv_from_server() {
-            Some((ServerToClientMsg::UnblockInputThread, _)) => {
+            Some((ServerToClientMsg::UnblockInputThread, _))
+                if !is_piping && !is_querying && !is_subscribed && !is_waiting =>
+            {
+                os_input.send_to_server(ClientToServerMsg::ClientExited);
+                process::exit(0);
+            },
+            Some((ServerToClientMsg::PipeOutput(output), _)) => {
+                let mut stdout = io::stdout();
+                let _ = stdout.write_all(output.as_bytes());
+                let _ = stdout.flush();
+            },
+            Some((ServerToClientMsg::QueryResponse(output), _)) => {
+                let mut stdout = io::stdout();
+                let _ = stdout.write_all(output.as_bytes());
+                let _ = stdout.flush();
+                os_input.send_to_server(ClientToServerMsg::ClientExited);
+                process::exit(0);
+            },
+            Some((ServerToClientMsg::ActionFailed(error), _)) => {
+                eprintln!("{}", error);
+                os_input.send_to_server(ClientToServerMsg::ClientExited);
+                process::exit(1);
+            },
+            Some((ServerToClientMsg::SubscriptionEvent(line), _)) => {
+                let mut stdout = io::stdout();
+                if writeln!(stdout, "{}", line)
+                    .and_then(|_| stdout.flush())
+                    .is_err()
+                {
+                    // whoever reads our output is gone (eg. `zellij subscribe | head`)
+                    os_input.send_to_server(ClientToServerMsg::ClientExited);
+                    process::exit(0);
+                }
+            },
+            Some((ServerToClientMsg::Exit(_), _)) | None if is_subscribed => {
+                process::exit(0);
+            },
+            Some((ServerToClientMsg::CommandOutput(output), _)) => {
+                let mut stdout = io::stdout();
+                let _ = stdout.write_all(&output);
+                let _ = stdout.flush();
+            },
+            Some((ServerToClientMsg::CommandExited(exit_status), _)) => {
+                os_input.send_to_server(ClientToServerMsg::ClientExited);
+                process::exit(exit_status.unwrap_or(1));
+            },
+            Some((ServerToClientMsg::Exit(_), _)) | None if is_waiting => {
+                eprintln!("The session ended before the command exited");
+                process::exit(1);
+            },
+            Some((ServerToClientMsg::PipeClosed, _)) => {
                 os_input.send_to_server(ClientToServerMsg::ClientExited);
                 process::exit(0);
             },
@@ -9,7 +9,7 @@ use zellij_utils::{
     errors::{ContextType, ErrorContext},
     input::{
         actions::Action,
-        cast_termwiz_key,
+        cast_termwiz_key, cast_termwiz_modifiers,
         config::Config,
         mouse::{MouseButton, MouseEvent},
         options::Options,
@@ -44,6 +44,8 @@ struct InputHandler {
     should_exit: bool,
     receive_input_instructions: Receiver<(InputInstruction, ErrorContext)>,
     holding_mouse: Option<HeldMouseButton>,
+    /// Whether plugins subscribe to every key press
+    sends_key_presses: bool,
 }
 
 impl InputHandler {
@@ -67,6 +69,7 @@ impl InputHandler {
             should_exit: false,
             receive_input_instructions,
             holding_mouse: None,
+            sends_key_presses: false,
         }
     }
 
@@ -88,7 +91,12 @@ impl InputHandler {
                 Ok((InputInstruction::KeyEvent(input_event, raw_bytes), _error_context)) => {
                     match input_event {
                         InputEvent::Key(key_event) => {
+                            let modifiers = cast_termwiz_modifiers(key_event.modifiers);
                             let key = cast_termwiz_key(key_event, &raw_bytes);
+                            if self.sends_key_presses {
+                                self.os_input
+                                    .send_to_server(ClientToServerMsg::KeyPress(key, modifiers));
+                            }
                             self.handle_key(&key, raw_bytes);
                         },
                         InputEvent::Mouse(mouse_event) => {
@@ -118,6 +126,15 @@ impl InputHandler {
                 Ok((InputInstruction::SwitchToMode(input_mode), _error_context)) => {
                     self.mode = input_mode;
                 },
+                Ok((
+                    InputInstruction::InputSubscriptions(key_presses, mouse_motion),
+                    _error_context,
+                )) => {
+                    self.sends_key_presses = key_presses;
+                    if self.options.mouse_mode.unwrap_or(true) {
+                        self.os_input.report_mouse_motion(mouse_motion);
+                    }
+                },
                 Ok((
                     InputInstruction::AnsiStdinInstructions(ansi_stdin_instructions),
                     _error_context,
@@ -199,6 +216,11 @@ impl InputHandler {
                     self.holding_mouse = Some(HeldMouseButton::Middle);
                 },
             },
+            MouseEvent::Release(point) if self.holding_mouse.is_none() => {
+                // terminals report the mouse moving without any button held the same way they
+                // report a release, which can only happen after a press
+                self.dispatch_action(Action::MouseHover(point), None);
+            },
             MouseEvent::Release(point) => {
                 let button_released = self.holding_mouse.unwrap_or_default();
                 match button_released {
@@ -22,12 +22,14 @@ use crate::{
 };
 use zellij_utils::{
     channels::{self, ChannelWithContext, SenderWithContext},
-    consts::ZELLIJ_IPC_PIPE,
-    data::{ClientId, InputMode, Style},
+    consts::{ZELLIJ_IPC_PIPE, ZELLIJ_SOCK_DIR},
+    data::{ClientId, InputMode, SessionInfo, Style},
     envs,
     errors::{ClientContext, ContextType, ErrorInstruction},
     input::{config::Config, options::Options},
-    ipc::{ClientAttributes, ClientToServerMsg, ExitReason, ServerToClientMsg},
+    ipc::{query_session_info, ClientAttributes, ClientToServerMsg, ExitReason, ServerToClientMsg},
+    sessions::validate_session_name,
+    setup::{find_default_config_dir, get_layout_dir},
     termwiz::input::InputEvent,
 };
 use zellij_utils::{cli::CliArgs, input::layout::Layout};
@@ -42,6 +44,16 @@ pub(crate) enum ClientInstruction {
     SwitchToMode(InputMode),
     Connected,
     ActiveClients(Vec<ClientId>),
+    PipeOutput(String),
+    PipeClosed,
+    QueryResponse(String),
+    ActionFailed(String),
+    SubscriptionEvent(String),
+    CommandOutput(Vec<u8>),
+    CommandExited(Option<i32>),
+    SessionInfo(SessionInfo),
+    SwitchSession(String),
+    InputSubscriptions(bool, bool), // key presses, mouse motion
 }
 
 impl From<ServerToClientMsg> for ClientInstruction {
@@ -55,6 +67,24 @@ impl From<ServerToClientMsg> for ClientInstruction {
             },
             ServerToClientMsg::Connected => ClientInstruction::Connected,
             ServerToClientMsg::ActiveClients(clients) => ClientInstruction::ActiveClients(clients),
+            ServerToClientMsg::PipeOutput(output) => ClientInstruction::PipeOutput(output),
+            ServerToClientMsg::PipeClosed => ClientInstruction::PipeClosed,
+            ServerToClientMsg::QueryResponse(output) => ClientInstruction::QueryResponse(output),
+            ServerToClientMsg::ActionFailed(error) => ClientInstruction::ActionFailed(error),
+            ServerToClientMsg::SubscriptionEvent(line) => {
+                ClientInstruction::SubscriptionEvent(line)
+            },
+            ServerToClientMsg::CommandOutput(output) => ClientInstruction::CommandOutput(output),
+            ServerToClientMsg::CommandExited(exit_status) => {
+                ClientInstruction::CommandExited(exit_status)
+            },
+            ServerToClientMsg::SessionInfo(session_info) => {
+                ClientInstruction::SessionInfo(session_info)
+            },
+            ServerToClientMsg::SwitchSession(name) => ClientInstruction::SwitchSession(name),
+            ServerToClientMsg::InputSubscriptions(key_presses, mouse_motion) => {
+                ClientInstruction::InputSubscriptions(key_presses, mouse_motion)
+            },
         }
     }
 }
@@ -69,6 +99,16 @@ impl From<&ClientInstruction> for ClientContext {
             ClientInstruction::SwitchToMode(_) => ClientContext::SwitchToMode,
             ClientInstruction::Connected => ClientContext::Connected,
             ClientInstruction::ActiveClients(_) => ClientContext::ActiveClients,
+            ClientInstruction::PipeOutput(_) => ClientContext::PipeOutput,
+            ClientInstruction::PipeClosed => ClientContext::PipeClosed,
+            ClientInstruction::QueryResponse(_) => ClientContext::QueryResponse,
+            ClientInstruction::ActionFailed(_) => ClientContext::ActionFailed,
+            ClientInstruction::SubscriptionEvent(_) => ClientContext::SubscriptionEvent,
+            ClientInstruction::CommandOutput(_) => ClientContext::CommandOutput,
+            ClientInstruction::CommandExited(_) => ClientContext::CommandExited,
+            ClientInstruction::SessionInfo(_) => ClientContext::SessionInfo,
+            ClientInstruction::SwitchSession(_) => ClientContext::SwitchSession,
+            ClientInstruction::InputSubscriptions(..) => ClientContext::InputSubscriptions,
         }
     }
 }
@@ -115,11 +155,78 @@ impl ClientInfo {
     }
 }
 
+/// Attaches to the session with the given name instead of the one the client is connected to,
+/// starting a new session by that name if none is running
+///
+/// The server detaches the client before asking it to switch, so if the switch fails the client
+/// attaches back to the session it came from.
+fn switch_session(
+    os_input: &dyn ClientOsApi,
+    name: String,
+    mut client_attributes: ClientAttributes,
+    opts: &CliArgs,
+    config: &Config,
+    config_options: &Options,
+) {
+    client_attributes.size = os_input.get_terminal_size_using_fd(0);
+    let first_msg = validate_session_name(&name)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+        .and_then(|()| {
+            let socket_path = ZELLIJ_SOCK_DIR.join(&name);
+            if query_session_info(&socket_path).is_some() {
+                return Ok(ClientToServerMsg::AttachClient(
+                    client_attributes.clone(),
+                    config_options.clone(),
+                ));
+            }
+            let layout_path = opts
+                .layout
+                .as_ref()
+                .or(config_options.default_layout.as_ref());
+            let layout_dir = config_options.layout_dir.clone().or_else(|| {
+                get_layout_dir(opts.config_dir.clone().or_else(find_default_config_dir))
+            });
+            let layout = Layout::from_path_or_default(layout_path, layout_dir, config.clone())
+                .map(|(layout, _config)| layout)
+                .unwrap_or_else(|e| {
+                    log::error!("Failed to load the layout of the new session: {}", e);
+                    Layout::default()
+                });
+            spawn_server(&socket_path, opts.debug)?;
+            Ok(ClientToServerMsg::NewClient(
+                client_attributes.clone(),
+                Box::new(opts.clone()),
+                Box::new(config_options.clone()),
+                Box::new(layout),
+                Some(config.plugins.clone()),
+            ))
+        });
+
+    match first_msg {
+        Ok(first_msg) => {
+            os_input.connect_to_server(&ZELLIJ_SOCK_DIR.join(&name));
+            os_input.send_to_server(first_msg);
+            envs::set_session_name(name);
+        },
+        Err(e) => {
+            log::error!("Failed to switch to session {:?}: {}", name, e);
+            if let Ok(current_session_name) = envs::get_session_name() {
+                os_input.connect_to_server(&ZELLIJ_SOCK_DIR.join(current_session_name));
+                os_input.send_to_server(ClientToServerMsg::AttachClient(
+                    client_attributes,
+                    config_options.clone(),
+                ));
+            }
+        },
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(crate) enum InputInstruction {
     KeyEvent(InputEvent, Vec<u8>),
     SwitchToMode(InputMode),
     AnsiStdinInstructions(Vec<AnsiStdinInstruction>),
+    InputSubscriptions(bool, bool), // key presses, mouse motion
 }
 
 pub fn start_client(
@@ -161,6 +268,14 @@ pub fn start_client(
         keybinds: config.keybinds.clone(),
     };
 
+    // kept to attach to another session when a plugin asks to switch
+    let session_switch_state = (
+        client_attributes.clone(),
+        opts.clone(),
+        config.clone(),
+        config_options.clone(),
+    );
+
     let first_msg = match info {
         ClientInfo::Attach(name, config_options) => {
             envs::set_session_name(name);
@@ -291,10 +406,22 @@ pub fn start_client(
         .spawn({
             let os_input = os_input.clone();
             let mut should_break = false;
+            let (client_attributes, opts, config, config_options) = session_switch_state;
             move || loop {
                 match os_input.recv_from_server() {
                     Some((instruction, err_ctx)) => {
                         err_ctx.update_thread_ctx();
+                        if let ServerToClientMsg::SwitchSession(name) = instruction {
+                            switch_session(
+                                &*os_input,
+                                name,
+                                client_attributes.clone(),
+                                &opts,
+                                &config,
+                                &config_options,
+                            );
+                            continue;
+                        }
                         if let ServerToClientMsg::Exit(_) = instruction {
                             should_break = true;
                         }
@@ -373,6 +500,14 @@ pub fn start_client(
                     .send(InputInstruction::SwitchToMode(input_mode))
                     .unwrap();
             },
+            ClientInstruction::InputSubscriptions(key_presses, mouse_motion) => {
+                send_input_instructions
+                    .send(InputInstruction::InputSubscriptions(
+                        key_presses,
+                        mouse_motion,
+                    ))
+                    .unwrap();
+            },
             _ => {},
         }
     }
@@ -21,7 +21,11 @@ use zellij_utils::{
 const SIGWINCH_CB_THROTTLE_DURATION: time::Duration = time::Duration::from_millis(50);
 
 const ENABLE_MOUSE_SUPPORT: &str = "\u{1b}[?1000h\u{1b}[?1002h\u{1b}[?1015h\u{1b}[?1006h";
-const DISABLE_MOUSE_SUPPORT: &str = "\u{1b}[?1006l\u{1b}[?1015l\u{1b}[?1002l\u{1b}[?1000l";
+const DISABLE_MOUSE_SUPPORT: &str =
+    "\u{1b}[?1006l\u{1b}[?1015l\u{1b}[?1003l\u{1b}[?1002l\u{1b}[?1000l";
+// reports the mouse moving without any button held, on top of the above
+const ENABLE_MOUSE_MOTION: &str = "\u{1b}[?1003h";
+const DISABLE_MOUSE_MOTION: &str = "\u{1b}[?1003l";
 
 fn into_raw_mode(pid: RawFd) {
     let mut tio = termios::tcgetattr(pid).expect("could not get terminal attribute");
@@ -108,6 +112,9 @@ pub trait ClientOsApi: Send + Sync {
     fn load_palette(&self) -> Palette;
     fn enable_mouse(&self);
     fn disable_mouse(&self);
+    /// Whether the terminal should report the mouse moving without any button held, only needed
+    /// while plugins subscribe to hover events
+    fn report_mouse_motion(&self, should_report: bool);
     // Repeatedly send action, until stdin is readable again
     fn stdin_poller(&self) -> StdinPoller;
 }
@@ -229,6 +236,18 @@ impl ClientOsApi for ClientOsInputOutput {
             .unwrap();
     }
 
+    fn report_mouse_motion(&self, should_report: bool) {
+        let mouse_motion = if should_report {
+            ENABLE_MOUSE_MOTION
+        } else {
+            DISABLE_MOUSE_MOTION
+        };
+        let _ = self
+            .get_stdout_writer()
+            .write(mouse_motion.as_bytes())
+            .unwrap();
+    }
+
     fn stdin_poller(&self) -> StdinPoller {
         StdinPoller::default()
     }
@@ -1,13 +1,16 @@
 use super::input_loop;
 use crate::stdin_ansi_parser::StdinAnsiParser;
 use crate::stdin_loop;
-use zellij_utils::data::{InputMode, Palette};
+use zellij_utils::data::{InputMode, Key, Palette};
 use zellij_utils::input::actions::{Action, Direction};
 use zellij_utils::input::config::Config;
 use zellij_utils::input::options::Options;
 use zellij_utils::nix;
 use zellij_utils::pane_size::Size;
-use zellij_utils::termwiz::input::{InputEvent, KeyCode, KeyEvent, Modifiers};
+use zellij_utils::position::Position;
+use zellij_utils::termwiz::input::{
+    InputEvent, KeyCode, KeyEvent, Modifiers, MouseButtons, MouseEvent,
+};
 
 use crate::InputInstruction;
 use crate::{
@@ -108,6 +111,7 @@ struct FakeClientOsApi {
     command_is_executing: Arc<Mutex<CommandIsExecuting>>,
     stdout_buffer: Arc<Mutex<Vec<u8>>>,
     stdin_buffer: Vec<u8>,
+    reported_mouse_motion: Arc<Mutex<Vec<bool>>>,
 }
 
 impl FakeClientOsApi {
@@ -125,6 +129,7 @@ impl FakeClientOsApi {
             command_is_executing,
             stdout_buffer,
             stdin_buffer: vec![],
+            reported_mouse_motion: Arc::new(Mutex::new(vec![])),
         }
     }
     pub fn with_stdin_buffer(mut self, stdin_buffer: Vec<u8>) -> Self {
@@ -183,6 +188,12 @@ impl ClientOsApi for FakeClientOsApi {
     }
     fn enable_mouse(&self) {}
     fn disable_mouse(&self) {}
+    fn report_mouse_motion(&self, should_report: bool) {
+        self.reported_mouse_motion
+            .lock()
+            .unwrap()
+            .push(should_report);
+    }
     fn stdin_poller(&self) -> StdinPoller {
         unimplemented!()
     }
@@ -407,3 +418,136 @@ pub fn pixel_info_sent_to_server() {
     drop(stdin_thread);
     drop(input_thread);
 }
+
+fn quit_event() -> InputInstruction {
+    InputInstruction::KeyEvent(
+        InputEvent::Key(KeyEvent {
+            key: KeyCode::Char('q'),
+            modifiers: Modifiers::CTRL,
+        }),
+        commands::QUIT.to_vec(),
+    )
+}
+
+// runs the input loop over the given instructions, followed by quitting
+fn run_input_loop(
+    input_instructions: Vec<InputInstruction>,
+) -> (Vec<ClientToServerMsg>, FakeClientOsApi) {
+    let events_sent_to_server = Arc::new(Mutex::new(vec![]));
+    let command_is_executing = CommandIsExecuting::new();
+    let client_os_api =
+        FakeClientOsApi::new(events_sent_to_server.clone(), command_is_executing.clone());
+    let config = Config::from_default_assets().unwrap();
+    let options = Options::default();
+
+    let (send_client_instructions, _receive_client_instructions): ChannelWithContext<
+        ClientInstruction,
+    > = channels::bounded(50);
+    let send_client_instructions = SenderWithContext::new(send_client_instructions);
+
+    let (send_input_instructions, receive_input_instructions): ChannelWithContext<
+        InputInstruction,
+    > = channels::bounded(50);
+    let send_input_instructions = SenderWithContext::new(send_input_instructions);
+    for instruction in input_instructions {
+        send_input_instructions.send(instruction).unwrap();
+    }
+    send_input_instructions.send(quit_event()).unwrap();
+
+    input_loop(
+        Box::new(client_os_api.clone()),
+        config,
+        options,
+        command_is_executing,
+        send_client_instructions,
+        InputMode::Normal,
+        receive_input_instructions,
+    );
+    let events_sent_to_server = events_sent_to_server.lock().unwrap().clone();
+    (events_sent_to_server, client_os_api)
+}
+
+#[test]
+pub fn key_presses_are_only_sent_while_plugins_subscribe_to_them() {
+    let key_press = || {
+        InputInstruction::KeyEvent(
+            InputEvent::Key(KeyEvent {
+                key: KeyCode::Char('a'),
+                modifiers: Modifiers::NONE,
+            }),
+            vec![97],
+        )
+    };
+    let (events_sent_to_server, _) = run_input_loop(vec![
+        key_press(),
+        InputInstruction::InputSubscriptions(true, false),
+        key_press(),
+        InputInstruction::InputSubscriptions(false, false),
+        key_press(),
+    ]);
+    let key_presses: Vec<Key> = events_sent_to_server
+        .iter()
+        .filter_map(|event| match event {
+            ClientToServerMsg::KeyPress(key, _modifiers) => Some(*key),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(key_presses, vec![Key::Char('a')]);
+    let written_keys = extract_actions_sent_to_server(Arc::new(Mutex::new(events_sent_to_server)))
+        .into_iter()
+        .filter(|action| matches!(action, Action::Write(_)))
+        .count();
+    assert_eq!(
+        written_keys, 3,
+        "keys are handled whether or not they are sent"
+    );
+}
+
+#[test]
+pub fn mouse_motion_is_only_reported_while_plugins_subscribe_to_hover_events() {
+    let (_, client_os_api) = run_input_loop(vec![
+        InputInstruction::InputSubscriptions(false, true),
+        InputInstruction::InputSubscriptions(false, false),
+    ]);
+    assert_eq!(
+        *client_os_api.reported_mouse_motion.lock().unwrap(),
+        vec![true, false]
+    );
+}
+
+#[test]
+pub fn mouse_moving_without_a_held_button_hovers() {
+    let mouse_motion = |x, y| {
+        InputInstruction::KeyEvent(
+            InputEvent::Mouse(MouseEvent {
+                x,
+                y,
+                mouse_buttons: MouseButtons::NONE,
+                modifiers: Modifiers::NONE,
+            }),
+            vec![],
+        )
+    };
+    let left_click = InputInstruction::KeyEvent(
+        InputEvent::Mouse(MouseEvent {
+            x: 5,
+            y: 5,
+            mouse_buttons: MouseButtons::LEFT,
+            modifiers: Modifiers::NONE,
+        }),
+        vec![],
+    );
+    let (events_sent_to_server, _) =
+        run_input_loop(vec![mouse_motion(10, 3), left_click, mouse_motion(5, 5)]);
+    let actions = extract_actions_sent_to_server(Arc::new(Mutex::new(events_sent_to_server)));
+    assert_eq!(
+        actions,
+        vec![
+            Action::MouseHover(Position::new(2, 9)),
+            Action::LeftClick(Position::new(4, 4)),
+            Action::LeftMouseRelease(Position::new(4, 4)),
+            Action::Quit,
+        ],
+        "the mouse reported without buttons after a press is a release, not a hover"
+    );
+}
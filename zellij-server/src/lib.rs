@@ -3,11 +3,13 @@ pub mod output;
 pub mod panes;
 pub mod tab;
 
+mod listings;
 mod logging_pipe;
 mod pty;
 mod pty_writer;
 mod route;
 mod screen;
+mod session_scanner;
 mod terminal_bytes;
 mod thread_bus;
 mod ui;
@@ -25,27 +27,27 @@ use zellij_utils::envs;
 use zellij_utils::nix::sys::stat::{umask, Mode};
 use zellij_utils::pane_size::Size;
 
-use wasmer::Store;
-
 use crate::{
     os_input_output::ServerOsApi,
     pty::{pty_thread_main, Pty, PtyInstruction},
     screen::{screen_thread_main, ScreenInstruction},
     thread_bus::{Bus, ThreadSenders},
-    wasm_vm::{wasm_thread_main, PluginInstruction},
+    wasm_vm::{metered_store, wasm_thread_main, PluginInstruction},
 };
-use route::route_thread_main;
+use route::{route_action, route_thread_main};
 use zellij_utils::{
     channels::{self, ChannelWithContext, SenderWithContext},
     cli::CliArgs,
     consts::{DEFAULT_SCROLL_BUFFER_SIZE, SCROLL_BUFFER_SIZE},
-    data::{Event, PluginCapabilities},
-    errors::{ContextType, ErrorInstruction, FatalError, ServerContext},
+    data::{Event, PermissionStatus, PermissionType, PluginCapabilities, SessionInfo},
+    errors::{prelude::*, ContextType, ErrorInstruction, ServerContext},
     input::{
+        actions::Action,
         command::{RunCommand, TerminalAction},
         get_mode_info,
         layout::Layout,
         options::Options,
+        permission::PermissionCache,
         plugins::PluginsConfig,
     },
     ipc::{ClientAttributes, ExitReason, ServerToClientMsg},
@@ -75,6 +77,19 @@ pub enum ServerInstruction {
     AttachClient(ClientAttributes, Options, ClientId),
     ConnStatus(ClientId),
     ActiveClients(ClientId),
+    PluginPermissionsResponse(u32, ClientId, Vec<PermissionType>, PermissionStatus), // plugin_id, client_id, permissions, status
+    ActionFromPlugin(Action, ClientId), // run on behalf of this client
+    PipeOutput(ClientId, String),       // cli client id, output
+    ClosePipe(ClientId),                // cli client id
+    QuerySessionInfo(ClientId),
+    SessionInfo(ClientId, SessionInfo),
+    SwitchSession(String, ClientId),          // session name
+    QueryResponse(ClientId, String),          // cli client id, output
+    ActionFailed(ClientId, String),           // cli client id, error
+    SubscriptionEvent(ClientId, String),      // cli client id, a line of JSON
+    CommandOutput(ClientId, Vec<u8>),         // cli client id, output
+    CommandExited(ClientId, Option<i32>),     // cli client id, exit status
+    InputSubscriptions(ClientId, bool, bool), // key presses, mouse motion
 }
 
 impl From<&ServerInstruction> for ServerContext {
@@ -91,6 +106,21 @@ impl From<&ServerInstruction> for ServerContext {
             ServerInstruction::AttachClient(..) => ServerContext::AttachClient,
             ServerInstruction::ConnStatus(..) => ServerContext::ConnStatus,
             ServerInstruction::ActiveClients(_) => ServerContext::ActiveClients,
+            ServerInstruction::PluginPermissionsResponse(..) => {
+                ServerContext::PluginPermissionsResponse
+            },
+            ServerInstruction::ActionFromPlugin(..) => ServerContext::ActionFromPlugin,
+            ServerInstruction::PipeOutput(..) => ServerContext::PipeOutput,
+            ServerInstruction::ClosePipe(..) => ServerContext::ClosePipe,
+            ServerInstruction::QuerySessionInfo(..) => ServerContext::QuerySessionInfo,
+            ServerInstruction::SessionInfo(..) => ServerContext::SessionInfo,
+            ServerInstruction::SwitchSession(..) => ServerContext::SwitchSession,
+            ServerInstruction::QueryResponse(..) => ServerContext::QueryResponse,
+            ServerInstruction::ActionFailed(..) => ServerContext::ActionFailed,
+            ServerInstruction::SubscriptionEvent(..) => ServerContext::SubscriptionEvent,
+            ServerInstruction::CommandOutput(..) => ServerContext::CommandOutput,
+            ServerInstruction::CommandExited(..) => ServerContext::CommandExited,
+            ServerInstruction::InputSubscriptions(..) => ServerContext::InputSubscriptions,
         }
     }
 }
@@ -585,6 +615,153 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                     session_state
                 );
             },
+            ServerInstruction::PluginPermissionsResponse(
+                plugin_id,
+                client_id,
+                permissions,
+                status,
+            ) => {
+                if let Some(session_data) = session_data.write().unwrap().as_ref() {
+                    session_data
+                        .senders
+                        .send_to_plugin(PluginInstruction::PermissionRequestResult(
+                            plugin_id,
+                            client_id,
+                            permissions,
+                            status,
+                        ))
+                        .unwrap();
+                }
+            },
+            ServerInstruction::ActionFromPlugin(action, client_id) => {
+                if let Some(session_data) = session_data.read().unwrap().as_ref() {
+                    route_action(action, session_data, &*os_input, &to_server, client_id)
+                        .context("failed to run action from plugin")
+                        .non_fatal();
+                }
+            },
+            ServerInstruction::PipeOutput(client_id, output) => {
+                send_to_client!(
+                    client_id,
+                    os_input,
+                    ServerToClientMsg::PipeOutput(output),
+                    session_state
+                );
+            },
+            ServerInstruction::ClosePipe(client_id) => {
+                send_to_client!(
+                    client_id,
+                    os_input,
+                    ServerToClientMsg::PipeClosed,
+                    session_state
+                );
+            },
+            ServerInstruction::QuerySessionInfo(client_id) => {
+                match session_data.read().unwrap().as_ref() {
+                    Some(session_data) => session_data
+                        .senders
+                        .send_to_screen(ScreenInstruction::QuerySessionInfo(client_id))
+                        .unwrap(),
+                    // the session is still starting, there is nothing to report yet
+                    None => {
+                        remove_client!(client_id, os_input, session_state);
+                    },
+                }
+            },
+            ServerInstruction::SessionInfo(client_id, session_info) => {
+                let _ = os_input
+                    .send_to_client(client_id, ServerToClientMsg::SessionInfo(session_info));
+                remove_client!(client_id, os_input, session_state);
+            },
+            ServerInstruction::SwitchSession(session_name, client_id) => {
+                // like detaching, except the client attaches to the other session instead of
+                // exiting
+                let _ = os_input
+                    .send_to_client(client_id, ServerToClientMsg::SwitchSession(session_name));
+                remove_client!(client_id, os_input, session_state);
+                match session_data.read().unwrap().as_ref() {
+                    Some(session_data) => {
+                        if let Some(min_size) =
+                            session_state.read().unwrap().min_client_terminal_size()
+                        {
+                            session_data
+                                .senders
+                                .send_to_screen(ScreenInstruction::TerminalResize(min_size))
+                                .unwrap();
+                        }
+                        session_data
+                            .senders
+                            .send_to_screen(ScreenInstruction::RemoveClient(client_id))
+                            .unwrap();
+                        session_data
+                            .senders
+                            .send_to_plugin(PluginInstruction::RemoveClient(client_id))
+                            .unwrap();
+                    },
+                    None => {
+                        log::warn!(
+                            "Client {} switched sessions after this session was torn down",
+                            client_id
+                        );
+                    },
+                }
+            },
+            ServerInstruction::QueryResponse(client_id, output) => {
+                send_to_client!(
+                    client_id,
+                    os_input,
+                    ServerToClientMsg::QueryResponse(output),
+                    session_state
+                );
+            },
+            ServerInstruction::ActionFailed(client_id, error) => {
+                send_to_client!(
+                    client_id,
+                    os_input,
+                    ServerToClientMsg::ActionFailed(error),
+                    session_state
+                );
+            },
+            ServerInstruction::SubscriptionEvent(client_id, line) => {
+                let send_res =
+                    os_input.send_to_client(client_id, ServerToClientMsg::SubscriptionEvent(line));
+                if send_res.is_err() {
+                    // the subscriber went away, stop collecting events for it
+                    remove_client!(client_id, os_input, session_state);
+                    if let Some(session_data) = session_data.read().unwrap().as_ref() {
+                        let _ = session_data
+                            .senders
+                            .send_to_screen(ScreenInstruction::RemoveClient(client_id));
+                        let _ = session_data
+                            .senders
+                            .send_to_plugin(PluginInstruction::RemoveClient(client_id));
+                    }
+                }
+            },
+            ServerInstruction::CommandOutput(client_id, output) => {
+                send_to_client!(
+                    client_id,
+                    os_input,
+                    ServerToClientMsg::CommandOutput(output),
+                    session_state
+                );
+            },
+            ServerInstruction::CommandExited(client_id, exit_status) => {
+                send_to_client!(
+                    client_id,
+                    os_input,
+                    ServerToClientMsg::CommandExited(exit_status),
+                    session_state
+                );
+            },
+            ServerInstruction::InputSubscriptions(client_id, key_presses, mouse_motion) => {
+                send_to_client!(
+                    client_id,
+                    os_input,
+                    ServerToClientMsg::InputSubscriptions(key_presses, mouse_motion),
+                    session_state
+                );
+            },
         }
     }
 
@@ -713,14 +890,23 @@ fn init_session(
                 Some(&to_screen),
                 Some(&to_pty),
                 Some(&to_plugin),
-                None,
+                Some(&to_server),
                 Some(&to_pty_writer),
                 None,
             );
-            let store = Store::default();
+            let store = metered_store();
+            let default_shell = default_shell.clone();
 
             move || {
-                wasm_thread_main(plugin_bus, store, data_dir, plugins.unwrap_or_default()).fatal()
+                wasm_thread_main(
+                    plugin_bus,
+                    store,
+                    data_dir,
+                    PermissionCache::default_path(),
+                    plugins.unwrap_or_default(),
+                    default_shell,
+                )
+                .fatal()
             }
         })
         .unwrap();
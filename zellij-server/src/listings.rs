@@ -0,0 +1,118 @@
+//! The replies to the `zellij action list-*` commands, either as JSON or as tab separated tables.
+//! Tabs are listed by their position starting at 1, like `zellij action go-to-tab` numbers them.
+use std::fmt::Write;
+
+use zellij_utils::data::{ClientId, ClientListing, PaneId, PaneListing, TabListing};
+use zellij_utils::serde::Serialize;
+
+fn to_json(listings: &impl Serialize) -> String {
+    match serde_json::to_string(listings) {
+        Ok(json) => format!("{}\n", json),
+        Err(e) => {
+            log::error!("Failed to serialize listing: {}", e);
+            String::new()
+        },
+    }
+}
+
+fn pane_id_to_string(id: u32, is_plugin: bool) -> String {
+    if is_plugin {
+        format!("plugin_{}", id)
+    } else {
+        format!("terminal_{}", id)
+    }
+}
+
+/// Formats a pane id the way it is listed and passed to `--pane-id`, eg. `terminal_3`
+pub(crate) fn format_pane_id(pane_id: PaneId) -> String {
+    match pane_id {
+        PaneId::Terminal(id) => pane_id_to_string(id, false),
+        PaneId::Plugin(id) => pane_id_to_string(id, true),
+    }
+}
+
+// the rows are tab separated, so tabs and newlines in names, titles or commands are escaped
+fn escape_field(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn client_ids_to_string(client_ids: &[ClientId]) -> String {
+    client_ids
+        .iter()
+        .map(|client_id| client_id.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+pub(crate) fn format_tabs(tabs: &[TabListing], json: bool) -> String {
+    if json {
+        return to_json(&tabs);
+    }
+    let mut output = String::from("POSITION\tNAME\tPANES\tFOCUSED_BY\n");
+    for tab in tabs {
+        let _ = writeln!(
+            output,
+            "{}\t{}\t{}\t{}",
+            tab.position,
+            escape_field(&tab.name),
+            tab.pane_count,
+            client_ids_to_string(&tab.focused_by_clients),
+        );
+    }
+    output
+}
+
+pub(crate) fn format_panes(panes: &[PaneListing], json: bool) -> String {
+    if json {
+        return to_json(&panes);
+    }
+    let mut output = String::from("ID\tTAB\tTITLE\tFOCUSED\tFLOATING\tCOMMAND\tCWD\n");
+    for pane in panes {
+        let _ = writeln!(
+            output,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            pane_id_to_string(pane.id, pane.is_plugin),
+            pane.tab_position,
+            escape_field(&pane.title),
+            pane.is_focused,
+            pane.is_floating,
+            escape_field(pane.command.as_deref().unwrap_or("")),
+            pane.cwd
+                .as_ref()
+                .map(|cwd| escape_field(&cwd.display().to_string()))
+                .unwrap_or_default(),
+        );
+    }
+    output
+}
+
+pub(crate) fn format_clients(clients: &[ClientListing], json: bool) -> String {
+    if json {
+        return to_json(&clients);
+    }
+    let mut output = String::from("CLIENT_ID\tTAB\tFOCUSED_PANE\n");
+    for client in clients {
+        let _ = writeln!(
+            output,
+            "{}\t{}\t{}",
+            client.id,
+            client
+                .tab_position
+                .map(|position| position.to_string())
+                .unwrap_or_default(),
+            client
+                .focused_pane_id
+                .map(|id| pane_id_to_string(id, client.focused_pane_is_plugin))
+                .unwrap_or_default(),
+        );
+    }
+    output
+}
+
+#[cfg(test)]
+#[path = "./unit/listings_tests.rs"]
+mod listings_tests;
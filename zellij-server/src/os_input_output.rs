@@ -27,6 +27,7 @@ use nix::unistd;
 use signal_hook::consts::*;
 use zellij_utils::{
     data::Palette,
+    envs,
     input::command::{RunCommand, TerminalAction},
     ipc::{ClientToServerMsg, IpcReceiverWithContext, IpcSenderWithContext, ServerToClientMsg},
     shared::default_palette,
@@ -132,6 +133,7 @@ fn handle_openpty(
     cmd: RunCommand,
     quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>, // u32 is the exit status
     terminal_id: u32,
+    tab_index: Option<usize>,
 ) -> Result<(RawFd, RawFd), SpawnTerminalError> {
     // primary side of pty and child fd
     let pid_primary = open_pty_res.master;
@@ -152,8 +154,12 @@ fn handle_openpty(
                     );
                 }
             }
+            if let Some(tab_index) = tab_index {
+                command.env(envs::TAB_INDEX_ENV_KEY, tab_index.to_string());
+            }
             command
                 .args(&cmd.args)
+                .env(envs::PANE_ID_ENV_KEY, terminal_id.to_string())
                 .pre_exec(move || -> std::io::Result<()> {
                     if libc::login_tty(pid_secondary) != 0 {
                         panic!("failed to set controlling terminal");
@@ -188,15 +194,21 @@ fn handle_terminal(
     orig_termios: termios::Termios,
     quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>,
     terminal_id: u32,
+    tab_index: Option<usize>,
 ) -> Result<(RawFd, RawFd), SpawnTerminalError> {
     // Create a pipe to allow the child the communicate the shell's pid to its
     // parent.
     match openpty(None, Some(&orig_termios)) {
-        Ok(open_pty_res) => handle_openpty(open_pty_res, cmd, quit_cb, terminal_id),
+        Ok(open_pty_res) => handle_openpty(open_pty_res, cmd, quit_cb, terminal_id, tab_index),
         Err(e) => match failover_cmd {
-            Some(failover_cmd) => {
-                handle_terminal(failover_cmd, None, orig_termios, quit_cb, terminal_id)
-            },
+            Some(failover_cmd) => handle_terminal(
+                failover_cmd,
+                None,
+                orig_termios,
+                quit_cb,
+                terminal_id,
+                tab_index,
+            ),
             None => {
                 log::error!("Failed to start pty: {:?}", e);
                 Err(SpawnTerminalError::FailedToStartPty)
@@ -241,6 +253,7 @@ fn spawn_terminal(
     quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>, // u32 is the exit_status
     default_editor: Option<PathBuf>,
     terminal_id: u32,
+    tab_index: Option<usize>,
 ) -> Result<(RawFd, RawFd), SpawnTerminalError> {
     // returns the terminal_id, the primary fd and the
     // secondary fd
@@ -292,7 +305,14 @@ fn spawn_terminal(
         None
     };
 
-    handle_terminal(cmd, failover_cmd, orig_termios, quit_cb, terminal_id)
+    handle_terminal(
+        cmd,
+        failover_cmd,
+        orig_termios,
+        quit_cb,
+        terminal_id,
+        tab_index,
+    )
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -374,12 +394,14 @@ pub trait ServerOsApi: Send + Sync {
     fn set_terminal_size_using_terminal_id(&self, id: u32, cols: u16, rows: u16);
     /// Spawn a new terminal, with a terminal action. The returned tuple contains the master file
     /// descriptor of the forked pseudo terminal and a [ChildId] struct containing process id's for
-    /// the forked child process.
+    /// the forked child process. `tab_index` (if known) is the index of the tab the terminal opens
+    /// in.
     fn spawn_terminal(
         &self,
         terminal_action: TerminalAction,
         quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>, // u32 is the exit status
         default_editor: Option<PathBuf>,
+        tab_index: Option<usize>,
     ) -> Result<(u32, RawFd, RawFd), SpawnTerminalError>;
     /// Read bytes from the standard output of the virtual terminal referred to by `fd`.
     fn read_from_tty_stdout(&self, fd: RawFd, buf: &mut [u8]) -> Result<usize, nix::Error>;
@@ -409,6 +431,8 @@ pub trait ServerOsApi: Send + Sync {
     fn load_palette(&self) -> Palette;
     /// Returns the current working directory for a given pid
     fn get_cwd(&self, pid: Pid) -> Option<PathBuf>;
+    /// Returns the command line of the process with the given pid
+    fn get_cmd(&self, pid: Pid) -> Option<Vec<String>>;
     /// Writes the given buffer to a string
     fn write_to_file(&mut self, buf: String, file: Option<String>);
 
@@ -417,6 +441,7 @@ pub trait ServerOsApi: Send + Sync {
         terminal_id: u32,
         run_command: RunCommand,
         quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>, // u32 is the exit status
+        tab_index: Option<usize>,
     ) -> Result<(RawFd, RawFd), SpawnTerminalError>;
     fn clear_terminal_id(&self, terminal_id: u32);
 }
@@ -439,6 +464,7 @@ impl ServerOsApi for ServerOsInputOutput {
         terminal_action: TerminalAction,
         quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>, // u32 is the exit status
         default_editor: Option<PathBuf>,
+        tab_index: Option<usize>,
     ) -> Result<(u32, RawFd, RawFd), SpawnTerminalError> {
         let orig_termios = self.orig_termios.lock().unwrap();
         let mut terminal_id = None;
@@ -470,6 +496,7 @@ impl ServerOsApi for ServerOsInputOutput {
                     quit_cb,
                     default_editor,
                     terminal_id,
+                    tab_index,
                 ) {
                     Ok((pid_primary, pid_secondary)) => {
                         self.terminal_id_to_raw_fd
@@ -565,6 +592,14 @@ impl ServerOsApi for ServerOsInputOutput {
         }
         None
     }
+    fn get_cmd(&self, pid: Pid) -> Option<Vec<String>> {
+        let mut system_info = System::new();
+        system_info.refresh_processes_specifics(ProcessRefreshKind::default());
+
+        system_info
+            .process(pid.into())
+            .map(|process| process.cmd().to_vec())
+    }
     fn write_to_file(&mut self, buf: String, name: Option<String>) {
         let mut f: File = match name {
             Some(x) => File::create(x).unwrap(),
@@ -579,6 +614,7 @@ impl ServerOsApi for ServerOsInputOutput {
         terminal_id: u32,
         run_command: RunCommand,
         quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>, // u32 is the exit status
+        tab_index: Option<usize>,
     ) -> Result<(RawFd, RawFd), SpawnTerminalError> {
         let orig_termios = self.orig_termios.lock().unwrap();
         let default_editor = None; // no need for a default editor when running an explicit command
@@ -588,6 +624,7 @@ impl ServerOsApi for ServerOsInputOutput {
             quit_cb,
             default_editor,
             terminal_id,
+            tab_index,
         ) {
             Ok((pid_primary, pid_secondary)) => {
                 self.terminal_id_to_raw_fd
@@ -18,8 +18,9 @@ use std::time::Instant;
 use zellij_utils::{
     data::{ModeInfo, Style},
     errors::prelude::*,
+    input::actions::ResizeDirection,
     input::command::RunCommand,
-    pane_size::{Offset, PaneGeom, Size, Viewport},
+    pane_size::{Dimension, Offset, PaneGeom, Size, Viewport},
 };
 
 macro_rules! resize_pty {
@@ -310,11 +311,66 @@ impl FloatingPanes {
         floating_pane_grid.resize(new_screen_size);
         self.set_force_render();
     }
+    /// Resizes the pane so that its content has the given size, moving it up and to the left
+    /// where needed to keep it on screen
+    pub fn resize_pane_to(
+        &mut self,
+        pane_id: PaneId,
+        rows: usize,
+        cols: usize,
+        os_api: &mut Box<dyn ServerOsApi>,
+    ) {
+        let viewport = *self.viewport.borrow();
+        if let Some(pane) = self.panes.get_mut(&pane_id) {
+            let frame_size = if pane.borderless() { 0 } else { 2 };
+            let rows = (rows + frame_size).min(viewport.rows);
+            let cols = (cols + frame_size).min(viewport.cols);
+            let current_geom = pane.position_and_size();
+            let mut geom = PaneGeom {
+                x: current_geom.x.min(viewport.x + viewport.cols - cols),
+                y: current_geom.y.min(viewport.y + viewport.rows - rows),
+                rows: Dimension::fixed(rows),
+                cols: Dimension::fixed(cols),
+            };
+            geom.rows.set_inner(rows);
+            geom.cols.set_inner(cols);
+            pane.set_geom(geom);
+            resize_pty!(pane, os_api);
+            self.desired_pane_positions.insert(pane_id, geom);
+        }
+    }
     pub fn resize_pty_all_panes(&mut self, os_api: &mut Box<dyn ServerOsApi>) {
         for pane in self.panes.values_mut() {
             resize_pty!(pane, os_api);
         }
     }
+    pub fn resize_pane_with_id(
+        &mut self,
+        pane_id: PaneId,
+        direction: &ResizeDirection,
+        os_api: &mut Box<dyn ServerOsApi>,
+    ) {
+        let display_area = *self.display_area.borrow();
+        let viewport = *self.viewport.borrow();
+        let mut floating_pane_grid = FloatingPaneGrid::new(
+            &mut self.panes,
+            &mut self.desired_pane_positions,
+            display_area,
+            viewport,
+        );
+        match direction {
+            ResizeDirection::Left => floating_pane_grid.resize_pane_left(&pane_id),
+            ResizeDirection::Right => floating_pane_grid.resize_pane_right(&pane_id),
+            ResizeDirection::Up => floating_pane_grid.resize_pane_up(&pane_id),
+            ResizeDirection::Down => floating_pane_grid.resize_pane_down(&pane_id),
+            ResizeDirection::Increase => floating_pane_grid.resize_increase(&pane_id),
+            ResizeDirection::Decrease => floating_pane_grid.resize_decrease(&pane_id),
+        }
+        for pane in self.panes.values_mut() {
+            resize_pty!(pane, os_api);
+        }
+        self.set_force_render();
+    }
     pub fn resize_active_pane_left(
         &mut self,
         client_id: ClientId,
@@ -34,7 +34,7 @@ use crate::panes::search::SearchResult;
 use crate::panes::selection::Selection;
 use crate::panes::terminal_character::{
     AnsiCode, CharacterStyles, CharsetIndex, Cursor, CursorShape, StandardCharset,
-    TerminalCharacter, EMPTY_TERMINAL_CHARACTER,
+    TerminalCharacter, EMPTY_TERMINAL_CHARACTER, RESET_STYLES,
 };
 
 fn get_top_non_canonical_rows(rows: &mut Vec<Row>) -> Vec<Row> {
@@ -299,6 +299,33 @@ macro_rules! dump_screen {
     }};
 }
 
+macro_rules! dump_screen_with_styles {
+    ($lines:expr) => {{
+        let mut is_first = true;
+        let mut buf = "".to_owned();
+        let mut current_styles = RESET_STYLES;
+
+        for line in &$lines {
+            if line.is_canonical && !is_first {
+                buf.push_str("\n");
+            }
+            for character in &line.columns {
+                if let Some(new_styles) =
+                    current_styles.update_and_return_diff(&character.styles, None)
+                {
+                    buf.push_str(&new_styles.to_string());
+                }
+                buf.push(character.character);
+            }
+            is_first = false;
+        }
+        if current_styles != RESET_STYLES {
+            buf.push_str("\u{1b}[m");
+        }
+        buf
+    }};
+}
+
 fn utf8_mouse_coordinates(column: usize, line: isize) -> Vec<u8> {
     let mut coordinates = vec![];
     let mouse_pos_encode = |pos: usize| -> Vec<u8> {
@@ -997,6 +1024,19 @@ impl Grid {
         scrollback.push_str(&viewport);
         scrollback
     }
+    /// Like `dump_screen`, but keeps the styling of the characters as ANSI escape codes
+    pub fn dump_screen_with_styles(&mut self, full: bool) -> String {
+        let viewport: String = dump_screen_with_styles!(self.viewport);
+        if !full {
+            return viewport;
+        }
+        let mut scrollback: String = dump_screen_with_styles!(self.lines_above);
+        if !scrollback.is_empty() {
+            scrollback.push('\n');
+        }
+        scrollback.push_str(&viewport);
+        scrollback
+    }
     pub fn move_viewport_up(&mut self, count: usize) {
         for _ in 0..count {
             self.scroll_up_one_line();
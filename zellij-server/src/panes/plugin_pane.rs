@@ -14,8 +14,9 @@ use zellij_utils::position::Position;
 use zellij_utils::shared::ansi_len;
 use zellij_utils::{
     channels::SenderWithContext,
-    data::{Event, InputMode, Mouse, PaletteColor},
+    data::{Event, InputMode, Mouse, PaletteColor, PaneGeometry},
     errors::prelude::*,
+    input::layout::RunPluginLocation,
     pane_size::{Dimension, PaneGeom},
     shared::make_terminal_title,
 };
@@ -29,11 +30,13 @@ pub(crate) struct PluginPane {
     pub content_offset: Offset,
     pub send_plugin_instructions: SenderWithContext<PluginInstruction>,
     pub active_at: Instant,
+    pub location: RunPluginLocation,
     pub pane_title: String,
     pub pane_name: String,
     prev_pane_name: String,
     frame: bool,
     borderless: bool,
+    reported_geometry: Option<PaneGeometry>,
 }
 
 impl PluginPane {
@@ -41,7 +44,7 @@ impl PluginPane {
         pid: u32,
         position_and_size: PaneGeom,
         send_plugin_instructions: SenderWithContext<PluginInstruction>,
-        title: String,
+        location: RunPluginLocation,
         pane_name: String,
     ) -> Self {
         Self {
@@ -54,10 +57,24 @@ impl PluginPane {
             active_at: Instant::now(),
             frame: false,
             content_offset: Offset::default(),
-            pane_title: title,
+            pane_title: location.to_string(),
+            location,
             borderless: false,
             pane_name: pane_name.clone(),
             prev_pane_name: pane_name,
+            reported_geometry: None,
+        }
+    }
+    fn geometry(&self) -> PaneGeometry {
+        PaneGeometry {
+            x: self.x(),
+            y: self.y(),
+            rows: self.rows(),
+            columns: self.cols(),
+            content_x: self.get_content_x(),
+            content_y: self.get_content_y(),
+            content_rows: self.get_content_rows(),
+            content_columns: self.get_content_columns(),
         }
     }
 }
@@ -155,6 +172,20 @@ impl Pane for PluginPane {
             let mut vte_output = String::new();
             let (buf_tx, buf_rx) = channel();
 
+            // let the plugin know about its new geometry before it renders with it
+            let geometry = self.geometry();
+            if self.reported_geometry != Some(geometry) {
+                self.send_plugin_instructions
+                    .send(PluginInstruction::Update(
+                        Some(self.pid),
+                        None,
+                        Event::Resize(geometry),
+                    ))
+                    .to_anyhow()
+                    .with_context(err_context)?;
+                self.reported_geometry = Some(geometry);
+            }
+
             self.send_plugin_instructions
                 .send(PluginInstruction::Render(
                     buf_tx,
@@ -418,6 +449,20 @@ impl Pane for PluginPane {
     fn borderless(&self) -> bool {
         self.borderless
     }
+    fn plugin_location(&self) -> Option<&RunPluginLocation> {
+        Some(&self.location)
+    }
+    fn set_title(&mut self, title: String) {
+        self.pane_title = title;
+        self.set_should_render(true);
+    }
+    fn current_title(&self) -> String {
+        if self.pane_name.is_empty() {
+            self.pane_title.to_owned()
+        } else {
+            self.pane_name.to_owned()
+        }
+    }
     fn handle_right_click(&mut self, to: &Position, client_id: ClientId) {
         self.send_plugin_instructions
             .send(PluginInstruction::Update(
@@ -427,4 +472,13 @@ impl Pane for PluginPane {
             ))
             .unwrap();
     }
+    fn handle_mouse_hover(&mut self, to: &Position, client_id: ClientId) {
+        self.send_plugin_instructions
+            .send(PluginInstruction::Update(
+                Some(self.pid),
+                Some(client_id),
+                Event::MouseHover(to.line.0, to.column.0),
+            ))
+            .unwrap();
+    }
 }
@@ -83,6 +83,25 @@ pub enum PaneId {
     Plugin(u32), // FIXME: Drop the trait object, make this a wrapper for the struct?
 }
 
+// the plugin-facing pane id lives in zellij-utils so that it can be shared with zellij-tile
+impl From<zellij_utils::data::PaneId> for PaneId {
+    fn from(pane_id: zellij_utils::data::PaneId) -> Self {
+        match pane_id {
+            zellij_utils::data::PaneId::Terminal(id) => PaneId::Terminal(id),
+            zellij_utils::data::PaneId::Plugin(id) => PaneId::Plugin(id),
+        }
+    }
+}
+
+impl From<PaneId> for zellij_utils::data::PaneId {
+    fn from(pane_id: PaneId) -> Self {
+        match pane_id {
+            PaneId::Terminal(id) => zellij_utils::data::PaneId::Terminal(id),
+            PaneId::Plugin(id) => zellij_utils::data::PaneId::Plugin(id),
+        }
+    }
+}
+
 // FIXME: This should hold an os_api handle so that terminal panes can set their own size via FD in
 // their `reflow_lines()` method. Drop a Box<dyn ServerOsApi> in here somewhere.
 #[allow(clippy::too_many_arguments)]
@@ -104,8 +123,10 @@ pub struct TerminalPane {
     borderless: bool,
     fake_cursor_locations: HashSet<(usize, usize)>, // (x, y) - these hold a record of previous fake cursors which we need to clear on render
     search_term: String,
-    is_held: Option<(Option<i32>, RunCommand)>, // a "held" pane means that its command has exited and its waiting for a
-                                                // possible user instruction to be re-run
+    // a "held" pane means that its command has exited and its waiting for a possible user
+    // instruction to be re-run
+    is_held: Option<(Option<i32>, RunCommand)>,
+    invoked_with: Option<RunCommand>, // the command this pane was opened with, if it's a command pane
 }
 
 impl Pane for TerminalPane {
@@ -522,6 +543,9 @@ impl Pane for TerminalPane {
     fn dump_screen(&mut self, _client_id: ClientId, full: bool) -> String {
         self.grid.dump_screen(full)
     }
+    fn dump_screen_with_styles(&mut self, _client_id: ClientId, full: bool) -> String {
+        self.grid.dump_screen_with_styles(full)
+    }
     fn scroll_up(&mut self, count: usize, _client_id: ClientId) {
         self.grid.move_viewport_up(count);
         self.set_should_render(true);
@@ -702,9 +726,40 @@ impl Pane for TerminalPane {
         self.grid.is_alternate_mode_active()
     }
     fn hold(&mut self, exit_status: Option<i32>, run_command: RunCommand) {
+        self.invoked_with = Some(run_command.clone());
         self.is_held = Some((exit_status, run_command));
         self.set_should_render(true);
     }
+    fn current_title(&self) -> String {
+        if self.pane_name.is_empty() {
+            self.grid
+                .title
+                .as_deref()
+                .unwrap_or(&self.pane_title)
+                .to_owned()
+        } else {
+            self.pane_name.to_owned()
+        }
+    }
+    fn is_held(&self) -> bool {
+        self.is_held.is_some()
+    }
+    fn exit_status(&self) -> Option<i32> {
+        self.is_held
+            .as_ref()
+            .and_then(|(exit_status, _run_command)| *exit_status)
+    }
+    fn held_command(&self) -> Option<RunCommand> {
+        self.is_held
+            .as_ref()
+            .map(|(_exit_status, run_command)| run_command.clone())
+    }
+    fn invoked_with(&self) -> Option<RunCommand> {
+        self.invoked_with.clone()
+    }
+    fn set_invoked_with(&mut self, run_command: Option<RunCommand>) {
+        self.invoked_with = run_command;
+    }
 }
 
 impl TerminalPane {
@@ -752,6 +807,7 @@ impl TerminalPane {
             fake_cursor_locations: HashSet::new(),
             search_term: String::new(),
             is_held: None,
+            invoked_with: None,
         }
     }
     pub fn get_x(&self) -> usize {
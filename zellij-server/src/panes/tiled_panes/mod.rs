@@ -15,6 +15,7 @@ use std::time::Instant;
 use zellij_utils::errors::prelude::*;
 use zellij_utils::{
     data::{ModeInfo, Style},
+    input::actions::ResizeDirection,
     input::command::RunCommand,
     input::layout::SplitDirection,
     pane_size::{Offset, PaneGeom, Size, SizeInPixels, Viewport},
@@ -494,6 +495,25 @@ impl TiledPanes {
         }
         self.set_pane_frames(self.draw_pane_frames);
     }
+    pub fn resize_pane_with_id(&mut self, pane_id: PaneId, direction: &ResizeDirection) {
+        let mut pane_grid = TiledPaneGrid::new(
+            &mut self.panes,
+            &self.panes_to_hide,
+            *self.display_area.borrow(),
+            *self.viewport.borrow(),
+        );
+        match direction {
+            ResizeDirection::Left => pane_grid.resize_pane_left(&pane_id),
+            ResizeDirection::Right => pane_grid.resize_pane_right(&pane_id),
+            ResizeDirection::Up => pane_grid.resize_pane_up(&pane_id),
+            ResizeDirection::Down => pane_grid.resize_pane_down(&pane_id),
+            ResizeDirection::Increase => pane_grid.resize_increase(&pane_id),
+            ResizeDirection::Decrease => pane_grid.resize_decrease(&pane_id),
+        }
+        for pane in self.panes.values_mut() {
+            resize_pty!(pane, self.os_api);
+        }
+    }
     pub fn resize_active_pane_left(&mut self, client_id: ClientId) {
         if let Some(active_pane_id) = self.get_active_pane_id(client_id) {
             let mut pane_grid = TiledPaneGrid::new(
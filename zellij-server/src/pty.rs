@@ -1,6 +1,7 @@
 use crate::os_input_output::SpawnTerminalError;
 use crate::terminal_bytes::TerminalBytes;
 use crate::{
+    listings::format_panes,
     panes::PaneId,
     screen::ScreenInstruction,
     thread_bus::{Bus, ThreadSenders},
@@ -8,10 +9,11 @@ use crate::{
     ClientId, ServerInstruction,
 };
 use async_std::task::{self, JoinHandle};
-use std::{collections::HashMap, env, os::unix::io::RawFd, path::PathBuf};
+use std::{collections::HashMap, env, os::unix::io::RawFd, path::PathBuf, sync::mpsc};
 use zellij_utils::nix::unistd::Pid;
 use zellij_utils::{
     async_std,
+    data::PaneListing,
     errors::prelude::*,
     errors::{ContextType, PtyContext},
     input::{
@@ -57,6 +59,8 @@ pub(crate) enum PtyInstruction {
     ClosePane(PaneId),
     CloseTab(Vec<PaneId>),
     ReRunCommandInPane(PaneId, RunCommand),
+    ListPanes(Vec<PaneListing>, bool, ClientId), // bool is json
+    WaitForCommand(ClientId, bool),              // cli client id, bool is print_output
     Exit,
 }
 
@@ -73,6 +77,8 @@ impl From<&PtyInstruction> for PtyContext {
             PtyInstruction::CloseTab(_) => PtyContext::CloseTab,
             PtyInstruction::NewTab(..) => PtyContext::NewTab,
             PtyInstruction::ReRunCommandInPane(..) => PtyContext::ReRunCommandInPane,
+            PtyInstruction::ListPanes(..) => PtyContext::ListPanes,
+            PtyInstruction::WaitForCommand(..) => PtyContext::WaitForCommand,
             PtyInstruction::Exit => PtyContext::Exit,
         }
     }
@@ -85,6 +91,22 @@ pub(crate) struct Pty {
     debug_to_file: bool,
     task_handles: HashMap<u32, JoinHandle<()>>, // terminal_id to join-handle
     default_editor: Option<PathBuf>,
+    /// cli clients waiting for the command of the next pane they open, see `zellij run --wait`
+    command_waiters: HashMap<ClientId, bool>, // bool is print_output
+    /// The index of the tab each terminal was opened in (panes don't move between tabs)
+    tab_indices: HashMap<u32, usize>, // terminal_id => tab_index
+    /// The index the next tab will be created with, tab indices are never reused so that they
+    /// can identify a tab for as long as it's open
+    next_tab_index: usize,
+}
+
+pub(crate) fn get_default_terminal(cwd: Option<PathBuf>) -> TerminalAction {
+    TerminalAction::RunCommand(RunCommand {
+        args: vec![],
+        command: PathBuf::from(env::var("SHELL").expect("Could not find the SHELL variable")),
+        cwd, // note: this might also be filled by the calling function, eg. spawn_terminal
+        hold_on_close: false,
+    })
 }
 
 pub(crate) fn pty_thread_main(mut pty: Pty, layout: Box<Layout>) -> Result<()> {
@@ -115,6 +137,7 @@ pub(crate) fn pty_thread_main(mut pty: Pty, layout: Box<Layout>) -> Result<()> {
                                 PaneId::Terminal(pid),
                                 pane_title,
                                 should_float,
+                                run_command.clone(),
                                 client_or_tab_index,
                             ))
                             .with_context(err_context)?;
@@ -127,6 +150,7 @@ pub(crate) fn pty_thread_main(mut pty: Pty, layout: Box<Layout>) -> Result<()> {
                                     PaneId::Terminal(pid),
                                     pane_title,
                                     should_float,
+                                    run_command.clone(),
                                     client_or_tab_index,
                                 ))
                                 .with_context(err_context)?;
@@ -184,6 +208,7 @@ pub(crate) fn pty_thread_main(mut pty: Pty, layout: Box<Layout>) -> Result<()> {
                             .send_to_screen(ScreenInstruction::VerticalSplit(
                                 PaneId::Terminal(pid),
                                 pane_title,
+                                run_command.clone(),
                                 client_id,
                             ))
                             .with_context(err_context)?;
@@ -195,6 +220,7 @@ pub(crate) fn pty_thread_main(mut pty: Pty, layout: Box<Layout>) -> Result<()> {
                                 .send_to_screen(ScreenInstruction::VerticalSplit(
                                     PaneId::Terminal(pid),
                                     pane_title,
+                                    run_command.clone(),
                                     client_id,
                                 ))
                                 .with_context(err_context)?;
@@ -244,6 +270,7 @@ pub(crate) fn pty_thread_main(mut pty: Pty, layout: Box<Layout>) -> Result<()> {
                             .send_to_screen(ScreenInstruction::HorizontalSplit(
                                 PaneId::Terminal(pid),
                                 pane_title,
+                                run_command.clone(),
                                 client_id,
                             ))
                             .with_context(err_context)?;
@@ -255,6 +282,7 @@ pub(crate) fn pty_thread_main(mut pty: Pty, layout: Box<Layout>) -> Result<()> {
                                 .send_to_screen(ScreenInstruction::HorizontalSplit(
                                     PaneId::Terminal(pid),
                                     pane_title,
+                                    run_command.clone(),
                                     client_id,
                                 ))
                                 .with_context(err_context)?;
@@ -364,6 +392,21 @@ pub(crate) fn pty_thread_main(mut pty: Pty, layout: Box<Layout>) -> Result<()> {
                     },
                 }
             },
+            PtyInstruction::ListPanes(mut panes, json, client_id) => {
+                for pane in panes.iter_mut().filter(|pane| !pane.is_plugin) {
+                    pty.fill_process_info(pane);
+                }
+                pty.bus
+                    .senders
+                    .send_to_server(ServerInstruction::QueryResponse(
+                        client_id,
+                        format_panes(&panes, json),
+                    ))
+                    .with_context(err_context)?;
+            },
+            PtyInstruction::WaitForCommand(client_id, print_output) => {
+                pty.command_waiters.insert(client_id, print_output);
+            },
             PtyInstruction::Exit => break,
         }
     }
@@ -383,15 +426,13 @@ impl Pty {
             debug_to_file,
             task_handles: HashMap::new(),
             default_editor,
+            command_waiters: HashMap::new(),
+            tab_indices: HashMap::new(),
+            next_tab_index: 0,
         }
     }
     pub fn get_default_terminal(&self, cwd: Option<PathBuf>) -> TerminalAction {
-        TerminalAction::RunCommand(RunCommand {
-            args: vec![],
-            command: PathBuf::from(env::var("SHELL").expect("Could not find the SHELL variable")),
-            cwd, // note: this might also be filled by the calling function, eg. spawn_terminal
-            hold_on_close: false,
-        })
+        get_default_terminal(cwd)
     }
     fn fill_cwd(&self, terminal_action: &mut TerminalAction, client_id: ClientId) {
         if let TerminalAction::RunCommand(run_command) = terminal_action {
@@ -412,6 +453,19 @@ impl Pty {
             };
         };
     }
+    fn fill_process_info(&self, pane: &mut PaneListing) {
+        let os_input = match self.bus.os_input.as_ref() {
+            Some(os_input) => os_input,
+            None => return,
+        };
+        if let Some(&child_pid) = self.id_to_child_pid.get(&pane.id) {
+            let pid = Pid::from_raw(child_pid);
+            pane.cwd = os_input.get_cwd(pid);
+            if pane.command.is_none() {
+                pane.command = os_input.get_cmd(pid).map(|cmd| cmd.join(" "));
+            }
+        }
+    }
     pub fn spawn_terminal(
         &mut self,
         terminal_action: Option<TerminalAction>,
@@ -433,9 +487,26 @@ impl Pty {
             TerminalAction::RunCommand(run_command) => run_command.hold_on_close,
             _ => false,
         };
+        let tab_index = match client_or_tab_index {
+            ClientOrTabIndex::ClientId(client_id) => self.active_tab_index(client_id),
+            ClientOrTabIndex::TabIndex(tab_index) => Some(tab_index),
+        };
+        let command_waiter = match client_or_tab_index {
+            ClientOrTabIndex::ClientId(client_id) => self
+                .command_waiters
+                .remove(&client_id)
+                .map(|print_output| (client_id, print_output)),
+            ClientOrTabIndex::TabIndex(_) => None,
+        };
+        // the command can exit before all of its output was read, so the exit is reported once the
+        // output of the terminal ends
+        let (command_exit_tx, command_exit_rx) = mpsc::channel();
         let quit_cb = Box::new({
             let senders = self.bus.senders.clone();
             move |pane_id, exit_status, command| {
+                if command_waiter.is_some() {
+                    let _ = command_exit_tx.send((pane_id, exit_status));
+                }
                 if hold_on_close {
                     let _ = senders.send_to_screen(ScreenInstruction::HoldPane(
                         pane_id,
@@ -448,12 +519,45 @@ impl Pty {
                 }
             }
         });
-        let (terminal_id, pid_primary, child_fd): (u32, RawFd, RawFd) = self
+        let default_editor = self.default_editor.clone();
+        let spawn_result = self
             .bus
             .os_input
             .as_mut()
-            .ok_or_else(|| SpawnTerminalError::GenericSpawnError("os input is none"))?
-            .spawn_terminal(terminal_action, quit_cb, self.default_editor.clone())?;
+            .ok_or_else(|| SpawnTerminalError::GenericSpawnError("os input is none"))
+            .and_then(|os_input| {
+                os_input.spawn_terminal(terminal_action, quit_cb, default_editor, tab_index)
+            });
+        if let (
+            Some(tab_index),
+            Ok((terminal_id, ..)) | Err(SpawnTerminalError::CommandNotFound(terminal_id)),
+        ) = (tab_index, &spawn_result)
+        {
+            self.tab_indices.insert(*terminal_id, tab_index);
+        }
+        let (terminal_id, pid_primary, child_fd): (u32, RawFd, RawFd) = match spawn_result {
+            Ok(spawned) => spawned,
+            Err(e) => {
+                if let Some((client_id, _)) = command_waiter {
+                    let exit_status = match e {
+                        SpawnTerminalError::CommandNotFound(_) => Some(127), // like a shell would
+                        _ => None,
+                    };
+                    let _ = self
+                        .bus
+                        .senders
+                        .send_to_server(ServerInstruction::CommandExited(client_id, exit_status));
+                }
+                return Err(e);
+            },
+        };
+        if let Some((client_id, true)) = command_waiter {
+            // before we start reading from the terminal, so that none of its output is missed
+            let _ = self
+                .bus
+                .senders
+                .send_to_screen(ScreenInstruction::CopyCommandOutput(terminal_id, client_id));
+        }
         let terminal_bytes = task::spawn({
             let err_context =
                 |terminal_id: u32| format!("failed to run async task for terminal {terminal_id}");
@@ -467,11 +571,28 @@ impl Pty {
                 .clone();
             let debug_to_file = self.debug_to_file;
             async move {
-                TerminalBytes::new(pid_primary, senders, os_input, debug_to_file, terminal_id)
-                    .listen()
-                    .await
-                    .with_context(|| err_context(terminal_id))
-                    .fatal();
+                TerminalBytes::new(
+                    pid_primary,
+                    senders.clone(),
+                    os_input,
+                    debug_to_file,
+                    terminal_id,
+                )
+                .listen()
+                .await
+                .with_context(|| err_context(terminal_id))
+                .fatal();
+                if let Some((client_id, _)) = command_waiter {
+                    let command_exit = task::spawn_blocking(move || command_exit_rx.recv()).await;
+                    if let Ok((pane_id, exit_status)) = command_exit {
+                        // through screen, so that the client gets the last of the output first
+                        let _ = senders.send_to_screen(ScreenInstruction::CommandExited(
+                            pane_id,
+                            exit_status,
+                            client_id,
+                        ));
+                    }
+                }
             }
         });
 
@@ -486,6 +607,8 @@ impl Pty {
         client_id: ClientId,
     ) -> Result<()> {
         let err_context = || format!("failed to spawn terminals for layout for client {client_id}");
+        let tab_index = self.next_tab_index;
+        self.next_tab_index += 1;
         let mut default_shell = default_shell.unwrap_or_else(|| self.get_default_terminal(None));
         self.fill_cwd(&mut default_shell, client_id);
         let extracted_run_instructions = layout.extract_run_instructions();
@@ -525,7 +648,7 @@ impl Pty {
                         .os_input
                         .as_mut()
                         .with_context(err_context)?
-                        .spawn_terminal(cmd, quit_cb, self.default_editor.clone())
+                        .spawn_terminal(cmd, quit_cb, self.default_editor.clone(), Some(tab_index))
                     {
                         Ok((terminal_id, pid_primary, child_fd)) => {
                             self.id_to_child_pid.insert(terminal_id, child_fd);
@@ -554,8 +677,12 @@ impl Pty {
                         .os_input
                         .as_mut()
                         .with_context(err_context)?
-                        .spawn_terminal(shell, quit_cb, self.default_editor.clone())
-                    {
+                        .spawn_terminal(
+                            shell,
+                            quit_cb,
+                            self.default_editor.clone(),
+                            Some(tab_index),
+                        ) {
                         Ok((terminal_id, pid_primary, child_fd)) => {
                             self.id_to_child_pid.insert(terminal_id, child_fd);
                             new_pane_pids.push((terminal_id, None, Ok(pid_primary)));
@@ -582,6 +709,7 @@ impl Pty {
                             TerminalAction::OpenFile(path_to_file, line_number),
                             quit_cb,
                             self.default_editor.clone(),
+                            Some(tab_index),
                         ) {
                         Ok((terminal_id, pid_primary, child_fd)) => {
                             self.id_to_child_pid.insert(terminal_id, child_fd);
@@ -605,8 +733,12 @@ impl Pty {
                         .os_input
                         .as_mut()
                         .with_context(err_context)?
-                        .spawn_terminal(default_shell.clone(), quit_cb, self.default_editor.clone())
-                    {
+                        .spawn_terminal(
+                            default_shell.clone(),
+                            quit_cb,
+                            self.default_editor.clone(),
+                            Some(tab_index),
+                        ) {
                         Ok((terminal_id, pid_primary, child_fd)) => {
                             self.id_to_child_pid.insert(terminal_id, child_fd);
                             new_pane_pids.push((terminal_id, None, Ok(pid_primary)));
@@ -631,11 +763,15 @@ impl Pty {
             .iter()
             .map(|(terminal_id, _, _)| *terminal_id)
             .collect::<Vec<u32>>();
+        for terminal_id in &new_tab_pane_ids {
+            self.tab_indices.insert(*terminal_id, tab_index);
+        }
         self.bus
             .senders
             .send_to_screen(ScreenInstruction::NewTab(
                 layout,
                 new_tab_pane_ids,
+                tab_index,
                 client_id,
             ))
             .with_context(err_context)?;
@@ -695,6 +831,7 @@ impl Pty {
         match id {
             PaneId::Terminal(id) => {
                 self.task_handles.remove(&id);
+                self.tab_indices.remove(&id);
                 if let Some(child_fd) = self.id_to_child_pid.remove(&id) {
                     task::block_on(async {
                         let err_context = || format!("failed to run async task for pane {id}");
@@ -729,6 +866,14 @@ impl Pty {
         }
         Ok(())
     }
+    /// The index of the tab focused by `client_id`, as far as the pty thread knows it (ie. if its
+    /// focused pane is a terminal)
+    fn active_tab_index(&self, client_id: ClientId) -> Option<usize> {
+        match self.active_panes.get(&client_id) {
+            Some(PaneId::Terminal(id)) => self.tab_indices.get(id).copied(),
+            _ => None,
+        }
+    }
     pub fn set_active_pane(&mut self, pane_id: Option<PaneId>, client_id: ClientId) {
         if let Some(pane_id) = pane_id {
             self.active_panes.insert(client_id, pane_id);
@@ -761,7 +906,12 @@ impl Pty {
                     .os_input
                     .as_mut()
                     .ok_or_else(|| SpawnTerminalError::GenericSpawnError("os input is none"))?
-                    .re_run_command_in_terminal(id, run_command, quit_cb)?;
+                    .re_run_command_in_terminal(
+                        id,
+                        run_command,
+                        quit_cb,
+                        self.tab_indices.get(&id).copied(),
+                    )?;
                 let terminal_bytes = task::spawn({
                     let err_context =
                         |pane_id| format!("failed to run async task for pane {pane_id:?}");
@@ -829,3 +979,7 @@ fn send_command_not_found_to_screen(
         .with_context(err_context)?;
     Ok(())
 }
+
+#[path = "./unit/pty_tests.rs"]
+#[cfg(test)]
+mod pty_tests;
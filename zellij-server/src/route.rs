@@ -9,12 +9,13 @@ use crate::{
 };
 use zellij_utils::{
     channels::SenderWithContext,
-    data::Event,
+    data::{Event, SubscriptionKind},
     errors::prelude::*,
     input::{
         actions::{Action, Direction, ResizeDirection, SearchDirection, SearchOption},
         command::TerminalAction,
         get_mode_info,
+        layout::RunPlugin,
     },
     ipc::{ClientToServerMsg, ExitReason, IpcReceiverWithContext, ServerToClientMsg},
 };
@@ -31,11 +32,11 @@ pub(crate) fn route_action(
     let mut should_break = false;
     let err_context = || format!("failed to route action for client {client_id}");
 
-    // forward the action to plugins unless it is a mousehold
+    // forward the action to plugins unless it is a mousehold or hover
     // this is a bit of a hack around the unfortunate architecture we use with plugins
     // this will change as soon as we refactor
     match action {
-        Action::MouseHoldLeft(..) | Action::MouseHoldRight(..) => {},
+        Action::MouseHoldLeft(..) | Action::MouseHoldRight(..) | Action::MouseHover(..) => {},
         _ => {
             session
                 .senders
@@ -177,6 +178,44 @@ pub(crate) fn route_action(
                 .send_to_screen(ScreenInstruction::DumpScreen(val, client_id, full))
                 .with_context(err_context)?;
         },
+        Action::ListTabs(json) => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::ListTabs(json, client_id))
+                .with_context(err_context)?;
+        },
+        Action::ListPanes(json) => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::ListPanes(json, client_id))
+                .with_context(err_context)?;
+        },
+        Action::ListClients(json) => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::ListClients(json, client_id))
+                .with_context(err_context)?;
+        },
+        Action::Subscribe(kinds, pane_ids) => {
+            if kinds.contains(&SubscriptionKind::PaneOutput) {
+                session
+                    .senders
+                    .send_to_screen(ScreenInstruction::SubscribeToPaneOutput(
+                        pane_ids, client_id,
+                    ))
+                    .with_context(err_context)?;
+            }
+            session
+                .senders
+                .send_to_plugin(PluginInstruction::Subscribe(kinds, client_id))
+                .with_context(err_context)?;
+        },
+        Action::WaitForCommand(print_output) => {
+            session
+                .senders
+                .send_to_pty(PtyInstruction::WaitForCommand(client_id, print_output))
+                .with_context(err_context)?;
+        },
         Action::EditScrollback => {
             session
                 .senders
@@ -553,6 +592,12 @@ pub(crate) fn route_action(
                 .send_to_screen(ScreenInstruction::MouseHoldMiddle(point, client_id))
                 .with_context(err_context)?;
         },
+        Action::MouseHover(point) => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::MouseHover(point, client_id))
+                .with_context(err_context)?;
+        },
         Action::Copy => {
             session
                 .senders
@@ -588,6 +633,33 @@ pub(crate) fn route_action(
                 .send_to_screen(ScreenInstruction::UpdateSearch(c, client_id))
                 .with_context(err_context)?;
         },
+        Action::ReloadPlugin(location, watch) => {
+            session
+                .senders
+                .send_to_plugin(PluginInstruction::Reload(location, watch))
+                .with_context(err_context)?;
+        },
+        Action::LaunchOrFocusPlugin(location, should_float) => {
+            let run_plugin = RunPlugin {
+                _allow_exec_host_cmd: false,
+                location,
+                configuration: Default::default(),
+            };
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::LaunchOrFocusPlugin(
+                    run_plugin,
+                    should_float,
+                    client_id,
+                ))
+                .with_context(err_context)?;
+        },
+        Action::PipeToPlugin(location, name, payload) => {
+            session
+                .senders
+                .send_to_plugin(PluginInstruction::Pipe(location, name, payload, client_id))
+                .with_context(err_context)?;
+        },
         Action::Search(d) => {
             let instruction = match d {
                 SearchDirection::Down => ScreenInstruction::SearchDown(client_id),
@@ -677,6 +749,16 @@ pub(crate) fn route_thread_main(
                                 }
                             }
                         },
+                        ClientToServerMsg::TargetedAction(action, target) => {
+                            if let Some(rlocked_sessions) = rlocked_sessions.as_ref() {
+                                rlocked_sessions
+                                    .senders
+                                    .send_to_screen(ScreenInstruction::TargetedAction(
+                                        action, target, client_id,
+                                    ))
+                                    .with_context(err_context)?;
+                            }
+                        },
                         ClientToServerMsg::TerminalResize(new_size) => {
                             session_state
                                 .write()
@@ -778,6 +860,22 @@ pub(crate) fn route_thread_main(
                         ClientToServerMsg::ListClients => {
                             let _ = to_server.send(ServerInstruction::ActiveClients(client_id));
                         },
+                        ClientToServerMsg::KeyPress(key, modifiers) => {
+                            if let Some(rlocked_sessions) = rlocked_sessions.as_ref() {
+                                rlocked_sessions
+                                    .senders
+                                    .send_to_plugin(PluginInstruction::Update(
+                                        None,
+                                        Some(client_id),
+                                        Event::KeyPress(key, modifiers),
+                                    ))
+                                    .with_context(err_context)?;
+                            }
+                        },
+                        ClientToServerMsg::QuerySessionInfo => {
+                            let _ = to_server.send(ServerInstruction::QuerySessionInfo(client_id));
+                            should_break = true;
+                        },
                     }
                     Ok(should_break)
                 };
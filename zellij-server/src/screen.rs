@@ -1,7 +1,7 @@
 //! Things related to [`Screen`]s.
 
 use std::cell::RefCell;
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::rc::Rc;
 use std::str;
 
@@ -9,26 +9,42 @@ use zellij_utils::errors::prelude::*;
 use zellij_utils::input::command::RunCommand;
 use zellij_utils::input::options::Clipboard;
 use zellij_utils::pane_size::{Size, SizeInPixels};
-use zellij_utils::{input::command::TerminalAction, input::layout::PaneLayout, position::Position};
+use zellij_utils::{
+    envs,
+    input::command::TerminalAction,
+    input::layout::{PaneLayout, RunPlugin},
+    position::Position,
+};
 
 use crate::panes::alacritty_functions::xparse_color;
 use crate::panes::terminal_character::AnsiCode;
 
 use crate::{
+    listings::{format_clients, format_pane_id, format_tabs},
     output::Output,
     panes::sixel::SixelImageStore,
     panes::PaneId,
     pty::{ClientOrTabIndex, PtyInstruction, VteBytes},
-    tab::Tab,
+    tab::{ScrollAmount, Tab},
     thread_bus::Bus,
-    ui::overlay::{Overlay, OverlayWindow, Overlayable},
+    ui::overlay::{
+        prompt::generate_plugin_permissions_prompt, Overlay, OverlayWindow, Overlayable,
+    },
     wasm_vm::PluginInstruction,
     ClientId, ServerInstruction,
 };
 use zellij_utils::{
-    data::{Event, InputMode, ModeInfo, Palette, PaletteColor, PluginCapabilities, Style, TabInfo},
+    data::{
+        ClientListing, Event, InputMode, ModeInfo, Palette, PaletteColor, PaneId as DataPaneId,
+        PaneListing, PaneManifest, PermissionType, PluginCapabilities, SessionInfo, Style, TabInfo,
+        TabListing,
+    },
     errors::{ContextType, ScreenContext},
-    input::{get_mode_info, options::Options},
+    input::{
+        actions::{Action, ActionTarget, TabSelector},
+        get_mode_info,
+        options::Options,
+    },
     ipc::{ClientAttributes, PixelDimensions, ServerToClientMsg},
 };
 
@@ -117,15 +133,24 @@ macro_rules! active_tab_and_connected_client_id {
 pub enum ScreenInstruction {
     PtyBytes(u32, VteBytes),
     Render,
-    NewPane(PaneId, Option<String>, Option<bool>, ClientOrTabIndex), // String is initial title,
+    NewPane(
+        PaneId,
+        Option<String>,
+        Option<bool>,
+        Option<RunCommand>,
+        ClientOrTabIndex,
+    ), // String is initial title, RunCommand is set for command panes
     // bool (if Some) is
     // should_float
     OpenInPlaceEditor(PaneId, ClientId),
     TogglePaneEmbedOrFloating(ClientId),
     ToggleFloatingPanes(ClientId, Option<TerminalAction>),
-    HorizontalSplit(PaneId, Option<String>, ClientId), // String is initial title
-    VerticalSplit(PaneId, Option<String>, ClientId),   // String is initial title
+    HorizontalSplit(PaneId, Option<String>, Option<RunCommand>, ClientId), // String is initial title
+    VerticalSplit(PaneId, Option<String>, Option<RunCommand>, ClientId), // String is initial title
     WriteCharacter(Vec<u8>, ClientId),
+    WriteFromPlugin(Vec<u8>, u32, ClientId), // u32 is the id of the writing plugin
+    WriteToPaneId(Vec<u8>, PaneId),
+    DumpPaneContentsForPlugin(PaneId, bool, bool, u32, ClientId), // pane id, full, with styles, plugin id, client id
     ResizeLeft(ClientId),
     ResizeRight(ClientId),
     ResizeDown(ClientId),
@@ -163,11 +188,15 @@ pub enum ScreenInstruction {
     ToggleActiveTerminalFullscreen(ClientId),
     TogglePaneFrames,
     SetSelectable(PaneId, bool, usize),
+    SetPaneTitle(PaneId, String),
+    SetPaneBorderless(PaneId, bool),
+    SetPanePreferredSize(PaneId, usize, usize), // pane id, rows, cols
+    LaunchOrFocusPlugin(RunPlugin, bool, ClientId), // bool is should_float
     ClosePane(PaneId, Option<ClientId>),
     HoldPane(PaneId, Option<i32>, RunCommand, Option<ClientId>), // Option<i32> is the exit status
     UpdatePaneName(Vec<u8>, ClientId),
     UndoRenamePane(ClientId),
-    NewTab(PaneLayout, Vec<u32>, ClientId),
+    NewTab(PaneLayout, Vec<u32>, usize, ClientId), // usize is the tab index
     SwitchTabNext(ClientId),
     SwitchTabPrev(ClientId),
     ToggleActiveSyncTab(ClientId),
@@ -192,6 +221,7 @@ pub enum ScreenInstruction {
     MouseHoldLeft(Position, ClientId),
     MouseHoldRight(Position, ClientId),
     MouseHoldMiddle(Position, ClientId),
+    MouseHover(Position, ClientId),
     Copy(ClientId),
     AddClient(ClientId),
     RemoveClient(ClientId),
@@ -199,12 +229,21 @@ pub enum ScreenInstruction {
     RemoveOverlay(ClientId),
     ConfirmPrompt(ClientId),
     DenyPrompt(ClientId),
+    RequestPluginPermissions(u32, ClientId, String, Vec<PermissionType>), // plugin id, client id, plugin name, permissions
     UpdateSearch(Vec<u8>, ClientId),
     SearchDown(ClientId),
     SearchUp(ClientId),
     SearchToggleCaseSensitivity(ClientId),
     SearchToggleWholeWord(ClientId),
     SearchToggleWrap(ClientId),
+    QuerySessionInfo(ClientId),
+    ListTabs(bool, ClientId),    // bool is json
+    ListPanes(bool, ClientId),   // bool is json
+    ListClients(bool, ClientId), // bool is json
+    TargetedAction(Action, ActionTarget, ClientId),
+    SubscribeToPaneOutput(Vec<DataPaneId>, ClientId), // panes to stream, cli client id
+    CopyCommandOutput(u32, ClientId),                 // terminal id, cli client id
+    CommandExited(PaneId, Option<i32>, ClientId),     // Option<i32> is the exit status
 }
 
 impl From<&ScreenInstruction> for ScreenContext {
@@ -221,6 +260,11 @@ impl From<&ScreenInstruction> for ScreenContext {
             ScreenInstruction::HorizontalSplit(..) => ScreenContext::HorizontalSplit,
             ScreenInstruction::VerticalSplit(..) => ScreenContext::VerticalSplit,
             ScreenInstruction::WriteCharacter(..) => ScreenContext::WriteCharacter,
+            ScreenInstruction::WriteFromPlugin(..) => ScreenContext::WriteFromPlugin,
+            ScreenInstruction::WriteToPaneId(..) => ScreenContext::WriteToPaneId,
+            ScreenInstruction::DumpPaneContentsForPlugin(..) => {
+                ScreenContext::DumpPaneContentsForPlugin
+            },
             ScreenInstruction::ResizeLeft(..) => ScreenContext::ResizeLeft,
             ScreenInstruction::ResizeRight(..) => ScreenContext::ResizeRight,
             ScreenInstruction::ResizeDown(..) => ScreenContext::ResizeDown,
@@ -262,6 +306,10 @@ impl From<&ScreenInstruction> for ScreenContext {
             },
             ScreenInstruction::TogglePaneFrames => ScreenContext::TogglePaneFrames,
             ScreenInstruction::SetSelectable(..) => ScreenContext::SetSelectable,
+            ScreenInstruction::SetPaneTitle(..) => ScreenContext::SetPaneTitle,
+            ScreenInstruction::SetPaneBorderless(..) => ScreenContext::SetPaneBorderless,
+            ScreenInstruction::SetPanePreferredSize(..) => ScreenContext::SetPanePreferredSize,
+            ScreenInstruction::LaunchOrFocusPlugin(..) => ScreenContext::LaunchOrFocusPlugin,
             ScreenInstruction::ClosePane(..) => ScreenContext::ClosePane,
             ScreenInstruction::HoldPane(..) => ScreenContext::HoldPane,
             ScreenInstruction::UpdatePaneName(..) => ScreenContext::UpdatePaneName,
@@ -300,6 +348,7 @@ impl From<&ScreenInstruction> for ScreenContext {
             ScreenInstruction::MouseHoldLeft(..) => ScreenContext::MouseHoldLeft,
             ScreenInstruction::MouseHoldRight(..) => ScreenContext::MouseHoldRight,
             ScreenInstruction::MouseHoldMiddle(..) => ScreenContext::MouseHoldMiddle,
+            ScreenInstruction::MouseHover(..) => ScreenContext::MouseHover,
             ScreenInstruction::Copy(..) => ScreenContext::Copy,
             ScreenInstruction::ToggleTab(..) => ScreenContext::ToggleTab,
             ScreenInstruction::AddClient(..) => ScreenContext::AddClient,
@@ -308,6 +357,9 @@ impl From<&ScreenInstruction> for ScreenContext {
             ScreenInstruction::RemoveOverlay(..) => ScreenContext::RemoveOverlay,
             ScreenInstruction::ConfirmPrompt(..) => ScreenContext::ConfirmPrompt,
             ScreenInstruction::DenyPrompt(..) => ScreenContext::DenyPrompt,
+            ScreenInstruction::RequestPluginPermissions(..) => {
+                ScreenContext::RequestPluginPermissions
+            },
             ScreenInstruction::UpdateSearch(..) => ScreenContext::UpdateSearch,
             ScreenInstruction::SearchDown(..) => ScreenContext::SearchDown,
             ScreenInstruction::SearchUp(..) => ScreenContext::SearchUp,
@@ -316,10 +368,62 @@ impl From<&ScreenInstruction> for ScreenContext {
             },
             ScreenInstruction::SearchToggleWholeWord(..) => ScreenContext::SearchToggleWholeWord,
             ScreenInstruction::SearchToggleWrap(..) => ScreenContext::SearchToggleWrap,
+            ScreenInstruction::QuerySessionInfo(..) => ScreenContext::QuerySessionInfo,
+            ScreenInstruction::ListTabs(..) => ScreenContext::ListTabs,
+            ScreenInstruction::ListPanes(..) => ScreenContext::ListPanes,
+            ScreenInstruction::ListClients(..) => ScreenContext::ListClients,
+            ScreenInstruction::TargetedAction(..) => ScreenContext::TargetedAction,
+            ScreenInstruction::SubscribeToPaneOutput(..) => ScreenContext::SubscribeToPaneOutput,
+            ScreenInstruction::CopyCommandOutput(..) => ScreenContext::CopyCommandOutput,
+            ScreenInstruction::CommandExited(..) => ScreenContext::CommandExited,
         }
     }
 }
 
+/// Whether the instruction can change the panes reported to plugins in a `PaneUpdate`, on top of
+/// those changing the tabs (see [`Screen::update_tabs`]). Output and rendering can't, which keeps
+/// the reporting off the hot path.
+fn changes_pane_state(instruction: &ScreenInstruction) -> bool {
+    matches!(
+        instruction,
+        ScreenInstruction::ResizeLeft(..)
+            | ScreenInstruction::ResizeRight(..)
+            | ScreenInstruction::ResizeDown(..)
+            | ScreenInstruction::ResizeUp(..)
+            | ScreenInstruction::ResizeIncrease(..)
+            | ScreenInstruction::ResizeDecrease(..)
+            | ScreenInstruction::SwitchFocus(..)
+            | ScreenInstruction::FocusNextPane(..)
+            | ScreenInstruction::FocusPreviousPane(..)
+            | ScreenInstruction::MoveFocusLeft(..)
+            | ScreenInstruction::MoveFocusLeftOrPreviousTab(..)
+            | ScreenInstruction::MoveFocusDown(..)
+            | ScreenInstruction::MoveFocusUp(..)
+            | ScreenInstruction::MoveFocusRight(..)
+            | ScreenInstruction::MoveFocusRightOrNextTab(..)
+            | ScreenInstruction::MovePane(..)
+            | ScreenInstruction::MovePaneUp(..)
+            | ScreenInstruction::MovePaneDown(..)
+            | ScreenInstruction::MovePaneRight(..)
+            | ScreenInstruction::MovePaneLeft(..)
+            | ScreenInstruction::TogglePaneFrames
+            | ScreenInstruction::SetSelectable(..)
+            | ScreenInstruction::SetPaneTitle(..)
+            | ScreenInstruction::SetPaneBorderless(..)
+            | ScreenInstruction::SetPanePreferredSize(..)
+            | ScreenInstruction::LaunchOrFocusPlugin(..)
+            | ScreenInstruction::ClosePane(..)
+            | ScreenInstruction::HoldPane(..)
+            | ScreenInstruction::UpdatePaneName(..)
+            | ScreenInstruction::UndoRenamePane(..)
+            | ScreenInstruction::TerminalResize(..)
+            | ScreenInstruction::LeftMouseRelease(..)
+            | ScreenInstruction::MouseHoldLeft(..)
+            | ScreenInstruction::TargetedAction(..)
+            | ScreenInstruction::CommandExited(..)
+    )
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct CopyOptions {
     pub command: Option<String>,
@@ -366,6 +470,9 @@ pub(crate) struct Screen {
     sixel_image_store: Rc<RefCell<SixelImageStore>>,
     /// The overlay that is drawn on top of [`Pane`]'s', [`Tab`]'s and the [`Screen`]
     overlay: OverlayWindow,
+    /// The prompts waiting for the one shown in the overlay to be answered, along with the
+    /// clients they are shown to
+    pending_prompts: VecDeque<(ClientId, Overlay)>,
     terminal_emulator_colors: Rc<RefCell<Palette>>,
     terminal_emulator_color_codes: Rc<RefCell<HashMap<usize, String>>>,
     connected_clients: Rc<RefCell<HashSet<ClientId>>>,
@@ -378,6 +485,15 @@ pub(crate) struct Screen {
     draw_pane_frames: bool,
     session_is_mirrored: bool,
     copy_options: CopyOptions,
+    /// The terminal panes whose output is streamed to cli clients (see `zellij subscribe`).
+    pane_output_subscriptions: HashMap<ClientId, HashSet<u32>>,
+    /// The bytes of a character split across reads of the pty of a subscribed pane, held back
+    /// until the rest of the character arrives
+    incomplete_pane_output: HashMap<u32, Vec<u8>>,
+    /// The cli clients the output of these terminal panes is copied to (see `zellij run --wait`).
+    command_output_clients: HashMap<u32, ClientId>,
+    /// Whether the panes changed since they were last sent to the plugins in a `PaneUpdate`
+    pane_state_changed: bool,
 }
 
 impl Screen {
@@ -403,6 +519,7 @@ impl Screen {
             active_tab_indices: BTreeMap::new(),
             tabs: BTreeMap::new(),
             overlay: OverlayWindow::default(),
+            pending_prompts: VecDeque::new(),
             terminal_emulator_colors: Rc::new(RefCell::new(Palette::default())),
             terminal_emulator_color_codes: Rc::new(RefCell::new(HashMap::new())),
             tab_history: BTreeMap::new(),
@@ -411,17 +528,10 @@ impl Screen {
             draw_pane_frames,
             session_is_mirrored,
             copy_options,
-        }
-    }
-
-    /// Returns the index where a new [`Tab`] should be created in this [`Screen`].
-    /// Currently, this is right after the last currently existing tab, or `0` if
-    /// no tabs exist in this screen yet.
-    fn get_new_tab_index(&self) -> usize {
-        if let Some(index) = self.tabs.keys().last() {
-            *index + 1
-        } else {
-            0
+            pane_output_subscriptions: HashMap::new(),
+            incomplete_pane_output: HashMap::new(),
+            command_output_clients: HashMap::new(),
+            pane_state_changed: false,
         }
     }
 
@@ -796,6 +906,18 @@ impl Screen {
         }
     }
 
+    /// Remembers the command a new terminal pane runs, so that it's reported to plugins before
+    /// the command exits.
+    pub fn set_pane_invoked_with(&mut self, pid: PaneId, run_command: Option<RunCommand>) {
+        if let Some(tab) = self
+            .tabs
+            .values_mut()
+            .find(|tab| tab.has_pane_with_pid(&pid))
+        {
+            tab.set_pane_invoked_with(pid, run_command);
+        }
+    }
+
     /// Returns a mutable reference to this [`Screen`]'s active [`Overlays`].
     pub fn get_active_overlays_mut(&mut self) -> &mut Vec<Overlay> {
         &mut self.overlay.overlay_stack
@@ -806,10 +928,11 @@ impl Screen {
         self.get_tabs_mut().get_mut(&tab_index)
     }
 
-    /// Creates a new [`Tab`] in this [`Screen`], applying the specified [`Layout`]
-    /// and switching to it.
+    /// Creates a new [`Tab`] with the index `tab_index` (chosen by the pty thread, which spawns its
+    /// terminals) in this [`Screen`], applying the specified [`Layout`] and switching to it.
     pub fn new_tab(
         &mut self,
+        tab_index: usize,
         layout: PaneLayout,
         new_ids: Vec<u32>,
         client_id: ClientId,
@@ -822,7 +945,6 @@ impl Screen {
             client_id
         };
         let err_context = || format!("failed to create new tab for client {client_id:?}",);
-        let tab_index = self.get_new_tab_index();
         let position = self.tabs.len();
         let mut tab = Tab::new(
             tab_index,
@@ -934,10 +1056,98 @@ impl Screen {
             self.tab_history.remove(&client_id);
         }
         self.connected_clients.borrow_mut().remove(&client_id);
+        self.pane_output_subscriptions.remove(&client_id);
+        let subscriptions = &self.pane_output_subscriptions;
+        self.incomplete_pane_output.retain(|pid, _| {
+            subscriptions
+                .values()
+                .any(|terminal_ids| terminal_ids.contains(pid))
+        });
+        self.command_output_clients
+            .retain(|_, output_client_id| *output_client_id != client_id);
         self.update_tabs().with_context(err_context)
     }
 
-    pub fn update_tabs(&self) -> Result<()> {
+    pub fn subscribe_to_pane_output(&mut self, pane_ids: Vec<DataPaneId>, client_id: ClientId) {
+        let terminal_ids = pane_ids
+            .into_iter()
+            .filter_map(|pane_id| match pane_id {
+                DataPaneId::Terminal(id) => Some(id),
+                DataPaneId::Plugin(_) => None,
+            })
+            .collect();
+        self.pane_output_subscriptions
+            .insert(client_id, terminal_ids);
+    }
+
+    /// Sends the bytes a terminal pane received as a line of JSON to the cli clients subscribed to
+    /// its output, and as is to the cli client waiting for its command.
+    fn send_pane_output_to_subscribers(&mut self, pid: u32, vte_bytes: &[u8]) -> Result<()> {
+        let subscribers: Vec<ClientId> = self
+            .pane_output_subscriptions
+            .iter()
+            .filter(|(_, terminal_ids)| terminal_ids.contains(&pid))
+            .map(|(client_id, _)| *client_id)
+            .collect();
+        if !subscribers.is_empty() {
+            let mut bytes = self.incomplete_pane_output.remove(&pid).unwrap_or_default();
+            bytes.extend_from_slice(vte_bytes);
+            let complete_len = bytes.len() - incomplete_utf8_suffix_len(&bytes);
+            let incomplete = bytes.split_off(complete_len);
+            if !incomplete.is_empty() {
+                self.incomplete_pane_output.insert(pid, incomplete);
+            }
+            if !bytes.is_empty() {
+                let line = serde_json::json!({
+                    "PaneOutput": {
+                        "pane_id": pid,
+                        "output": String::from_utf8_lossy(&bytes),
+                    }
+                })
+                .to_string();
+                for client_id in subscribers {
+                    self.bus
+                        .senders
+                        .send_to_server(ServerInstruction::SubscriptionEvent(
+                            client_id,
+                            line.clone(),
+                        ))
+                        .with_context(|| {
+                            format!("failed to send output of pane {pid} to subscribers")
+                        })?;
+                }
+            }
+        }
+        if let Some(client_id) = self.command_output_clients.get(&pid) {
+            self.bus
+                .senders
+                .send_to_server(ServerInstruction::CommandOutput(
+                    *client_id,
+                    vte_bytes.to_vec(),
+                ))
+                .with_context(|| format!("failed to copy output of pane {pid}"))?;
+        }
+        Ok(())
+    }
+
+    /// Reports the exit status of a command to the cli client waiting for it.
+    fn report_command_exit(
+        &mut self,
+        pane_id: PaneId,
+        exit_status: Option<i32>,
+        client_id: ClientId,
+    ) -> Result<()> {
+        if let PaneId::Terminal(terminal_id) = pane_id {
+            self.command_output_clients.remove(&terminal_id);
+        }
+        self.bus
+            .senders
+            .send_to_server(ServerInstruction::CommandExited(client_id, exit_status))
+            .with_context(|| format!("failed to report the exit of the command in {pane_id:?}"))
+    }
+
+    pub fn update_tabs(&mut self) -> Result<()> {
+        self.pane_state_changed = true;
         for (client_id, active_tab_index) in self.active_tab_indices.iter() {
             let mut tab_data = vec![];
             for tab in self.tabs.values() {
@@ -976,6 +1186,27 @@ impl Screen {
         Ok(())
     }
 
+    /// Sends the panes of every tab to the plugins if they changed since the last time. Called
+    /// once after every instruction the screen thread handles.
+    pub fn report_pane_state(&mut self) -> Result<()> {
+        if !self.pane_state_changed {
+            return Ok(());
+        }
+        self.pane_state_changed = false;
+        let mut pane_manifest = PaneManifest::default();
+        for tab in self.tabs.values() {
+            pane_manifest.panes.insert(tab.position, tab.pane_infos());
+        }
+        self.bus
+            .senders
+            .send_to_plugin(PluginInstruction::Update(
+                None,
+                None,
+                Event::PaneUpdate(pane_manifest),
+            ))
+            .context("failed to update panes")
+    }
+
     pub fn update_active_tab_name(&mut self, buf: Vec<u8>, client_id: ClientId) -> Result<()> {
         let client_id = if self.get_active_tab(client_id).is_some() {
             Some(client_id)
@@ -983,35 +1214,41 @@ impl Screen {
             self.get_first_client_id()
         };
         match client_id {
-            Some(client_id) => {
-                let s = str::from_utf8(&buf)
-                    .with_context(|| format!("failed to construct tab name from buf: {buf:?}"))?;
-                if let Some(active_tab) = self.get_active_tab_mut(client_id) {
-                    match s {
-                        "\0" => {
-                            active_tab.name = String::new();
-                        },
-                        "\u{007F}" | "\u{0008}" => {
-                            // delete and backspace keys
-                            active_tab.name.pop();
-                        },
-                        c => {
-                            // It only allows printable unicode
-                            if buf.iter().all(|u| matches!(u, 0x20..=0x7E)) {
-                                active_tab.name.push_str(c);
-                            }
-                        },
-                    }
-                    self.update_tabs()
-                        .context("failed to update active tabs name for client id: {client_id:?}")
-                } else {
+            Some(client_id) => match self.active_tab_indices.get(&client_id) {
+                Some(&tab_index) => self
+                    .update_tab_name(tab_index, buf)
+                    .context("failed to update active tabs name for client id: {client_id:?}"),
+                None => {
                     log::error!("Active tab not found for client id: {client_id:?}");
                     Ok(())
-                }
+                },
             },
             None => Ok(()),
         }
     }
+    fn update_tab_name(&mut self, tab_index: usize, buf: Vec<u8>) -> Result<()> {
+        let err_context = || format!("failed to update name of tab at index {tab_index}");
+
+        let s = str::from_utf8(&buf)
+            .with_context(|| format!("failed to construct tab name from buf: {buf:?}"))?;
+        let tab = self.tabs.get_mut(&tab_index).with_context(err_context)?;
+        match s {
+            "\0" => {
+                tab.name = String::new();
+            },
+            "\u{007F}" | "\u{0008}" => {
+                // delete and backspace keys
+                tab.name.pop();
+            },
+            c => {
+                // It only allows printable unicode
+                if buf.iter().all(|u| matches!(u, 0x20..=0x7E)) {
+                    tab.name.push_str(c);
+                }
+            },
+        }
+        self.update_tabs().with_context(err_context)
+    }
     pub fn undo_active_rename_tab(&mut self, client_id: ClientId) -> Result<()> {
         let client_id = if self.get_active_tab(client_id).is_some() {
             Some(client_id)
@@ -1020,20 +1257,157 @@ impl Screen {
         };
         match client_id {
             Some(client_id) => {
-                if let Some(active_tab) = self.get_active_tab_mut(client_id) {
-                    if active_tab.name != active_tab.prev_name {
-                        active_tab.name = active_tab.prev_name.clone();
-                        self.update_tabs()
-                            .context("failed to undo renaming of active tab")?;
-                    }
-                } else {
-                    log::error!("Active tab not found for client id: {client_id:?}");
+                match self.active_tab_indices.get(&client_id) {
+                    Some(&tab_index) => self
+                        .undo_rename_tab(tab_index)
+                        .context("failed to undo renaming of active tab")?,
+                    None => log::error!("Active tab not found for client id: {client_id:?}"),
                 }
                 Ok(())
             },
             None => Ok(()),
         }
     }
+    fn undo_rename_tab(&mut self, tab_index: usize) -> Result<()> {
+        let tab = self
+            .tabs
+            .get_mut(&tab_index)
+            .with_context(|| format!("failed to undo renaming of tab at index {tab_index}"))?;
+        if tab.name != tab.prev_name {
+            tab.name = tab.prev_name.clone();
+            self.update_tabs()
+                .with_context(|| format!("failed to undo renaming of tab at index {tab_index}"))?;
+        }
+        Ok(())
+    }
+    /// Finds the tab and pane that an action sent from the command line should run against. When
+    /// only a tab is given, its focused pane is used.
+    fn resolve_action_target(&self, target: &ActionTarget) -> Result<(usize, PaneId)> {
+        let err_context = || format!("failed to find the pane or tab of {target:?}");
+
+        let tab_index = match &target.tab {
+            Some(TabSelector::Position(position)) => Some(
+                self.tabs
+                    .values()
+                    .find(|tab| tab.position + 1 == *position)
+                    .map(|tab| tab.index)
+                    .ok_or_else(|| anyhow!("there is no tab at position {position}"))
+                    .with_context(err_context)?,
+            ),
+            Some(TabSelector::Index(index)) => Some(
+                self.tabs
+                    .get(index)
+                    .map(|tab| tab.index)
+                    .ok_or_else(|| anyhow!("there is no tab with the index {index}"))
+                    .with_context(err_context)?,
+            ),
+            Some(TabSelector::Name(name)) => Some(
+                self.tabs
+                    .values()
+                    .find(|tab| &tab.name == name)
+                    .map(|tab| tab.index)
+                    .ok_or_else(|| anyhow!("there is no tab named {name:?}"))
+                    .with_context(err_context)?,
+            ),
+            None => None,
+        };
+        match (target.pane_id, tab_index) {
+            (Some(pane_id), tab_index) => {
+                let tab = self
+                    .tabs
+                    .values()
+                    .filter(|tab| tab_index.map(|index| index == tab.index).unwrap_or(true))
+                    .find(|tab| tab.has_pane_with_pid(&PaneId::from(pane_id)))
+                    .ok_or_else(|| match tab_index {
+                        Some(_) => anyhow!("the tab has no pane {}", format_pane_id(pane_id)),
+                        None => anyhow!("there is no pane {}", format_pane_id(pane_id)),
+                    })
+                    .with_context(err_context)?;
+                Ok((tab.index, PaneId::from(pane_id)))
+            },
+            (None, Some(tab_index)) => {
+                let tab = self.tabs.get(&tab_index).with_context(err_context)?;
+                let pane_id = self
+                    .get_first_client_id()
+                    .and_then(|client_id| tab.get_active_pane_id(client_id))
+                    .or_else(|| tab.get_tiled_pane_ids().into_iter().min())
+                    .with_context(err_context)?;
+                Ok((tab_index, pane_id))
+            },
+            (None, None) => {
+                Err(anyhow!("no pane or tab to run the action on")).with_context(err_context)
+            },
+        }
+    }
+    /// Runs an action sent from the command line against a pane or tab other than the focused
+    /// one, see [`Action::is_targetable`] for the supported actions
+    pub fn run_targeted_action(
+        &mut self,
+        action: Action,
+        target: &ActionTarget,
+        client_id: ClientId,
+    ) -> Result<()> {
+        let err_context = || format!("failed to run action on {target:?}");
+
+        let (tab_index, pane_id) = self
+            .resolve_action_target(target)
+            .with_context(err_context)?;
+        match action {
+            Action::CloseTab => {
+                return self.close_tab_at_index(tab_index).with_context(err_context)
+            },
+            Action::TabNameInput(buf) => {
+                return self
+                    .update_tab_name(tab_index, buf)
+                    .with_context(err_context)
+            },
+            Action::UndoRenameTab => {
+                return self.undo_rename_tab(tab_index).with_context(err_context)
+            },
+            _ => {},
+        }
+        let tab = self.tabs.get_mut(&tab_index).with_context(err_context)?;
+        match action {
+            Action::Write(bytes) => tab.write_to_pane_id(bytes, pane_id)?,
+            Action::WriteChars(chars) => tab.write_to_pane_id(chars.into_bytes(), pane_id)?,
+            Action::Resize(direction) => tab.resize_pane_with_id(pane_id, &direction),
+            Action::DumpScreen(file, full) => {
+                tab.dump_pane_with_id_to_file(pane_id, file, full, client_id)
+            },
+            Action::ScrollUp => {
+                tab.scroll_pane_with_id_up(pane_id, ScrollAmount::Line, client_id)?
+            },
+            Action::ScrollDown => {
+                tab.scroll_pane_with_id_down(pane_id, ScrollAmount::Line, client_id)?
+            },
+            Action::PageScrollUp => {
+                tab.scroll_pane_with_id_up(pane_id, ScrollAmount::Page, client_id)?
+            },
+            Action::PageScrollDown => {
+                tab.scroll_pane_with_id_down(pane_id, ScrollAmount::Page, client_id)?
+            },
+            Action::HalfPageScrollUp => {
+                tab.scroll_pane_with_id_up(pane_id, ScrollAmount::HalfPage, client_id)?
+            },
+            Action::HalfPageScrollDown => {
+                tab.scroll_pane_with_id_down(pane_id, ScrollAmount::HalfPage, client_id)?
+            },
+            Action::ScrollToBottom => tab.scroll_pane_with_id_to_bottom(pane_id)?,
+            Action::CloseFocus => {
+                tab.close_pane_with_id(pane_id)?;
+                self.update_tabs().with_context(err_context)?;
+            },
+            Action::PaneNameInput(buf) => tab.update_pane_name_with_id(pane_id, buf)?,
+            Action::UndoRenamePane => tab.undo_rename_pane_with_id(pane_id)?,
+            action => {
+                return Err(anyhow!(
+                    "{action:?} can not be run on a specific pane or tab"
+                ))
+                .with_context(err_context)
+            },
+        }
+        Ok(())
+    }
 
     pub fn change_mode(&mut self, mode_info: ModeInfo, client_id: ClientId) -> Result<()> {
         let previous_mode = self
@@ -1067,6 +1441,13 @@ impl Screen {
             }
         }
 
+        let leaves_prompt =
+            previous_mode == InputMode::Prompt && mode_info.mode != InputMode::Prompt;
+        if leaves_prompt {
+            // a prompt left without answering it (eg. with Esc) is denied
+            self.deny_prompt().with_context(err_context)?;
+        }
+
         if mode_info.mode == InputMode::RenameTab {
             if let Some(active_tab) = self.get_active_tab_mut(client_id) {
                 active_tab.prev_name = active_tab.name.clone();
@@ -1089,6 +1470,75 @@ impl Screen {
             tab.change_mode_info(mode_info.clone(), client_id);
             tab.mark_active_pane_for_rerender(client_id);
         }
+        if leaves_prompt && self.get_active_overlays_mut().is_empty() {
+            self.show_next_prompt().with_context(err_context)?;
+        }
+        Ok(())
+    }
+    /// Removes the active prompt (if any), notifying whoever is waiting on it that it was denied.
+    pub fn deny_prompt(&mut self) -> Result<()> {
+        let overlay = self.get_active_overlays_mut().pop();
+        if let Some(instruction) = overlay.and_then(|o| o.prompt_deny()) {
+            self.bus
+                .senders
+                .send_to_server(*instruction)
+                .context("failed to deny prompt")?;
+        }
+        Ok(())
+    }
+    /// Asks the user (through a prompt shown to `client_id`) whether to grant `permissions` to the
+    /// plugin with the id `plugin_id`. The answer is sent back to the plugin thread.
+    pub fn request_plugin_permissions(
+        &mut self,
+        plugin_id: u32,
+        client_id: ClientId,
+        plugin_name: String,
+        permissions: Vec<PermissionType>,
+    ) -> Result<()> {
+        let err_context = || format!("failed to request permissions for plugin {plugin_name}");
+
+        // only one prompt can be shown at a time, the others wait for it to be answered
+        let overlay =
+            generate_plugin_permissions_prompt(plugin_id, client_id, &plugin_name, permissions);
+        self.pending_prompts.push_back((client_id, overlay));
+        if self.get_active_overlays_mut().is_empty() {
+            self.show_next_prompt().with_context(err_context)?;
+        }
+        Ok(())
+    }
+    /// Shows the first of the pending prompts (if any) and switches the client it is shown to into
+    /// the prompt mode.
+    fn show_next_prompt(&mut self) -> Result<()> {
+        let err_context = || "failed to show the next prompt";
+
+        let (client_id, overlay) = match self.pending_prompts.pop_front() {
+            Some(prompt) => prompt,
+            None => return Ok(()),
+        };
+        self.get_active_overlays_mut().push(overlay);
+
+        let mut mode_info = self
+            .mode_info
+            .get(&client_id)
+            .unwrap_or(&self.default_mode_info)
+            .clone();
+        mode_info.mode = InputMode::Prompt;
+        self.change_mode(mode_info.clone(), client_id)
+            .with_context(err_context)?;
+        self.bus
+            .senders
+            .send_to_plugin(PluginInstruction::Update(
+                None,
+                Some(client_id),
+                Event::ModeUpdate(mode_info),
+            ))
+            .with_context(err_context)?;
+        if let Some(os_input) = &mut self.bus.os_input {
+            let _ = os_input.send_to_client(
+                client_id,
+                ServerToClientMsg::SwitchToMode(InputMode::Prompt),
+            );
+        }
         Ok(())
     }
     pub fn change_mode_for_all_clients(&mut self, mode_info: ModeInfo) -> Result<()> {
@@ -1168,6 +1618,28 @@ impl Screen {
     }
 }
 
+/// The length of the character cut short at the end of these bytes, if any
+fn incomplete_utf8_suffix_len(bytes: &[u8]) -> usize {
+    // the first byte of a character tells its length, the bytes following it are all 0b10xxxxxx
+    for (continuation_bytes, &byte) in bytes.iter().rev().take(4).enumerate() {
+        if byte & 0b1100_0000 == 0b1000_0000 {
+            continue;
+        }
+        let char_len = match byte {
+            0b1100_0000..=0b1101_1111 => 2,
+            0b1110_0000..=0b1110_1111 => 3,
+            0b1111_0000..=0b1111_0111 => 4,
+            _ => 1,
+        };
+        return if char_len > continuation_bytes + 1 {
+            continuation_bytes + 1
+        } else {
+            0
+        };
+    }
+    0
+}
+
 // The box is here in order to make the
 // NewClient enum smaller
 #[allow(clippy::boxed_local)]
@@ -1208,9 +1680,15 @@ pub(crate) fn screen_thread_main(
             .recv()
             .context("failed to receive event on channel")?;
         err_ctx.add_call(ContextType::Screen((&event).into()));
+        if changes_pane_state(&event) {
+            screen.pane_state_changed = true;
+        }
 
         match event {
             ScreenInstruction::PtyBytes(pid, vte_bytes) => {
+                screen
+                    .send_pane_output_to_subscribers(pid, &vte_bytes)
+                    .non_fatal();
                 let all_tabs = screen.get_tabs_mut();
                 for tab in all_tabs.values_mut() {
                     if tab.has_terminal_pid(pid) {
@@ -1227,6 +1705,7 @@ pub(crate) fn screen_thread_main(
                 pid,
                 initial_pane_title,
                 should_float,
+                invoked_with,
                 client_or_tab_index,
             ) => {
                 match client_or_tab_index {
@@ -1246,6 +1725,7 @@ pub(crate) fn screen_thread_main(
                         }
                     },
                 };
+                screen.set_pane_invoked_with(pid, invoked_with);
                 screen.unblock_input()?;
                 screen.update_tabs()?;
 
@@ -1275,24 +1755,31 @@ pub(crate) fn screen_thread_main(
 
                 screen.render()?;
             },
-            ScreenInstruction::HorizontalSplit(pid, initial_pane_title, client_id) => {
+            ScreenInstruction::HorizontalSplit(
+                pid,
+                initial_pane_title,
+                invoked_with,
+                client_id,
+            ) => {
                 active_tab_and_connected_client_id!(
                     screen,
                     client_id,
                     |tab: &mut Tab, client_id: ClientId| tab.horizontal_split(pid, initial_pane_title, client_id),
                     ?
                 );
+                screen.set_pane_invoked_with(pid, invoked_with);
                 screen.unblock_input()?;
                 screen.update_tabs()?;
                 screen.render()?;
             },
-            ScreenInstruction::VerticalSplit(pid, initial_pane_title, client_id) => {
+            ScreenInstruction::VerticalSplit(pid, initial_pane_title, invoked_with, client_id) => {
                 active_tab_and_connected_client_id!(
                     screen,
                     client_id,
                     |tab: &mut Tab, client_id: ClientId| tab.vertical_split(pid, initial_pane_title, client_id),
                     ?
                 );
+                screen.set_pane_invoked_with(pid, invoked_with);
                 screen.unblock_input()?;
                 screen.update_tabs()?;
                 screen.render()?;
@@ -1310,6 +1797,48 @@ pub(crate) fn screen_thread_main(
                     ?
                 );
             },
+            ScreenInstruction::WriteFromPlugin(bytes, plugin_id, client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.write_from_plugin(bytes, plugin_id, client_id),
+                    ?
+                );
+            },
+            ScreenInstruction::WriteToPaneId(bytes, pane_id) => {
+                match screen
+                    .tabs
+                    .values_mut()
+                    .find(|tab| tab.has_pane_with_pid(&pane_id))
+                {
+                    Some(tab) => tab.write_to_pane_id(bytes, pane_id)?,
+                    None => log::error!("Could not find pane with id {:?} to write to", pane_id),
+                }
+            },
+            ScreenInstruction::DumpPaneContentsForPlugin(
+                pane_id,
+                full,
+                with_styles,
+                plugin_id,
+                client_id,
+            ) => {
+                let contents = screen
+                    .tabs
+                    .values_mut()
+                    .find_map(|tab| tab.dump_pane_contents(pane_id, client_id, full, with_styles));
+                match contents {
+                    Some(contents) => screen
+                        .bus
+                        .senders
+                        .send_to_plugin(PluginInstruction::Update(
+                            Some(plugin_id),
+                            Some(client_id),
+                            Event::PaneContents(pane_id.into(), contents),
+                        ))
+                        .context("failed to send pane contents to plugin")?,
+                    None => log::error!("Could not find pane with id {:?} to dump", pane_id),
+                }
+            },
             ScreenInstruction::ResizeLeft(client_id) => {
                 active_tab_and_connected_client_id!(
                     screen,
@@ -1626,6 +2155,71 @@ pub(crate) fn screen_thread_main(
 
                 screen.render()?;
             },
+            ScreenInstruction::SetPaneTitle(id, title) => {
+                match screen
+                    .tabs
+                    .values_mut()
+                    .find(|tab| tab.has_pane_with_pid(&id))
+                {
+                    Some(tab) => tab.set_pane_title(id, title),
+                    None => log::warn!("Could not find pane with id {:?} to set its title", id),
+                }
+                screen.render()?;
+            },
+            ScreenInstruction::SetPaneBorderless(id, borderless) => {
+                match screen
+                    .tabs
+                    .values_mut()
+                    .find(|tab| tab.has_pane_with_pid(&id))
+                {
+                    Some(tab) => tab.set_pane_borderless(id, borderless),
+                    None => log::warn!("Could not find pane with id {:?} to set its frame", id),
+                }
+                screen.render()?;
+            },
+            ScreenInstruction::SetPanePreferredSize(id, rows, cols) => {
+                match screen
+                    .tabs
+                    .values_mut()
+                    .find(|tab| tab.has_pane_with_pid(&id))
+                {
+                    Some(tab) => tab.resize_pane_to_preferred_size(id, rows, cols),
+                    None => log::warn!("Could not find pane with id {:?} to resize", id),
+                }
+                screen.render()?;
+            },
+            ScreenInstruction::LaunchOrFocusPlugin(run_plugin, should_float, client_id) => {
+                let existing_plugin_pane = screen.tabs.values().find_map(|tab| {
+                    tab.find_plugin_pane(&run_plugin.location)
+                        .map(|pane_id| (tab.position, pane_id))
+                });
+                match existing_plugin_pane {
+                    Some((tab_position, pane_id)) => {
+                        screen.go_to_tab(tab_position + 1, client_id)?;
+                        active_tab_and_connected_client_id!(
+                            screen,
+                            client_id,
+                            |tab: &mut Tab, client_id: ClientId| tab
+                                .focus_pane_with_id(pane_id, client_id)
+                        );
+                    },
+                    None => {
+                        active_tab_and_connected_client_id!(
+                            screen,
+                            client_id,
+                            |tab: &mut Tab, client_id: ClientId| tab.new_plugin_pane(
+                                run_plugin,
+                                should_float,
+                                client_id
+                            ),
+                            ?
+                        );
+                    },
+                }
+                screen.update_tabs()?;
+                screen.render()?;
+                screen.unblock_input()?;
+            },
             ScreenInstruction::ClosePane(id, client_id) => {
                 match client_id {
                     Some(client_id) => {
@@ -1716,8 +2310,8 @@ pub(crate) fn screen_thread_main(
                 screen.unblock_input()?;
                 screen.render()?;
             },
-            ScreenInstruction::NewTab(layout, new_pane_pids, client_id) => {
-                screen.new_tab(layout, new_pane_pids, client_id)?;
+            ScreenInstruction::NewTab(layout, new_pane_pids, tab_index, client_id) => {
+                screen.new_tab(tab_index, layout, new_pane_pids, client_id)?;
                 screen.unblock_input()?;
                 screen.render()?;
             },
@@ -1836,6 +2430,10 @@ pub(crate) fn screen_thread_main(
                     .handle_mouse_hold_middle(&point, client_id), ?);
                 screen.render()?;
             },
+            ScreenInstruction::MouseHover(point, client_id) => {
+                active_tab!(screen, client_id, |tab: &mut Tab| tab
+                    .handle_mouse_hover(&point, client_id), ?);
+            },
             ScreenInstruction::Copy(client_id) => {
                 active_tab!(screen, client_id, |tab: &mut Tab| tab
                     .copy_selection(client_id), ?);
@@ -1881,10 +2479,24 @@ pub(crate) fn screen_thread_main(
                 screen.unblock_input()?;
             },
             ScreenInstruction::DenyPrompt(_client_id) => {
-                screen.get_active_overlays_mut().pop();
+                screen.deny_prompt()?;
                 screen.render()?;
                 screen.unblock_input()?;
             },
+            ScreenInstruction::RequestPluginPermissions(
+                plugin_id,
+                client_id,
+                plugin_name,
+                permissions,
+            ) => {
+                screen.request_plugin_permissions(
+                    plugin_id,
+                    client_id,
+                    plugin_name,
+                    permissions,
+                )?;
+                screen.render()?;
+            },
             ScreenInstruction::UpdateSearch(c, client_id) => {
                 active_tab_and_connected_client_id!(
                     screen,
@@ -1938,7 +2550,121 @@ pub(crate) fn screen_thread_main(
                 screen.render()?;
                 screen.unblock_input()?;
             },
+            ScreenInstruction::QuerySessionInfo(client_id) => {
+                let session_info = SessionInfo {
+                    name: envs::get_session_name().unwrap_or_default(),
+                    tabs: screen.tabs.len(),
+                    connected_clients: screen.connected_clients.borrow().len(),
+                    is_current_session: false,
+                };
+                screen
+                    .bus
+                    .senders
+                    .send_to_server(ServerInstruction::SessionInfo(client_id, session_info))
+                    .context("failed to send session info to server")?;
+            },
+            ScreenInstruction::ListTabs(json, client_id) => {
+                let tabs: Vec<TabListing> = screen
+                    .tabs
+                    .values()
+                    .map(|tab| TabListing {
+                        position: tab.position + 1,
+                        name: tab.name.clone(),
+                        pane_count: tab.get_all_pane_ids().len(),
+                        is_fullscreen_active: tab.is_fullscreen_active(),
+                        is_sync_panes_active: tab.is_sync_panes_active(),
+                        are_floating_panes_visible: tab.are_floating_panes_visible(),
+                        focused_by_clients: screen
+                            .active_tab_indices
+                            .iter()
+                            .filter(|(_, tab_index)| **tab_index == tab.index)
+                            .map(|(client_id, _)| *client_id)
+                            .collect(),
+                    })
+                    .collect();
+                screen
+                    .bus
+                    .senders
+                    .send_to_server(ServerInstruction::QueryResponse(
+                        client_id,
+                        format_tabs(&tabs, json),
+                    ))
+                    .context("failed to list tabs")?;
+            },
+            ScreenInstruction::ListPanes(json, client_id) => {
+                let panes: Vec<PaneListing> = screen
+                    .tabs
+                    .values()
+                    .flat_map(|tab| tab.pane_listings())
+                    .collect();
+                // the pty thread knows the processes running in the panes, it replies to the client
+                screen
+                    .bus
+                    .senders
+                    .send_to_pty(PtyInstruction::ListPanes(panes, json, client_id))
+                    .context("failed to list panes")?;
+            },
+            ScreenInstruction::ListClients(json, client_id) => {
+                let mut clients: Vec<ClientListing> = screen
+                    .connected_clients
+                    .borrow()
+                    .iter()
+                    .map(|&connected_client_id| {
+                        let tab = screen
+                            .active_tab_indices
+                            .get(&connected_client_id)
+                            .and_then(|tab_index| screen.tabs.get(tab_index));
+                        let focused_pane_id =
+                            tab.and_then(|tab| tab.get_active_pane_id(connected_client_id));
+                        ClientListing {
+                            id: connected_client_id,
+                            tab_position: tab.map(|tab| tab.position + 1),
+                            focused_pane_id: focused_pane_id.map(|pane_id| match pane_id {
+                                PaneId::Terminal(id) | PaneId::Plugin(id) => id,
+                            }),
+                            focused_pane_is_plugin: matches!(
+                                focused_pane_id,
+                                Some(PaneId::Plugin(_))
+                            ),
+                        }
+                    })
+                    .collect();
+                clients.sort_by_key(|client| client.id);
+                screen
+                    .bus
+                    .senders
+                    .send_to_server(ServerInstruction::QueryResponse(
+                        client_id,
+                        format_clients(&clients, json),
+                    ))
+                    .context("failed to list clients")?;
+            },
+            ScreenInstruction::TargetedAction(action, target, client_id) => {
+                let result = screen.run_targeted_action(action, &target, client_id);
+                if let Err(e) = &result {
+                    // the cli client prints this and exits with an error
+                    let error = e.root_cause().to_string();
+                    screen
+                        .bus
+                        .senders
+                        .send_to_server(ServerInstruction::ActionFailed(client_id, error))
+                        .context("failed to report a failed action")?;
+                }
+                result.non_fatal();
+                screen.unblock_input()?;
+                screen.render()?;
+            },
+            ScreenInstruction::SubscribeToPaneOutput(pane_ids, client_id) => {
+                screen.subscribe_to_pane_output(pane_ids, client_id);
+            },
+            ScreenInstruction::CopyCommandOutput(terminal_id, client_id) => {
+                screen.command_output_clients.insert(terminal_id, client_id);
+            },
+            ScreenInstruction::CommandExited(pane_id, exit_status, client_id) => {
+                screen.report_command_exit(pane_id, exit_status, client_id)?;
+            },
         }
+        screen.report_pane_state()?;
     }
     Ok(())
 }
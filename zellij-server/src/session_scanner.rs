@@ -0,0 +1,69 @@
+//! Keeps plugins informed about the other sessions running on this machine.
+use std::{
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+use zellij_utils::{
+    data::{Event, SessionInfo},
+    envs,
+    errors::prelude::*,
+    sessions::get_sessions_info,
+};
+
+use crate::{thread_bus::ThreadSenders, wasm_vm::PluginInstruction};
+
+const SCAN_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Lists the running sessions, sorted by name
+fn scan_sessions() -> Vec<SessionInfo> {
+    let current_session_name = envs::get_session_name().unwrap_or_default();
+    get_sessions_info()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|mut session_info| {
+            session_info.is_current_session = session_info.name == current_session_name;
+            session_info
+        })
+        .collect()
+}
+
+/// Sends an `Event::SessionUpdate` to all plugins whenever the running sessions change, for as long
+/// as it is kept around
+///
+/// Every scan queries the server of each session, so sessions only scan while their plugins or
+/// the cli clients streaming their events (see `zellij subscribe`) subscribe to the event.
+pub(crate) struct SessionScanner {
+    _stop: mpsc::Sender<()>, // the scanning thread stops once this is dropped
+}
+
+impl SessionScanner {
+    pub fn start(senders: ThreadSenders) -> Result<Self> {
+        let (stop, should_stop) = mpsc::channel::<()>();
+        thread::Builder::new()
+            .name("session_scanner".to_string())
+            .spawn(move || {
+                let mut last_sessions = None;
+                loop {
+                    let sessions = scan_sessions();
+                    if last_sessions.as_ref() != Some(&sessions) {
+                        let event = Event::SessionUpdate(sessions.clone());
+                        if senders
+                            .send_to_plugin(PluginInstruction::Update(None, None, event))
+                            .is_err()
+                        {
+                            break;
+                        }
+                        last_sessions = Some(sessions);
+                    }
+                    match should_stop.recv_timeout(SCAN_INTERVAL) {
+                        Err(RecvTimeoutError::Timeout) => {},
+                        _ => break,
+                    }
+                }
+            })
+            .context("failed to spawn the session scanner thread")?;
+        Ok(SessionScanner { _stop: stop })
+    }
+}
@@ -38,15 +38,24 @@ use std::{
     str,
 };
 use zellij_utils::{
-    data::{Event, InputMode, ModeInfo, Palette, PaletteColor, Style},
+    data::{Event, InputMode, ModeInfo, Palette, PaletteColor, PaneInfo, PaneListing, Style},
     input::{
+        actions::ResizeDirection,
         command::TerminalAction,
-        layout::{PaneLayout, Run},
+        layout::{PaneLayout, Run, RunPlugin, RunPluginLocation},
         parse_keys,
     },
     pane_size::{Offset, PaneGeom, Size, SizeInPixels, Viewport},
 };
 
+/// How far to scroll a pane that is not necessarily focused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollAmount {
+    Line,
+    HalfPage,
+    Page,
+}
+
 macro_rules! resize_pty {
     ($pane:expr, $os_input:expr) => {
         if let PaneId::Terminal(ref pid) = $pane.pid() {
@@ -168,6 +177,9 @@ pub trait Pane {
     fn dump_screen(&mut self, _client_id: ClientId, _full: bool) -> String {
         "".to_owned()
     }
+    fn dump_screen_with_styles(&mut self, _client_id: ClientId, _full: bool) -> String {
+        "".to_owned()
+    }
     fn scroll_up(&mut self, count: usize, client_id: ClientId);
     fn scroll_down(&mut self, count: usize, client_id: ClientId);
     fn clear_scroll(&mut self);
@@ -300,8 +312,14 @@ pub trait Pane {
     fn load_pane_name(&mut self);
     fn set_borderless(&mut self, borderless: bool);
     fn borderless(&self) -> bool;
+    // only plugins can set their own title, terminal panes get theirs from the terminal
+    fn set_title(&mut self, _title: String) {}
+    fn plugin_location(&self) -> Option<&RunPluginLocation> {
+        None
+    }
     // TODO: this should probably be merged with the mouse_right_click
     fn handle_right_click(&mut self, _to: &Position, _client_id: ClientId) {}
+    fn handle_mouse_hover(&mut self, _to: &Position, _client_id: ClientId) {}
     fn mouse_left_click(&self, _position: &Position, _is_held: bool) -> Option<String> {
         None
     }
@@ -357,6 +375,22 @@ pub trait Pane {
     fn hold(&mut self, _exit_status: Option<i32>, _run_command: RunCommand) {
         // No-op by default, only terminal panes support holding
     }
+    fn current_title(&self) -> String;
+    fn is_held(&self) -> bool {
+        false
+    }
+    fn exit_status(&self) -> Option<i32> {
+        None
+    }
+    fn held_command(&self) -> Option<RunCommand> {
+        None
+    }
+    fn invoked_with(&self) -> Option<RunCommand> {
+        None
+    }
+    fn set_invoked_with(&mut self, _run_command: Option<RunCommand>) {
+        // No-op by default, only terminal panes run commands
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -508,7 +542,7 @@ impl Tab {
                     // A plugin pane
                     if let Some(Run::Plugin(run)) = layout.run.clone() {
                         let (pid_tx, pid_rx) = channel();
-                        let pane_title = run.location.to_string();
+                        let location = run.location.clone();
                         self.senders
                             .send_to_plugin(PluginInstruction::Load(
                                 pid_tx, run, tab_index, client_id,
@@ -523,7 +557,7 @@ impl Tab {
                                 .as_ref()
                                 .with_context(err_context)?
                                 .clone(),
-                            pane_title,
+                            location,
                             layout.name.clone().unwrap_or_default(),
                         );
                         new_plugin.set_borderless(layout.borderless);
@@ -534,10 +568,13 @@ impl Tab {
                         // there are still panes left to fill, use the pids we received in this method
                         if let Some(pid) = new_ids.next() {
                             let next_terminal_position = self.get_next_terminal_position();
-                            let initial_title = match &layout.run {
-                                Some(Run::Command(run_command)) => Some(run_command.to_string()),
+                            let invoked_with = match &layout.run {
+                                Some(Run::Command(run_command)) => Some(run_command.clone()),
                                 _ => None,
                             };
+                            let initial_title = invoked_with
+                                .as_ref()
+                                .map(|run_command| run_command.to_string());
                             let mut new_pane = TerminalPane::new(
                                 *pid,
                                 *position_and_size,
@@ -552,6 +589,7 @@ impl Tab {
                                 initial_title,
                             );
                             new_pane.set_borderless(layout.borderless);
+                            new_pane.set_invoked_with(invoked_with);
                             self.tiled_panes.add_pane_with_existing_geom(
                                 PaneId::Terminal(*pid),
                                 Box::new(new_pane),
@@ -865,6 +903,94 @@ impl Tab {
         }
         Ok(())
     }
+    pub fn new_plugin_pane(
+        &mut self,
+        run: RunPlugin,
+        should_float: bool,
+        client_id: ClientId,
+    ) -> Result<()> {
+        let location = run.location.clone();
+        let err_context = || format!("failed to open plugin {} in a new pane", location);
+
+        let floating_pane_geom = if should_float {
+            self.floating_panes.toggle_show_panes(true);
+            match self.floating_panes.find_room_for_new_pane() {
+                Some(geom) => Some(geom),
+                None => {
+                    log::error!("No room for a new floating pane for {}", run.location);
+                    return Ok(());
+                },
+            }
+        } else {
+            self.floating_panes.toggle_show_panes(false);
+            if self.tiled_panes.fullscreen_is_active() {
+                self.tiled_panes.unset_fullscreen();
+            }
+            if !self.tiled_panes.has_room_for_new_pane() {
+                log::error!("No room for a new pane for {}", run.location);
+                return Ok(());
+            }
+            None
+        };
+
+        let (pid_tx, pid_rx) = channel();
+        self.senders
+            .send_to_plugin(PluginInstruction::Load(pid_tx, run, self.index, client_id))
+            .with_context(err_context)?;
+        let pid = pid_rx.recv().with_context(err_context)?;
+        let mut new_plugin = PluginPane::new(
+            pid,
+            floating_pane_geom.unwrap_or_default(), // the size of tiled panes is set later
+            self.senders
+                .to_plugin
+                .as_ref()
+                .with_context(err_context)?
+                .clone(),
+            location.clone(),
+            String::new(),
+        );
+        let pid = PaneId::Plugin(pid);
+        if floating_pane_geom.is_some() {
+            new_plugin.set_frame(true);
+            new_plugin.set_content_offset(Offset::frame(1)); // floating panes always have a frame
+            self.floating_panes.add_pane(pid, Box::new(new_plugin));
+            self.floating_panes.focus_pane_for_all_clients(pid);
+        } else {
+            self.tiled_panes.insert_pane(pid, Box::new(new_plugin));
+            self.tiled_panes.set_pane_frames(self.draw_pane_frames);
+            self.tiled_panes.focus_pane(pid, client_id);
+            self.should_clear_display_before_rendering = true;
+        }
+        self.set_force_render();
+        Ok(())
+    }
+    /// The id of the pane running the plugin with the given location, if there is one
+    ///
+    /// Only the location is compared: the `LaunchOrFocusPlugin` action carries no configuration,
+    /// so a running plugin is focused whatever it was configured with.
+    pub fn find_plugin_pane(&self, location: &RunPluginLocation) -> Option<PaneId> {
+        self.tiled_panes
+            .get_panes()
+            .chain(self.floating_panes.get_panes())
+            .find(|(_, pane)| pane.plugin_location() == Some(location))
+            .map(|(pane_id, _)| *pane_id)
+    }
+    pub fn focus_pane_with_id(&mut self, pane_id: PaneId, client_id: ClientId) {
+        if self.floating_panes.panes_contain(&pane_id) {
+            self.floating_panes.toggle_show_panes(true);
+            self.floating_panes.focus_pane(pane_id, client_id);
+        } else if self.tiled_panes.panes_contain(&pane_id) {
+            if self.tiled_panes.fullscreen_is_active() {
+                self.tiled_panes.unset_fullscreen();
+            }
+            self.floating_panes.toggle_show_panes(false);
+            self.tiled_panes.focus_pane(pane_id, client_id);
+        } else {
+            return;
+        }
+        self.set_pane_active_at(pane_id);
+        self.set_force_render();
+    }
     pub fn suppress_active_pane(&mut self, pid: PaneId, client_id: ClientId) -> Result<()> {
         // this method creates a new pane from pid and replaces it with the active pane
         // the active pane is then suppressed (hidden and not rendered) until the current
@@ -1041,12 +1167,12 @@ impl Tab {
         }
     }
     pub fn has_terminal_pid(&self, pid: u32) -> bool {
-        self.tiled_panes.panes_contain(&PaneId::Terminal(pid))
-            || self.floating_panes.panes_contain(&PaneId::Terminal(pid))
-            || self
-                .suppressed_panes
-                .values()
-                .any(|s_p| s_p.pid() == PaneId::Terminal(pid))
+        self.has_pane_with_pid(&PaneId::Terminal(pid))
+    }
+    pub fn has_pane_with_pid(&self, pid: &PaneId) -> bool {
+        self.tiled_panes.panes_contain(pid)
+            || self.floating_panes.panes_contain(pid)
+            || self.suppressed_panes.values().any(|s_p| s_p.pid() == *pid)
     }
     pub fn handle_pty_bytes(&mut self, pid: u32, bytes: VteBytes) -> Result<()> {
         let err_context = || format!("failed to handle pty bytes from fd {pid}");
@@ -1157,6 +1283,37 @@ impl Tab {
             .with_context(|| format!("failed to write to active terminal for client {client_id}"))
     }
 
+    pub fn write_from_plugin(
+        &mut self,
+        input_bytes: Vec<u8>,
+        plugin_id: u32,
+        client_id: ClientId,
+    ) -> Result<()> {
+        // a plugin writing to the focused pane(s) should never receive its own input back
+        let plugin_pane_id = PaneId::Plugin(plugin_id);
+        if self.is_sync_panes_active() {
+            let pane_ids = self.get_static_and_floating_pane_ids();
+            for pane_id in pane_ids.into_iter().filter(|p| *p != plugin_pane_id) {
+                self.write_to_pane_id(input_bytes.clone(), pane_id)
+                    .with_context(|| format!("failed to write from plugin {plugin_id}"))?;
+            }
+            return Ok(());
+        }
+        let active_pane_id = if self.floating_panes.panes_are_visible() {
+            self.floating_panes
+                .get_active_pane_id(client_id)
+                .or_else(|| self.tiled_panes.get_active_pane_id(client_id))
+        } else {
+            self.tiled_panes.get_active_pane_id(client_id)
+        };
+        if active_pane_id == Some(plugin_pane_id) {
+            log::warn!("Plugin {plugin_id} tried to write to its own pane, ignoring");
+            return Ok(());
+        }
+        self.write_to_active_terminal(input_bytes, client_id)
+            .with_context(|| format!("failed to write from plugin {plugin_id}"))
+    }
+
     pub fn write_to_terminal_at(
         &mut self,
         input_bytes: Vec<u8>,
@@ -1687,6 +1844,96 @@ impl Tab {
         // this is here just as a naming thing to make things more explicit
         self.get_static_and_floating_pane_ids()
     }
+    pub fn pane_infos(&self) -> Vec<PaneInfo> {
+        let focused_pane_ids: HashSet<PaneId> = self
+            .connected_clients
+            .borrow()
+            .iter()
+            .filter_map(|client_id| self.get_active_pane_id(*client_id))
+            .collect();
+        let tiled_panes = self
+            .tiled_panes
+            .get_panes()
+            .map(|(_, pane)| (pane, false, false));
+        let floating_panes = self
+            .floating_panes
+            .get_panes()
+            .map(|(_, pane)| (pane, true, false));
+        let suppressed_panes = self
+            .suppressed_panes
+            .values()
+            .map(|pane| (pane, false, true));
+        tiled_panes
+            .chain(floating_panes)
+            .chain(suppressed_panes)
+            .map(|(pane, is_floating, is_suppressed)| {
+                let (id, is_plugin) = match pane.pid() {
+                    PaneId::Terminal(id) => (id, false),
+                    PaneId::Plugin(id) => (id, true),
+                };
+                PaneInfo {
+                    id,
+                    is_plugin,
+                    is_focused: focused_pane_ids.contains(&pane.pid()),
+                    is_floating,
+                    is_suppressed,
+                    is_selectable: pane.selectable(),
+                    title: pane.current_title(),
+                    exited: pane.is_held(),
+                    exit_status: pane.exit_status(),
+                    terminal_command: pane
+                        .held_command()
+                        .or_else(|| pane.invoked_with())
+                        .map(|run_command| run_command.to_string()),
+                    pane_x: pane.x(),
+                    pane_content_x: pane.get_content_x(),
+                    pane_y: pane.y(),
+                    pane_content_y: pane.get_content_y(),
+                    pane_rows: pane.rows(),
+                    pane_content_rows: pane.get_content_rows(),
+                    pane_columns: pane.cols(),
+                    pane_content_columns: pane.get_content_columns(),
+                }
+            })
+            .collect()
+    }
+    /// The panes of this tab as listed by `zellij action list-panes`, the pty thread fills in the
+    /// working directory and running command of terminal panes
+    pub fn pane_listings(&self) -> Vec<PaneListing> {
+        self.pane_infos()
+            .into_iter()
+            .map(|pane_info| {
+                let command = if pane_info.is_plugin {
+                    let pane_id = PaneId::Plugin(pane_info.id);
+                    self.tiled_panes
+                        .get_pane(pane_id)
+                        .or_else(|| self.floating_panes.get_pane(pane_id))
+                        .or_else(|| self.suppressed_panes.get(&pane_id))
+                        .and_then(|pane| pane.plugin_location())
+                        .map(|location| location.to_string())
+                } else {
+                    pane_info.terminal_command
+                };
+                PaneListing {
+                    id: pane_info.id,
+                    is_plugin: pane_info.is_plugin,
+                    tab_position: self.position + 1,
+                    title: pane_info.title,
+                    is_focused: pane_info.is_focused,
+                    is_floating: pane_info.is_floating,
+                    is_suppressed: pane_info.is_suppressed,
+                    exited: pane_info.exited,
+                    exit_status: pane_info.exit_status,
+                    x: pane_info.pane_x,
+                    y: pane_info.pane_y,
+                    rows: pane_info.pane_rows,
+                    columns: pane_info.pane_columns,
+                    command,
+                    cwd: None,
+                }
+            })
+            .collect()
+    }
     pub fn get_static_and_floating_pane_ids(&self) -> Vec<PaneId> {
         self.tiled_panes
             .pane_ids()
@@ -1713,6 +1960,44 @@ impl Tab {
             }
         }
     }
+    pub fn set_pane_title(&mut self, id: PaneId, title: String) {
+        let pane = self
+            .floating_panes
+            .get_mut(&id)
+            .or_else(|| self.tiled_panes.get_pane_mut(id))
+            .or_else(|| self.suppressed_panes.get_mut(&id));
+        if let Some(pane) = pane {
+            pane.set_title(title);
+        }
+    }
+    pub fn set_pane_borderless(&mut self, id: PaneId, borderless: bool) {
+        let pane = self
+            .floating_panes
+            .get_mut(&id)
+            .or_else(|| self.tiled_panes.get_pane_mut(id))
+            .or_else(|| self.suppressed_panes.get_mut(&id));
+        if let Some(pane) = pane {
+            pane.set_borderless(borderless);
+            if borderless {
+                // the panes only take care of the frames of panes that aren't borderless
+                pane.set_frame(false);
+            }
+        }
+        self.tiled_panes.set_pane_frames(self.draw_pane_frames);
+        self.floating_panes.set_pane_frames(&mut self.os_api);
+        self.should_clear_display_before_rendering = true;
+        self.set_force_render();
+    }
+    pub fn resize_pane_to_preferred_size(&mut self, id: PaneId, rows: usize, cols: usize) {
+        if self.floating_panes.panes_contain(&id) {
+            self.floating_panes
+                .resize_pane_to(id, rows, cols, &mut self.os_api);
+            self.set_force_render();
+        } else {
+            // tiled panes get their size from the layout
+            log::debug!("Only floating panes can be resized to a preferred size");
+        }
+    }
     pub fn close_pane(
         &mut self,
         id: PaneId,
@@ -1753,6 +2038,12 @@ impl Tab {
             self.tiled_panes.hold_pane(id, exit_status, run_command);
         }
     }
+    /// Remember the command a terminal pane was opened with, so it's reported while it runs
+    pub fn set_pane_invoked_with(&mut self, id: PaneId, run_command: Option<RunCommand>) {
+        if let Some(pane) = self.get_pane_with_id_mut(id) {
+            pane.set_invoked_with(run_command);
+        }
+    }
     pub fn replace_pane_with_suppressed_pane(&mut self, pane_id: PaneId) -> Option<Box<dyn Pane>> {
         self.suppressed_panes
             .remove(&pane_id)
@@ -1814,6 +2105,24 @@ impl Tab {
             self.os_api.write_to_file(dump, file);
         }
     }
+    pub fn dump_pane_contents(
+        &mut self,
+        pane_id: PaneId,
+        client_id: ClientId,
+        full: bool,
+        with_styles: bool,
+    ) -> Option<String> {
+        let pane = self
+            .floating_panes
+            .get_mut(&pane_id)
+            .or_else(|| self.tiled_panes.get_pane_mut(pane_id))
+            .or_else(|| self.suppressed_panes.get_mut(&pane_id))?;
+        if with_styles {
+            Some(pane.dump_screen_with_styles(client_id, full))
+        } else {
+            Some(pane.dump_screen(client_id, full))
+        }
+    }
     pub fn edit_scrollback(&mut self, client_id: ClientId) -> Result<()> {
         let mut file = temp_dir();
         file.push(format!("{}.dump", Uuid::new_v4()));
@@ -1937,6 +2246,123 @@ impl Tab {
         Ok(())
     }
 
+    fn get_pane_with_id_mut(&mut self, pane_id: PaneId) -> Option<&mut Box<dyn Pane>> {
+        self.floating_panes
+            .get_mut(&pane_id)
+            .or_else(|| self.tiled_panes.get_pane_mut(pane_id))
+            .or_else(|| self.suppressed_panes.get_mut(&pane_id))
+    }
+
+    pub fn scroll_pane_with_id_up(
+        &mut self,
+        pane_id: PaneId,
+        amount: ScrollAmount,
+        client_id: ClientId,
+    ) -> Result<()> {
+        let pane = self
+            .get_pane_with_id_mut(pane_id)
+            .with_context(|| format!("failed to scroll up pane with id {pane_id:?}"))?;
+        // prevent overflow when row == 0
+        let scroll_rows = match amount {
+            ScrollAmount::Line => 1,
+            ScrollAmount::HalfPage => (pane.rows().max(1) - 1) / 2,
+            ScrollAmount::Page => pane.rows().max(1) - 1,
+        };
+        pane.scroll_up(scroll_rows, client_id);
+        Ok(())
+    }
+
+    pub fn scroll_pane_with_id_down(
+        &mut self,
+        pane_id: PaneId,
+        amount: ScrollAmount,
+        client_id: ClientId,
+    ) -> Result<()> {
+        let err_context = || format!("failed to scroll down pane with id {pane_id:?}");
+
+        let pane = self
+            .get_pane_with_id_mut(pane_id)
+            .with_context(err_context)?;
+        let scroll_rows = match amount {
+            ScrollAmount::Line => 1,
+            ScrollAmount::HalfPage => (pane.rows().max(1) - 1) / 2,
+            ScrollAmount::Page => pane.get_content_rows(),
+        };
+        pane.scroll_down(scroll_rows, client_id);
+        if !pane.is_scrolled() {
+            if let PaneId::Terminal(raw_fd) = pane_id {
+                self.process_pending_vte_events(raw_fd)
+                    .with_context(err_context)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn scroll_pane_with_id_to_bottom(&mut self, pane_id: PaneId) -> Result<()> {
+        let err_context = || format!("failed to scroll to bottom in pane with id {pane_id:?}");
+
+        let pane = self
+            .get_pane_with_id_mut(pane_id)
+            .with_context(err_context)?;
+        pane.clear_scroll();
+        if let PaneId::Terminal(raw_fd) = pane_id {
+            self.process_pending_vte_events(raw_fd)
+                .with_context(err_context)?;
+        }
+        Ok(())
+    }
+
+    pub fn resize_pane_with_id(&mut self, pane_id: PaneId, direction: &ResizeDirection) {
+        if self.floating_panes.panes_contain(&pane_id) {
+            self.floating_panes
+                .resize_pane_with_id(pane_id, direction, &mut self.os_api);
+            self.set_force_render(); // make sure the panes under the floating pane don't leave "garbage" in case of a decrease
+        } else if self.tiled_panes.panes_contain(&pane_id) {
+            self.tiled_panes.resize_pane_with_id(pane_id, direction);
+        }
+    }
+
+    pub fn dump_pane_with_id_to_file(
+        &mut self,
+        pane_id: PaneId,
+        file: String,
+        full: bool,
+        client_id: ClientId,
+    ) {
+        if let Some(dump) = self.dump_pane_contents(pane_id, client_id, full, false) {
+            self.os_api.write_to_file(dump, Some(file));
+        }
+    }
+
+    pub fn close_pane_with_id(&mut self, pane_id: PaneId) -> Result<()> {
+        self.close_pane(pane_id, false);
+        self.senders
+            .send_to_pty(PtyInstruction::ClosePane(pane_id))
+            .with_context(|| format!("failed to close pane with id {pane_id:?}"))
+    }
+
+    pub fn update_pane_name_with_id(&mut self, pane_id: PaneId, buf: Vec<u8>) -> Result<()> {
+        let err_context = || format!("failed to update name of pane {pane_id:?} to '{buf:?}'");
+
+        let pane = self
+            .get_pane_with_id_mut(pane_id)
+            .with_context(err_context)?;
+        // It only allows printable unicode, delete and backspace keys.
+        let is_updatable = buf.iter().all(|u| matches!(u, 0x20..=0x7E | 0x08 | 0x7F));
+        if is_updatable {
+            let s = str::from_utf8(&buf).with_context(err_context)?;
+            pane.update_name(s);
+        }
+        Ok(())
+    }
+
+    pub fn undo_rename_pane_with_id(&mut self, pane_id: PaneId) -> Result<()> {
+        self.get_pane_with_id_mut(pane_id)
+            .with_context(|| format!("failed to undo rename of pane {pane_id:?}"))?
+            .load_pane_name();
+        Ok(())
+    }
+
     pub fn handle_scrollwheel_up(
         &mut self,
         point: &Position,
@@ -2447,6 +2873,23 @@ impl Tab {
         Ok(false) // we shouldn't even get here, but might as well not needlessly render if we do
     }
 
+    pub fn handle_mouse_hover(&mut self, position: &Position, client_id: ClientId) -> Result<()> {
+        let err_context = || {
+            format!("failed to handle mouse hover at position {position:?} for client {client_id}")
+        };
+
+        if let Some(pane) = self
+            .get_pane_at(position, false)
+            .with_context(err_context)?
+        {
+            if !pane.position_is_on_frame(position) {
+                let relative_position = pane.relative_position(position);
+                pane.handle_mouse_hover(&relative_position, client_id);
+            }
+        }
+        Ok(())
+    }
+
     pub fn copy_selection(&self, client_id: ClientId) -> Result<()> {
         let selected_text = self
             .get_active_pane(client_id)
@@ -49,6 +49,7 @@ impl ServerOsApi for FakeInputOutput {
         _file_to_open: TerminalAction,
         _quit_db: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>,
         _default_editor: Option<PathBuf>,
+        _tab_index: Option<usize>,
     ) -> Result<(u32, RawFd, RawFd), SpawnTerminalError> {
         unimplemented!()
     }
@@ -96,6 +97,9 @@ impl ServerOsApi for FakeInputOutput {
     fn get_cwd(&self, _pid: Pid) -> Option<PathBuf> {
         unimplemented!()
     }
+    fn get_cmd(&self, _pid: Pid) -> Option<Vec<String>> {
+        unimplemented!()
+    }
     fn write_to_file(&mut self, buf: String, name: Option<String>) {
         let f: String = match name {
             Some(x) => x,
@@ -108,6 +112,7 @@ impl ServerOsApi for FakeInputOutput {
         _terminal_id: u32,
         _run_command: RunCommand,
         _quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>, // u32 is the exit status
+        _tab_index: Option<usize>,
     ) -> Result<(RawFd, RawFd), SpawnTerminalError> {
         unimplemented!()
     }
@@ -515,6 +520,38 @@ fn dump_screen() {
     );
 }
 
+#[test]
+fn dump_pane_contents() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let mut tab = create_new_tab(size, ModeInfo::default());
+    let new_pane_id = PaneId::Terminal(2);
+    tab.new_pane(new_pane_id, None, None, Some(client_id))
+        .unwrap();
+    tab.handle_pty_bytes(2, Vec::from("\u{1b}[31mred\u{1b}[m plain".as_bytes()))
+        .unwrap();
+    assert_eq!(
+        tab.dump_pane_contents(new_pane_id, client_id, false, false),
+        Some("red plain".to_owned()),
+        "pane contents were dumped as plain text"
+    );
+    let styled = tab
+        .dump_pane_contents(new_pane_id, client_id, false, true)
+        .unwrap();
+    assert!(
+        styled.starts_with("\u{1b}[31mred"),
+        "pane contents were dumped with styles"
+    );
+    assert_eq!(
+        tab.dump_pane_contents(PaneId::Terminal(3), client_id, false, false),
+        None,
+        "no contents for a pane that does not exist"
+    );
+}
+
 #[test]
 fn new_floating_pane() {
     let size = Size {
@@ -543,6 +580,100 @@ fn new_floating_pane() {
     assert_snapshot!(snapshot);
 }
 
+#[test]
+fn resize_floating_pane_to_preferred_size() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let mut tab = create_new_tab(size, ModeInfo::default());
+    let new_pane_id = PaneId::Terminal(2);
+    tab.toggle_floating_panes(client_id, None).unwrap();
+    tab.new_pane(new_pane_id, None, None, Some(client_id))
+        .unwrap();
+    let initial_geom = tab
+        .floating_panes
+        .get(&new_pane_id)
+        .unwrap()
+        .position_and_size();
+    tab.resize_pane_to_preferred_size(new_pane_id, 5, 20);
+    let pane = tab.floating_panes.get(&new_pane_id).unwrap();
+    assert_eq!(
+        pane.position_and_size().x,
+        initial_geom.x,
+        "pane did not move"
+    );
+    assert_eq!(
+        pane.position_and_size().y,
+        initial_geom.y,
+        "pane did not move"
+    );
+    assert_eq!(pane.get_content_rows(), 5, "pane has the preferred rows");
+    assert_eq!(
+        pane.get_content_columns(),
+        20,
+        "pane has the preferred cols"
+    );
+}
+
+#[test]
+fn resize_floating_pane_to_preferred_size_larger_than_screen() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let mut tab = create_new_tab(size, ModeInfo::default());
+    let new_pane_id = PaneId::Terminal(2);
+    tab.toggle_floating_panes(client_id, None).unwrap();
+    tab.new_pane(new_pane_id, None, None, Some(client_id))
+        .unwrap();
+    tab.resize_pane_to_preferred_size(new_pane_id, 100, 200);
+    let geom = tab
+        .floating_panes
+        .get(&new_pane_id)
+        .unwrap()
+        .position_and_size();
+    assert_eq!((geom.x, geom.y), (0, 0), "pane moved to stay on screen");
+    assert_eq!(geom.rows.as_usize(), 20, "pane is as high as the screen");
+    assert_eq!(geom.cols.as_usize(), 121, "pane is as wide as the screen");
+}
+
+#[test]
+fn set_floating_pane_borderless() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let mut tab = create_new_tab(size, ModeInfo::default());
+    let new_pane_id = PaneId::Terminal(2);
+    tab.toggle_floating_panes(client_id, None).unwrap();
+    tab.new_pane(new_pane_id, None, None, Some(client_id))
+        .unwrap();
+    let framed_content_rows = tab
+        .floating_panes
+        .get(&new_pane_id)
+        .unwrap()
+        .get_content_rows();
+    tab.set_pane_borderless(new_pane_id, true);
+    let pane = tab.floating_panes.get(&new_pane_id).unwrap();
+    assert!(pane.borderless(), "pane is borderless");
+    assert_eq!(
+        pane.get_content_rows(),
+        framed_content_rows + 2,
+        "content takes the place of the frame"
+    );
+    tab.set_pane_borderless(new_pane_id, false);
+    let pane = tab.floating_panes.get(&new_pane_id).unwrap();
+    assert_eq!(
+        pane.get_content_rows(),
+        framed_content_rows,
+        "frame is drawn again"
+    );
+}
+
 #[test]
 fn floating_panes_persist_across_toggles() {
     let size = Size {
@@ -1,6 +1,8 @@
 use super::Tab;
 use crate::panes::sixel::SixelImageStore;
+use crate::panes::PluginPane;
 use crate::screen::CopyOptions;
+use crate::wasm_vm::PluginInstruction;
 use crate::{
     os_input_output::{AsyncReader, Pid, ServerOsApi, SpawnTerminalError},
     panes::PaneId,
@@ -8,9 +10,11 @@ use crate::{
     ClientId,
 };
 use std::path::PathBuf;
-use zellij_utils::input::layout::PaneLayout;
+use zellij_utils::channels::{self, ChannelWithContext, SenderWithContext};
+use zellij_utils::data::Event;
+use zellij_utils::input::layout::{PaneLayout, RunPluginLocation};
 use zellij_utils::ipc::IpcReceiverWithContext;
-use zellij_utils::pane_size::{Size, SizeInPixels};
+use zellij_utils::pane_size::{PaneGeom, Size, SizeInPixels};
 
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
@@ -38,6 +42,7 @@ impl ServerOsApi for FakeInputOutput {
         _file_to_open: TerminalAction,
         _quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>,
         _default_editor: Option<PathBuf>,
+        _tab_index: Option<usize>,
     ) -> Result<(u32, RawFd, RawFd), SpawnTerminalError> {
         unimplemented!()
     }
@@ -85,6 +90,9 @@ impl ServerOsApi for FakeInputOutput {
     fn get_cwd(&self, _pid: Pid) -> Option<PathBuf> {
         unimplemented!()
     }
+    fn get_cmd(&self, _pid: Pid) -> Option<Vec<String>> {
+        unimplemented!()
+    }
 
     fn write_to_file(&mut self, _buf: String, _name: Option<String>) {
         unimplemented!()
@@ -94,6 +102,7 @@ impl ServerOsApi for FakeInputOutput {
         _terminal_id: u32,
         _run_command: RunCommand,
         _quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>, // u32 is the exit status
+        _tab_index: Option<usize>,
     ) -> Result<(RawFd, RawFd), SpawnTerminalError> {
         unimplemented!()
     }
@@ -14070,3 +14079,94 @@ fn correctly_resize_frameless_panes_on_pane_close() {
     let content_size = (pane.get_content_columns(), pane.get_content_rows());
     assert_eq!(content_size, (cols, rows));
 }
+
+#[test]
+fn pane_infos_report_the_command_of_running_and_held_command_panes() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    let run_command = RunCommand {
+        command: PathBuf::from("tail"),
+        args: vec!["-f".to_owned(), "/tmp/foo".to_owned()],
+        cwd: None,
+        hold_on_close: true,
+    };
+    tab.new_pane(PaneId::Terminal(2), None, None, Some(1))
+        .unwrap();
+    tab.set_pane_invoked_with(PaneId::Terminal(2), Some(run_command.clone()));
+    let terminal_command = |tab: &Tab, id| {
+        tab.pane_infos()
+            .into_iter()
+            .find(|pane_info| pane_info.id == id && !pane_info.is_plugin)
+            .unwrap()
+            .terminal_command
+    };
+    assert_eq!(
+        terminal_command(&tab, 1),
+        None,
+        "shell panes have no command"
+    );
+    assert_eq!(
+        terminal_command(&tab, 2),
+        Some(run_command.to_string()),
+        "the command is reported while it runs"
+    );
+    tab.hold_pane(PaneId::Terminal(2), Some(0), run_command.clone());
+    assert_eq!(
+        terminal_command(&tab, 2),
+        Some(run_command.to_string()),
+        "the command is reported after it exits"
+    );
+}
+
+#[test]
+fn plugins_do_not_write_to_their_own_focused_pane() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let mut tab = create_new_tab(size);
+    let (to_plugin, plugin_receiver): ChannelWithContext<PluginInstruction> = channels::unbounded();
+    tab.senders.to_plugin = Some(SenderWithContext::new(to_plugin));
+    // the pane gets its own channel so that its resize and render messages stay out of the way
+    let (to_plugin_pane, _plugin_pane_receiver): ChannelWithContext<PluginInstruction> =
+        channels::unbounded();
+    let plugin_pane = PluginPane::new(
+        1,
+        PaneGeom::default(),
+        SenderWithContext::new(to_plugin_pane),
+        RunPluginLocation::File(PathBuf::from("/path/to/plugin.wasm")),
+        String::new(),
+    );
+    tab.tiled_panes
+        .insert_pane(PaneId::Plugin(1), Box::new(plugin_pane));
+    tab.tiled_panes.focus_pane(PaneId::Plugin(1), client_id);
+
+    tab.write_from_plugin("a".as_bytes().to_vec(), 1, client_id)
+        .unwrap();
+    assert!(
+        plugin_receiver.try_recv().is_err(),
+        "the plugin does not receive its own input"
+    );
+
+    tab.write_from_plugin("a".as_bytes().to_vec(), 2, client_id)
+        .unwrap();
+    assert!(
+        matches!(
+            plugin_receiver.try_recv(),
+            Ok((PluginInstruction::Update(Some(1), None, Event::Key(_)), _))
+        ),
+        "other plugins can still write to it"
+    );
+
+    tab.toggle_sync_panes_is_active();
+    tab.write_from_plugin("a".as_bytes().to_vec(), 1, client_id)
+        .unwrap();
+    assert!(
+        plugin_receiver.try_recv().is_err(),
+        "the plugin is skipped when writing to all panes"
+    );
+}
@@ -2,6 +2,7 @@ use zellij_utils::pane_size::Size;
 
 use super::{Overlay, OverlayType, Overlayable};
 use crate::{ClientId, ServerInstruction};
+use zellij_utils::data::{PermissionStatus, PermissionType};
 use zellij_utils::errors::prelude::*;
 
 use std::fmt::Write;
@@ -63,3 +64,34 @@ pub fn _generate_quit_prompt(client_id: ClientId) -> Overlay {
         overlay_type: OverlayType::Prompt(prompt),
     }
 }
+
+pub fn generate_plugin_permissions_prompt(
+    plugin_id: u32,
+    client_id: ClientId,
+    plugin_name: &str,
+    permissions: Vec<PermissionType>,
+) -> Overlay {
+    let requested: Vec<String> = permissions.iter().map(|p| p.display_name()).collect();
+    let prompt = Prompt::new(
+        format!(
+            " Plugin {} asks for permission to: {}. Allow? [y]es / [n]o",
+            plugin_name,
+            requested.join(", ")
+        ),
+        Some(Box::new(ServerInstruction::PluginPermissionsResponse(
+            plugin_id,
+            client_id,
+            permissions.clone(),
+            PermissionStatus::Granted,
+        ))),
+        Some(Box::new(ServerInstruction::PluginPermissionsResponse(
+            plugin_id,
+            client_id,
+            permissions,
+            PermissionStatus::Denied,
+        ))),
+    );
+    Overlay {
+        overlay_type: OverlayType::Prompt(prompt),
+    }
+}
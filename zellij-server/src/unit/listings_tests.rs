@@ -0,0 +1,117 @@
+use super::*;
+use std::path::PathBuf;
+
+fn tabs() -> Vec<TabListing> {
+    vec![
+        TabListing {
+            position: 1,
+            name: "Tab #1".into(),
+            pane_count: 2,
+            focused_by_clients: vec![1, 2],
+            ..Default::default()
+        },
+        TabListing {
+            position: 2,
+            name: "logs".into(),
+            pane_count: 1,
+            ..Default::default()
+        },
+    ]
+}
+
+#[test]
+fn format_tabs_as_table() {
+    assert_eq!(
+        format_tabs(&tabs(), false),
+        "POSITION\tNAME\tPANES\tFOCUSED_BY\n1\tTab #1\t2\t1,2\n2\tlogs\t1\t\n"
+    );
+}
+
+#[test]
+fn format_tabs_as_table_escapes_names() {
+    let tabs = vec![TabListing {
+        position: 1,
+        name: "a\tb\nc\\d".into(),
+        pane_count: 1,
+        ..Default::default()
+    }];
+    assert_eq!(
+        format_tabs(&tabs, false),
+        "POSITION\tNAME\tPANES\tFOCUSED_BY\n1\ta\\tb\\nc\\\\d\t1\t\n"
+    );
+}
+
+#[test]
+fn format_tabs_as_json() {
+    let json = format_tabs(&tabs(), true);
+    assert!(json.ends_with('\n'));
+    let parsed: Vec<TabListing> = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, tabs());
+}
+
+#[test]
+fn format_panes_as_table() {
+    let panes = vec![
+        PaneListing {
+            id: 0,
+            tab_position: 1,
+            title: "vim".into(),
+            is_focused: true,
+            command: Some("vim src/main.rs".into()),
+            cwd: Some(PathBuf::from("/home/user/project")),
+            ..Default::default()
+        },
+        PaneListing {
+            id: 1,
+            is_plugin: true,
+            tab_position: 1,
+            title: "tab-bar".into(),
+            command: Some("zellij:tab-bar".into()),
+            ..Default::default()
+        },
+    ];
+    assert_eq!(
+        format_panes(&panes, false),
+        "ID\tTAB\tTITLE\tFOCUSED\tFLOATING\tCOMMAND\tCWD\n\
+         terminal_0\t1\tvim\ttrue\tfalse\tvim src/main.rs\t/home/user/project\n\
+         plugin_1\t1\ttab-bar\tfalse\tfalse\tzellij:tab-bar\t\n"
+    );
+}
+
+#[test]
+fn format_panes_as_table_escapes_titles_and_commands() {
+    let panes = vec![PaneListing {
+        id: 2,
+        tab_position: 1,
+        title: "multi\tline\r\ntitle".into(),
+        command: Some("printf 'a\tb'".into()),
+        ..Default::default()
+    }];
+    assert_eq!(
+        format_panes(&panes, false),
+        "ID\tTAB\tTITLE\tFOCUSED\tFLOATING\tCOMMAND\tCWD\n\
+         terminal_2\t1\tmulti\\tline\\r\\ntitle\tfalse\tfalse\tprintf 'a\\tb'\t\n"
+    );
+}
+
+#[test]
+fn format_clients_as_table() {
+    let clients = vec![
+        ClientListing {
+            id: 1,
+            tab_position: Some(1),
+            focused_pane_id: Some(3),
+            focused_pane_is_plugin: false,
+        },
+        ClientListing {
+            id: 2,
+            tab_position: None,
+            focused_pane_id: None,
+            focused_pane_is_plugin: false,
+        },
+    ];
+    assert_eq!(
+        format_clients(&clients, false),
+        "CLIENT_ID\tTAB\tFOCUSED_PANE\n1\t1\tterminal_3\n2\t\t\n"
+    );
+}
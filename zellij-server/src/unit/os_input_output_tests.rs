@@ -1,6 +1,8 @@
 use super::*;
 
 use nix::{pty::openpty, unistd::close};
+use std::sync::mpsc::channel;
+use std::time::Duration;
 
 struct TestTerminal {
     openpty: OpenptyResult,
@@ -48,3 +50,36 @@ fn get_cwd() {
         pid
     );
 }
+
+#[test]
+fn spawned_terminal_knows_its_pane_id_and_tab_index() {
+    let open_pty_res = openpty(None, None).expect("Could not create openpty");
+    let primary = open_pty_res.master;
+    let (exit_sender, exit_receiver) = channel();
+    let cmd = RunCommand {
+        command: PathBuf::from("sh"),
+        args: vec![
+            "-c".into(),
+            format!(
+                "printf %s/%s \"${}\" \"${}\"",
+                envs::PANE_ID_ENV_KEY,
+                envs::TAB_INDEX_ENV_KEY
+            ),
+        ],
+        cwd: None,
+        hold_on_close: false,
+    };
+    let quit_cb = Box::new(
+        move |_pane_id: PaneId, _exit_status: Option<i32>, _cmd: RunCommand| {
+            let _ = exit_sender.send(());
+        },
+    );
+    handle_openpty(open_pty_res, cmd, quit_cb, 7, Some(2)).expect("Could not spawn the terminal");
+    exit_receiver
+        .recv_timeout(Duration::from_secs(5))
+        .expect("The command did not exit");
+    let mut buf = [0; 16];
+    let read = unistd::read(primary, &mut buf).expect("Could not read from the terminal");
+    assert_eq!(&buf[..read], b"7/2");
+    close(primary).expect("Failed to close the master");
+}
@@ -0,0 +1,166 @@
+use super::*;
+use crate::os_input_output::{async_trait, AsyncReader, ServerOsApi};
+use std::time::Duration;
+use zellij_utils::channels::{self, ChannelWithContext, SenderWithContext};
+use zellij_utils::data::Palette;
+use zellij_utils::errors::ErrorContext;
+use zellij_utils::interprocess::local_socket::LocalSocketStream;
+use zellij_utils::ipc::{ClientToServerMsg, IpcReceiverWithContext, ServerToClientMsg};
+use zellij_utils::nix;
+
+/// Reads these chunks of output one at a time, then the end of the output
+struct SlowReader {
+    chunks: Vec<Vec<u8>>,
+}
+
+#[async_trait]
+impl AsyncReader for SlowReader {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, std::io::Error> {
+        task::sleep(Duration::from_millis(50)).await;
+        if self.chunks.is_empty() {
+            return Ok(0);
+        }
+        let chunk = self.chunks.remove(0);
+        buf[..chunk.len()].copy_from_slice(&chunk);
+        Ok(chunk.len())
+    }
+}
+
+/// Spawns commands that exit as soon as they start, before any of their output was read
+#[derive(Clone)]
+struct FakeInputOutput {
+    output: Vec<Vec<u8>>,
+}
+
+impl ServerOsApi for FakeInputOutput {
+    fn set_terminal_size_using_terminal_id(&self, _terminal_id: u32, _cols: u16, _rows: u16) {
+        // noop
+    }
+    fn spawn_terminal(
+        &self,
+        terminal_action: TerminalAction,
+        quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>,
+        _default_editor: Option<PathBuf>,
+        _tab_index: Option<usize>,
+    ) -> Result<(u32, RawFd, RawFd), SpawnTerminalError> {
+        let terminal_id = 1;
+        if let TerminalAction::RunCommand(command) = terminal_action {
+            quit_cb(PaneId::Terminal(terminal_id), Some(0), command);
+        }
+        Ok((terminal_id, 0, 0))
+    }
+    fn read_from_tty_stdout(&self, _fd: RawFd, _buf: &mut [u8]) -> Result<usize, nix::Error> {
+        unimplemented!()
+    }
+    fn async_file_reader(&self, _fd: RawFd) -> Box<dyn AsyncReader> {
+        Box::new(SlowReader {
+            chunks: self.output.clone(),
+        })
+    }
+    fn write_to_tty_stdin(&self, _id: u32, _buf: &[u8]) -> Result<usize, nix::Error> {
+        unimplemented!()
+    }
+    fn tcdrain(&self, _id: u32) -> Result<(), nix::Error> {
+        unimplemented!()
+    }
+    fn kill(&self, _pid: Pid) -> Result<(), nix::Error> {
+        Ok(())
+    }
+    fn force_kill(&self, _pid: Pid) -> Result<(), nix::Error> {
+        unimplemented!()
+    }
+    fn box_clone(&self) -> Box<dyn ServerOsApi> {
+        Box::new((*self).clone())
+    }
+    fn send_to_client(
+        &self,
+        _client_id: ClientId,
+        _msg: ServerToClientMsg,
+    ) -> Result<(), &'static str> {
+        unimplemented!()
+    }
+    fn new_client(
+        &mut self,
+        _client_id: ClientId,
+        _stream: LocalSocketStream,
+    ) -> IpcReceiverWithContext<ClientToServerMsg> {
+        unimplemented!()
+    }
+    fn remove_client(&mut self, _client_id: ClientId) {
+        unimplemented!()
+    }
+    fn load_palette(&self) -> Palette {
+        unimplemented!()
+    }
+    fn get_cwd(&self, _pid: Pid) -> Option<PathBuf> {
+        unimplemented!()
+    }
+    fn get_cmd(&self, _pid: Pid) -> Option<Vec<String>> {
+        unimplemented!()
+    }
+    fn write_to_file(&mut self, _contents: String, _filename: Option<String>) {
+        unimplemented!()
+    }
+    fn re_run_command_in_terminal(
+        &self,
+        _terminal_id: u32,
+        _run_command: RunCommand,
+        _quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>, // u32 is the exit status
+        _tab_index: Option<usize>,
+    ) -> Result<(RawFd, RawFd), SpawnTerminalError> {
+        unimplemented!()
+    }
+    fn clear_terminal_id(&self, _terminal_id: u32) {
+        // noop
+    }
+}
+
+#[test]
+fn command_exit_is_reported_after_the_last_of_its_output() {
+    let (_to_pty, pty_receiver): ChannelWithContext<PtyInstruction> = channels::unbounded();
+    let (to_screen, screen_receiver): ChannelWithContext<ScreenInstruction> = channels::unbounded();
+    let to_screen = SenderWithContext::new(to_screen);
+    let output = vec![b"first".to_vec(), b"second".to_vec(), b"last".to_vec()];
+    let os_input = FakeInputOutput {
+        output: output.clone(),
+    };
+    let bus = Bus::new(
+        vec![pty_receiver],
+        Some(&to_screen),
+        None,
+        None,
+        None,
+        None,
+        Some(Box::new(os_input)),
+    );
+    let mut pty = Pty::new(bus, false, None);
+    let client_id = 1;
+    pty.command_waiters.insert(client_id, true);
+    let command = TerminalAction::RunCommand(RunCommand {
+        command: PathBuf::from("true"),
+        ..Default::default()
+    });
+    pty.spawn_terminal(Some(command), ClientOrTabIndex::ClientId(client_id))
+        .unwrap();
+
+    let mut copied_output = vec![];
+    loop {
+        let (instruction, _): (ScreenInstruction, ErrorContext) = screen_receiver
+            .recv_timeout(Duration::from_secs(5))
+            .expect("the exit of the command was not reported");
+        match instruction {
+            ScreenInstruction::PtyBytes(_, bytes) => copied_output.push(bytes),
+            ScreenInstruction::CommandExited(pane_id, exit_status, id) => {
+                assert_eq!(pane_id, PaneId::Terminal(1));
+                assert_eq!(exit_status, Some(0));
+                assert_eq!(id, client_id);
+                break;
+            },
+            _ => {},
+        }
+    }
+    assert_eq!(
+        copied_output, output,
+        "all of the output is read before the command exit is reported"
+    );
+}
@@ -11,7 +11,7 @@ use insta::assert_snapshot;
 use std::path::PathBuf;
 use zellij_utils::cli::CliAction;
 use zellij_utils::errors::ErrorContext;
-use zellij_utils::input::actions::{Action, Direction, ResizeDirection};
+use zellij_utils::input::actions::{Action, ActionTarget, Direction, ResizeDirection, TabSelector};
 use zellij_utils::input::command::{RunCommand, TerminalAction};
 use zellij_utils::input::layout::{PaneLayout, SplitDirection};
 use zellij_utils::input::options::Options;
@@ -28,7 +28,10 @@ use zellij_utils::ipc::PixelDimensions;
 use zellij_utils::nix;
 use zellij_utils::{
     channels::{self, ChannelWithContext, Receiver},
-    data::{InputMode, ModeInfo, Palette, PluginCapabilities},
+    data::{
+        InputMode, ModeInfo, Palette, PaneId as DataPaneId, PermissionType, PluginCapabilities,
+        SessionInfo, TabListing,
+    },
     interprocess::local_socket::LocalSocketStream,
     ipc::{ClientAttributes, ClientToServerMsg, ServerToClientMsg},
 };
@@ -135,6 +138,7 @@ impl ServerOsApi for FakeInputOutput {
         _file_to_open: TerminalAction,
         _quit_db: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>,
         _default_editor: Option<PathBuf>,
+        _tab_index: Option<usize>,
     ) -> Result<(u32, RawFd, RawFd), SpawnTerminalError> {
         unimplemented!()
     }
@@ -188,6 +192,9 @@ impl ServerOsApi for FakeInputOutput {
     fn get_cwd(&self, _pid: Pid) -> Option<PathBuf> {
         unimplemented!()
     }
+    fn get_cmd(&self, _pid: Pid) -> Option<Vec<String>> {
+        unimplemented!()
+    }
     fn write_to_file(&mut self, contents: String, filename: Option<String>) {
         if let Some(filename) = filename {
             self.fake_filesystem
@@ -201,6 +208,7 @@ impl ServerOsApi for FakeInputOutput {
         _terminal_id: u32,
         _run_command: RunCommand,
         _quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>, // u32 is the exit status
+        _tab_index: Option<usize>,
     ) -> Result<(RawFd, RawFd), SpawnTerminalError> {
         unimplemented!()
     }
@@ -251,6 +259,7 @@ struct MockScreen {
     pub client_attributes: ClientAttributes,
     pub config_options: Options,
     pub session_metadata: SessionMetaData,
+    pub next_tab_index: usize,
 }
 
 impl MockScreen {
@@ -289,8 +298,10 @@ impl MockScreen {
         let _ = self.to_screen.send(ScreenInstruction::NewTab(
             pane_layout,
             pane_ids,
+            self.next_tab_index,
             self.main_client_id,
         ));
+        self.next_tab_index += 1;
         screen_thread
     }
     pub fn new_tab(&mut self, tab_layout: PaneLayout) {
@@ -302,8 +313,10 @@ impl MockScreen {
         let _ = self.to_screen.send(ScreenInstruction::NewTab(
             tab_layout,
             pane_ids,
+            self.next_tab_index,
             self.main_client_id,
         ));
+        self.next_tab_index += 1;
     }
     pub fn teardown(&mut self, threads: Vec<std::thread::JoinHandle<()>>) {
         let _ = self.to_pty.send(PtyInstruction::Exit);
@@ -395,6 +408,7 @@ impl MockScreen {
             client_attributes,
             config_options,
             session_metadata,
+            next_tab_index: 0,
         }
     }
 }
@@ -426,8 +440,14 @@ macro_rules! log_actions_in_thread {
 
 fn new_tab(screen: &mut Screen, pid: u32) {
     let client_id = 1;
+    let tab_index = screen
+        .tabs
+        .keys()
+        .last()
+        .map(|index| index + 1)
+        .unwrap_or(0);
     screen
-        .new_tab(PaneLayout::default(), vec![pid], client_id)
+        .new_tab(tab_index, PaneLayout::default(), vec![pid], client_id)
         .expect("TEST");
 }
 
@@ -918,6 +938,119 @@ pub fn send_cli_write_chars_action_to_screen() {
     assert_snapshot!(format!("{:?}", *received_pty_instructions.lock().unwrap()));
 }
 
+#[test]
+pub fn send_cli_write_chars_action_to_unfocused_pane() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 10; // fake client id should not appear in the screen's state
+    let mut initial_layout = PaneLayout::default();
+    initial_layout.children_split_direction = SplitDirection::Vertical;
+    initial_layout.children = vec![PaneLayout::default(), PaneLayout::default()];
+    let mut mock_screen = MockScreen::new(size);
+    let pty_writer_receiver = mock_screen.pty_writer_receiver.take().unwrap();
+    let screen_thread = mock_screen.run(Some(initial_layout));
+    let received_pty_instructions = Arc::new(Mutex::new(vec![]));
+    let pty_writer_thread = log_actions_in_thread!(
+        received_pty_instructions,
+        PtyWriteInstruction::Exit,
+        pty_writer_receiver
+    );
+    let target = ActionTarget {
+        pane_id: Some("terminal_1".parse().unwrap()),
+        tab: None,
+    };
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::TargetedAction(
+            Action::WriteChars("input from the cli".into()),
+            target,
+            client_id,
+        ));
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![pty_writer_thread, screen_thread]);
+    let written_to: Vec<u32> = received_pty_instructions
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|instruction| match instruction {
+            PtyWriteInstruction::Write(_, terminal_id) => Some(*terminal_id),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(written_to, vec![1], "only the targeted pane was written to");
+}
+
+fn errors_reported_for_targeted_action(action: Action, target: ActionTarget) -> Vec<String> {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 10; // fake client id should not appear in the screen's state
+    let mut mock_screen = MockScreen::new(size);
+    let screen_thread = mock_screen.run(None);
+    let received_server_instructions = Arc::new(Mutex::new(vec![]));
+    let server_receiver = mock_screen.server_receiver.take().unwrap();
+    let server_thread = log_actions_in_thread!(
+        received_server_instructions,
+        ServerInstruction::KillSession,
+        server_receiver
+    );
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::TargetedAction(action, target, client_id));
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![server_thread, screen_thread]);
+    let received_server_instructions = received_server_instructions.lock().unwrap();
+    received_server_instructions
+        .iter()
+        .filter_map(|instruction| match instruction {
+            ServerInstruction::ActionFailed(cli_client_id, error) => {
+                assert_eq!(
+                    *cli_client_id, client_id,
+                    "the error went to the cli client"
+                );
+                Some(error.clone())
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+pub fn send_cli_action_to_unknown_pane_reports_an_error_to_the_cli_client() {
+    let target = ActionTarget {
+        pane_id: Some("terminal_42".parse().unwrap()),
+        tab: None,
+    };
+    let errors = errors_reported_for_targeted_action(Action::WriteChars("input".into()), target);
+    assert_eq!(errors, vec!["there is no pane terminal_42".to_owned()]);
+}
+
+#[test]
+pub fn send_cli_action_to_unknown_tab_index_reports_an_error_to_the_cli_client() {
+    let target = ActionTarget {
+        pane_id: None,
+        tab: Some("index_42".parse().unwrap()),
+    };
+    let errors = errors_reported_for_targeted_action(Action::CloseTab, target);
+    assert_eq!(errors, vec!["there is no tab with the index 42".to_owned()]);
+}
+
+#[test]
+pub fn send_cli_action_to_unknown_tab_reports_an_error_to_the_cli_client() {
+    let target = ActionTarget {
+        pane_id: None,
+        tab: Some(TabSelector::Name("no such tab".into())),
+    };
+    let errors = errors_reported_for_targeted_action(Action::CloseTab, target);
+    assert_eq!(
+        errors,
+        vec!["there is no tab named \"no such tab\"".to_owned()]
+    );
+}
+
 #[test]
 pub fn send_cli_write_action_to_screen() {
     let size = Size {
@@ -944,6 +1077,38 @@ pub fn send_cli_write_action_to_screen() {
     assert_snapshot!(format!("{:?}", *received_pty_instructions.lock().unwrap()));
 }
 
+#[test]
+pub fn write_to_pane_id() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut mock_screen = MockScreen::new(size);
+    let pty_writer_receiver = mock_screen.pty_writer_receiver.take().unwrap();
+    let screen_thread = mock_screen.run(None);
+    let received_pty_instructions = Arc::new(Mutex::new(vec![]));
+    let pty_writer_thread = log_actions_in_thread!(
+        received_pty_instructions,
+        PtyWriteInstruction::Exit,
+        pty_writer_receiver
+    );
+    let _ = mock_screen.to_screen.send(ScreenInstruction::WriteToPaneId(
+        vec![102, 111, 111],
+        PaneId::Terminal(0),
+    ));
+    // a pane that does not exist should be ignored
+    let _ = mock_screen.to_screen.send(ScreenInstruction::WriteToPaneId(
+        vec![98, 97, 114],
+        PaneId::Terminal(42),
+    ));
+    std::thread::sleep(std::time::Duration::from_millis(100)); // give time for actions to be
+    mock_screen.teardown(vec![pty_writer_thread, screen_thread]);
+    assert_eq!(
+        format!("{:?}", *received_pty_instructions.lock().unwrap()),
+        "[Write([102, 111, 111], 0), Exit]"
+    );
+}
+
 #[test]
 pub fn send_cli_resize_action_to_screen() {
     let size = Size { cols: 80, rows: 20 };
@@ -1822,6 +1987,8 @@ pub fn send_cli_new_pane_action_with_default_parameters() {
         floating: false,
         name: None,
         close_on_exit: false,
+        wait: false,
+        print_output: false,
     };
     send_cli_action_to_server(
         &session_metadata,
@@ -1861,6 +2028,8 @@ pub fn send_cli_new_pane_action_with_split_direction() {
         floating: false,
         name: None,
         close_on_exit: false,
+        wait: false,
+        print_output: false,
     };
     send_cli_action_to_server(
         &session_metadata,
@@ -1900,6 +2069,8 @@ pub fn send_cli_new_pane_action_with_command_and_cwd() {
         floating: false,
         name: None,
         close_on_exit: false,
+        wait: false,
+        print_output: false,
     };
     send_cli_action_to_server(
         &session_metadata,
@@ -2506,3 +2677,302 @@ pub fn send_cli_undo_rename_tab() {
         *received_plugin_instructions.lock().unwrap()
     ))
 }
+
+#[test]
+pub fn query_session_info() {
+    let size = Size { cols: 80, rows: 10 };
+    let client_id = 10; // fake client id should not appear in the screen's state
+    let mut mock_screen = MockScreen::new(size);
+    mock_screen.new_tab(PaneLayout::default());
+    let screen_thread = mock_screen.run(Some(PaneLayout::default()));
+    let received_server_instructions = Arc::new(Mutex::new(vec![]));
+    let server_receiver = mock_screen.server_receiver.take().unwrap();
+    let server_thread = log_actions_in_thread!(
+        received_server_instructions,
+        ServerInstruction::KillSession,
+        server_receiver
+    );
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::QuerySessionInfo(client_id));
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![server_thread, screen_thread]);
+    let session_info = received_server_instructions
+        .lock()
+        .unwrap()
+        .iter()
+        .find_map(|instruction| match instruction {
+            ServerInstruction::SessionInfo(id, session_info) if *id == client_id => {
+                Some(session_info.clone())
+            },
+            _ => None,
+        });
+    assert_eq!(
+        session_info,
+        Some(SessionInfo {
+            name: "zellij-test".into(),
+            tabs: 2,
+            connected_clients: 1,
+            is_current_session: false,
+        })
+    );
+}
+
+#[test]
+pub fn list_tabs_as_json() {
+    let size = Size { cols: 80, rows: 10 };
+    let client_id = 10; // fake client id should not appear in the screen's state
+    let mut mock_screen = MockScreen::new(size);
+    mock_screen.new_tab(PaneLayout::default());
+    let screen_thread = mock_screen.run(Some(PaneLayout::default()));
+    let received_server_instructions = Arc::new(Mutex::new(vec![]));
+    let server_receiver = mock_screen.server_receiver.take().unwrap();
+    let server_thread = log_actions_in_thread!(
+        received_server_instructions,
+        ServerInstruction::KillSession,
+        server_receiver
+    );
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::ListTabs(true, client_id));
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![server_thread, screen_thread]);
+    let output = received_server_instructions
+        .lock()
+        .unwrap()
+        .iter()
+        .find_map(|instruction| match instruction {
+            ServerInstruction::QueryResponse(id, output) if *id == client_id => {
+                Some(output.clone())
+            },
+            _ => None,
+        })
+        .expect("no reply to the list-tabs query");
+    let tabs: Vec<TabListing> = serde_json::from_str(&output).unwrap();
+    let positions: Vec<usize> = tabs.iter().map(|tab| tab.position).collect();
+    assert_eq!(positions, vec![1, 2], "both tabs are listed in order");
+    assert!(
+        tabs.iter().all(|tab| tab.pane_count == 1),
+        "each tab has a single pane"
+    );
+}
+
+#[test]
+pub fn stream_output_of_subscribed_panes() {
+    let size = Size { cols: 80, rows: 20 };
+    let client_id = 10; // fake client id should not appear in the screen's state
+    let mut initial_layout = PaneLayout::default();
+    initial_layout.children_split_direction = SplitDirection::Vertical;
+    initial_layout.children = vec![PaneLayout::default(), PaneLayout::default()];
+    let mut mock_screen = MockScreen::new(size);
+    let screen_thread = mock_screen.run(Some(initial_layout));
+    let received_server_instructions = Arc::new(Mutex::new(vec![]));
+    let server_receiver = mock_screen.server_receiver.take().unwrap();
+    let server_thread = log_actions_in_thread!(
+        received_server_instructions,
+        ServerInstruction::KillSession,
+        server_receiver
+    );
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::SubscribeToPaneOutput(
+            vec![DataPaneId::Terminal(1)],
+            client_id,
+        ));
+    let _ = mock_screen.to_screen.send(ScreenInstruction::PtyBytes(
+        0,
+        "not subscribed".as_bytes().to_vec(),
+    ));
+    let _ = mock_screen.to_screen.send(ScreenInstruction::PtyBytes(
+        1,
+        "subscribed".as_bytes().to_vec(),
+    ));
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![server_thread, screen_thread]);
+    let lines: Vec<serde_json::Value> = received_server_instructions
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|instruction| match instruction {
+            ServerInstruction::SubscriptionEvent(id, line) if *id == client_id => {
+                Some(serde_json::from_str(line).unwrap())
+            },
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        lines,
+        vec![serde_json::json!({"PaneOutput": {"pane_id": 1, "output": "subscribed"}})],
+        "only the output of the subscribed pane is streamed"
+    );
+}
+
+#[test]
+pub fn stream_characters_split_across_reads_of_subscribed_panes() {
+    let size = Size { cols: 80, rows: 20 };
+    let client_id = 10; // fake client id should not appear in the screen's state
+    let mut mock_screen = MockScreen::new(size);
+    let screen_thread = mock_screen.run(None);
+    let received_server_instructions = Arc::new(Mutex::new(vec![]));
+    let server_receiver = mock_screen.server_receiver.take().unwrap();
+    let server_thread = log_actions_in_thread!(
+        received_server_instructions,
+        ServerInstruction::KillSession,
+        server_receiver
+    );
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::SubscribeToPaneOutput(
+            vec![DataPaneId::Terminal(0)],
+            client_id,
+        ));
+    let reads: [&[u8]; 3] = [b"caf\xc3", b"\xa9 \xf0\x9f", b"\xa6\x80"]; // "café 🦀"
+    for bytes in reads {
+        let _ = mock_screen
+            .to_screen
+            .send(ScreenInstruction::PtyBytes(0, bytes.to_vec()));
+    }
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![server_thread, screen_thread]);
+    let outputs: Vec<serde_json::Value> = received_server_instructions
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|instruction| match instruction {
+            ServerInstruction::SubscriptionEvent(_, line) => {
+                let line: serde_json::Value = serde_json::from_str(line).unwrap();
+                Some(line["PaneOutput"]["output"].clone())
+            },
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        outputs,
+        vec!["caf", "é ", "🦀"],
+        "characters are only streamed once all their bytes were read"
+    );
+}
+
+#[test]
+pub fn copy_command_output_until_it_exits() {
+    let size = Size { cols: 80, rows: 20 };
+    let client_id = 10; // fake client id should not appear in the screen's state
+    let mut initial_layout = PaneLayout::default();
+    initial_layout.children_split_direction = SplitDirection::Vertical;
+    initial_layout.children = vec![PaneLayout::default(), PaneLayout::default()];
+    let mut mock_screen = MockScreen::new(size);
+    let screen_thread = mock_screen.run(Some(initial_layout));
+    let received_server_instructions = Arc::new(Mutex::new(vec![]));
+    let server_receiver = mock_screen.server_receiver.take().unwrap();
+    let server_thread = log_actions_in_thread!(
+        received_server_instructions,
+        ServerInstruction::KillSession,
+        server_receiver
+    );
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::CopyCommandOutput(1, client_id));
+    let _ = mock_screen.to_screen.send(ScreenInstruction::PtyBytes(
+        0,
+        "not our command".as_bytes().to_vec(),
+    ));
+    let _ = mock_screen.to_screen.send(ScreenInstruction::PtyBytes(
+        1,
+        "our command".as_bytes().to_vec(),
+    ));
+    let _ = mock_screen.to_screen.send(ScreenInstruction::CommandExited(
+        PaneId::Terminal(1),
+        Some(3),
+        client_id,
+    ));
+    let _ = mock_screen.to_screen.send(ScreenInstruction::PtyBytes(
+        1,
+        "after the command exited".as_bytes().to_vec(),
+    ));
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![server_thread, screen_thread]);
+    let replies: Vec<String> = received_server_instructions
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|instruction| match instruction {
+            ServerInstruction::CommandOutput(id, output) if *id == client_id => {
+                Some(String::from_utf8_lossy(output).to_string())
+            },
+            ServerInstruction::CommandExited(id, exit_status) if *id == client_id => {
+                Some(format!("exited with {:?}", exit_status))
+            },
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        replies,
+        vec!["our command".to_string(), "exited with Some(3)".to_string()],
+        "the output of the command is copied until it exits, then its exit status is reported"
+    );
+}
+
+#[test]
+fn permission_prompts_wait_for_the_shown_one_to_be_answered() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+    let client_id = 1;
+    new_tab(&mut screen, 1);
+    screen
+        .request_plugin_permissions(
+            1,
+            client_id,
+            "first".into(),
+            vec![PermissionType::RunCommands],
+        )
+        .unwrap();
+    screen
+        .request_plugin_permissions(
+            2,
+            client_id,
+            "second".into(),
+            vec![PermissionType::RunCommands],
+        )
+        .unwrap();
+    assert_eq!(
+        screen.get_active_overlays_mut().len(),
+        1,
+        "one prompt is shown"
+    );
+    assert_eq!(screen.pending_prompts.len(), 1, "the other one waits");
+    assert_eq!(
+        screen.mode_info.get(&client_id).unwrap().mode,
+        InputMode::Prompt
+    );
+
+    // answering the prompt, then leaving the prompt mode
+    screen.get_active_overlays_mut().pop();
+    let mut mode_info = screen.mode_info.get(&client_id).unwrap().clone();
+    mode_info.mode = InputMode::Normal;
+    screen.change_mode(mode_info.clone(), client_id).unwrap();
+    assert_eq!(
+        screen.get_active_overlays_mut().len(),
+        1,
+        "the next prompt is shown"
+    );
+    assert!(screen.pending_prompts.is_empty());
+    assert_eq!(
+        screen.mode_info.get(&client_id).unwrap().mode,
+        InputMode::Prompt
+    );
+
+    // leaving the prompt mode without answering denies the prompt
+    screen.change_mode(mode_info, client_id).unwrap();
+    assert!(
+        screen.get_active_overlays_mut().is_empty(),
+        "the unanswered prompt is denied rather than asked again"
+    );
+    assert_eq!(
+        screen.mode_info.get(&client_id).unwrap().mode,
+        InputMode::Normal,
+        "the client is not sent back to the prompt mode"
+    );
+}
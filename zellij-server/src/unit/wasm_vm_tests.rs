@@ -0,0 +1,1070 @@
+use super::*;
+use tempfile::{tempdir, TempDir};
+use zellij_utils::channels::{self, ChannelWithContext, SenderWithContext};
+use zellij_utils::data::{InputMode, Key, KeyModifiers, ModeInfo, SubscriptionKind};
+use zellij_utils::errors::ErrorContext;
+use zellij_utils::input::command::RunCommandAction;
+use zellij_utils::input::layout::{PaneLayout, PluginUserConfiguration};
+use zellij_utils::input::plugins::PluginTag;
+
+fn plugin_config(fuel_limit: Option<u64>) -> PluginConfig {
+    PluginConfig {
+        path: PathBuf::from("/path/to/plugin.wasm"),
+        run: PluginType::Pane(None),
+        _allow_exec_host_cmd: false,
+        location: RunPluginLocation::File(PathBuf::from("/path/to/plugin.wasm")),
+        userspace_configuration: PluginUserConfiguration::default(),
+        fuel_limit,
+        memory_limit: None,
+    }
+}
+
+fn instantiate(store: &Store, wat: &str) -> Instance {
+    let module = Module::new(store, wat).unwrap();
+    Instance::new(&module, &imports! {}).unwrap()
+}
+
+#[test]
+fn plugin_spinning_forever_runs_out_of_fuel() {
+    let store = metered_store();
+    let instance = instantiate(
+        &store,
+        r#"(module (func (export "spin") (loop $forever (br $forever))))"#,
+    );
+    let plugin = plugin_config(Some(10_000));
+    set_remaining_points(&instance, fuel_limit(&plugin));
+    let result = instance.exports.get_function("spin").unwrap().call(&[]);
+    assert!(result.is_err(), "the call is interrupted");
+    let memory_limit_reached = AtomicBool::new(false);
+    let error =
+        check_limits(&instance, &plugin, &memory_limit_reached, result.is_err()).unwrap_err();
+    assert!(
+        matches!(
+            error.downcast_ref::<PluginLimitError>(),
+            Some(PluginLimitError::Fuel(10_000))
+        ),
+        "the plugin is reported as out of fuel, got: {:?}",
+        error
+    );
+}
+
+#[test]
+fn plugin_trapping_with_fuel_left_is_not_over_its_limits() {
+    let store = metered_store();
+    let instance = instantiate(&store, r#"(module (func (export "crash") unreachable))"#);
+    let plugin = plugin_config(None);
+    set_remaining_points(&instance, fuel_limit(&plugin));
+    let result = instance.exports.get_function("crash").unwrap().call(&[]);
+    assert!(result.is_err(), "the call traps");
+    let memory_limit_reached = AtomicBool::new(false);
+    assert!(check_limits(&instance, &plugin, &memory_limit_reached, result.is_err()).is_ok());
+}
+
+#[test]
+fn plugin_growing_its_memory_past_its_limit_is_refused_the_memory() {
+    let memory_limit_reached = Arc::new(AtomicBool::new(false));
+    let store = memory_limited_store(&metered_store(), 1, memory_limit_reached.clone());
+    let instance = instantiate(
+        &store,
+        r#"(module
+            (memory (export "memory") 1)
+            (func (export "grow")
+                (if (i32.eq (memory.grow (i32.const 64)) (i32.const -1))
+                    (then unreachable))))"#,
+    );
+    let mut plugin = plugin_config(None);
+    plugin.memory_limit = Some(1);
+    set_remaining_points(&instance, fuel_limit(&plugin));
+    let result = instance.exports.get_function("grow").unwrap().call(&[]);
+    assert!(result.is_err(), "the memory cannot grow to 4 MiB");
+    let memory = instance.exports.get_memory("memory").unwrap();
+    assert_eq!(memory.size(), Pages(1), "the memory was not grown");
+    let error =
+        check_limits(&instance, &plugin, &memory_limit_reached, result.is_err()).unwrap_err();
+    assert!(
+        matches!(
+            error.downcast_ref::<PluginLimitError>(),
+            Some(PluginLimitError::Memory(1))
+        ),
+        "the plugin is reported as out of memory, got: {:?}",
+        error
+    );
+}
+
+#[test]
+fn plugin_growing_its_memory_within_its_limit_is_not_over_its_limits() {
+    let memory_limit_reached = Arc::new(AtomicBool::new(false));
+    let store = memory_limited_store(&metered_store(), 1, memory_limit_reached.clone());
+    let instance = instantiate(
+        &store,
+        r#"(module
+            (memory (export "memory") 1)
+            (func (export "grow") (drop (memory.grow (i32.const 15)))))"#,
+    );
+    let plugin = plugin_config(None);
+    set_remaining_points(&instance, fuel_limit(&plugin));
+    let result = instance.exports.get_function("grow").unwrap().call(&[]);
+    assert!(result.is_ok());
+    let memory = instance.exports.get_memory("memory").unwrap();
+    assert_eq!(memory.size(), Pages(16), "the memory grew to its limit");
+    assert!(check_limits(&instance, &plugin, &memory_limit_reached, result.is_err()).is_ok());
+}
+
+/// A plugin in the wasm text format, reporting `version` when asked for it and rendering `text`
+fn plugin_wat(version: &str, text: &str) -> String {
+    format!(
+        r#"(module
+            (import "wasi_snapshot_preview1" "fd_write"
+                (func $fd_write (param i32 i32 i32 i32) (result i32)))
+            (memory (export "memory") 1)
+            (data (i32.const 64) "{version}")
+            (data (i32.const 128) "{text}")
+            (func $print (param $ptr i32) (param $len i32)
+                (i32.store (i32.const 0) (local.get $ptr))
+                (i32.store (i32.const 4) (local.get $len))
+                (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8))))
+            (func (export "_start"))
+            (func (export "plugin_version") (call $print (i32.const 64) (i32.const {version_len})))
+            (func (export "update"))
+            (func (export "render") (param i32 i32)
+                (call $print (i32.const 128) (i32.const {text_len}))))"#,
+        version = version,
+        text = text,
+        version_len = version.len(),
+        text_len = text.len(),
+    )
+}
+
+/// A plugin replying "done" to `reply_to` whenever input is piped to it
+fn piping_plugin_wat(reply_to: ClientId) -> String {
+    let subscriptions = r#"["PipeMessage"]"#;
+    let reply = format!(r#"[{},"done"]"#, reply_to);
+    format!(
+        r#"(module
+            (import "wasi_snapshot_preview1" "fd_write"
+                (func $fd_write (param i32 i32 i32 i32) (result i32)))
+            (import "zellij" "host_subscribe" (func $subscribe))
+            (import "zellij" "host_pipe_output" (func $pipe_output))
+            (memory (export "memory") 1)
+            (data (i32.const 64) "{version}")
+            (data (i32.const 128) "{subscriptions}")
+            (data (i32.const 192) "{reply}")
+            (func $print (param $ptr i32) (param $len i32)
+                (i32.store (i32.const 0) (local.get $ptr))
+                (i32.store (i32.const 4) (local.get $len))
+                (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8))))
+            (func (export "_start")
+                (call $print (i32.const 128) (i32.const {subscriptions_len}))
+                (call $subscribe))
+            (func (export "plugin_version") (call $print (i32.const 64) (i32.const {version_len})))
+            (func (export "update")
+                (call $print (i32.const 192) (i32.const {reply_len}))
+                (call $pipe_output))
+            (func (export "render") (param i32 i32)))"#,
+        version = VERSION,
+        version_len = VERSION.len(),
+        subscriptions = subscriptions.replace('"', "\\\""),
+        subscriptions_len = subscriptions.len(),
+        reply = reply.replace('"', "\\\""),
+        reply_len = reply.len(),
+    )
+}
+
+/// A plugin subscribing to the given event types on load, rendering whether it received an event
+fn subscribing_plugin_wat(event_types: &[&str]) -> String {
+    let subscriptions = serde_json::to_string(event_types).unwrap();
+    format!(
+        r#"(module
+            (import "wasi_snapshot_preview1" "fd_write"
+                (func $fd_write (param i32 i32 i32 i32) (result i32)))
+            (import "zellij" "host_subscribe" (func $subscribe))
+            (memory (export "memory") 1)
+            (data (i32.const 64) "{version}")
+            (data (i32.const 128) "{subscriptions}")
+            (data (i32.const 256) "updated")
+            (data (i32.const 264) "waiting")
+            (func $print (param $ptr i32) (param $len i32)
+                (i32.store (i32.const 0) (local.get $ptr))
+                (i32.store (i32.const 4) (local.get $len))
+                (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8))))
+            (func (export "_start")
+                (call $print (i32.const 128) (i32.const {subscriptions_len}))
+                (call $subscribe))
+            (func (export "plugin_version") (call $print (i32.const 64) (i32.const {version_len})))
+            (func (export "update") (i32.store (i32.const 16) (i32.const 1)))
+            (func (export "render") (param i32 i32)
+                (if (i32.load (i32.const 16))
+                    (then (call $print (i32.const 256) (i32.const 7)))
+                    (else (call $print (i32.const 264) (i32.const 7))))))"#,
+        version = VERSION,
+        version_len = VERSION.len(),
+        subscriptions = subscriptions.replace('"', "\\\""),
+        subscriptions_len = subscriptions.len(),
+    )
+}
+
+/// A plugin switching to the second tab whenever the mode of a client changes
+fn tab_switching_plugin_wat() -> String {
+    let subscriptions = r#"["ModeUpdate"]"#;
+    format!(
+        r#"(module
+            (import "wasi_snapshot_preview1" "fd_write"
+                (func $fd_write (param i32 i32 i32 i32) (result i32)))
+            (import "zellij" "host_subscribe" (func $subscribe))
+            (import "zellij" "host_switch_tab_to" (func $switch_tab_to (param i32)))
+            (memory (export "memory") 1)
+            (data (i32.const 64) "{version}")
+            (data (i32.const 128) "{subscriptions}")
+            (func $print (param $ptr i32) (param $len i32)
+                (i32.store (i32.const 0) (local.get $ptr))
+                (i32.store (i32.const 4) (local.get $len))
+                (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8))))
+            (func (export "_start")
+                (call $print (i32.const 128) (i32.const {subscriptions_len}))
+                (call $subscribe))
+            (func (export "plugin_version") (call $print (i32.const 64) (i32.const {version_len})))
+            (func (export "update") (call $switch_tab_to (i32.const 1)))
+            (func (export "render") (param i32 i32)))"#,
+        version = VERSION,
+        version_len = VERSION.len(),
+        subscriptions = subscriptions.replace('"', "\\\""),
+        subscriptions_len = subscriptions.len(),
+    )
+}
+
+/// A plugin subscribing to the given event types and calling `host_function` with `object` on
+/// load, rendering whether it received an event
+fn host_calling_plugin_wat(event_types: &[&str], host_function: &str, object: &str) -> String {
+    let subscriptions = serde_json::to_string(event_types).unwrap();
+    format!(
+        r#"(module
+            (import "wasi_snapshot_preview1" "fd_write"
+                (func $fd_write (param i32 i32 i32 i32) (result i32)))
+            (import "zellij" "host_subscribe" (func $subscribe))
+            (import "zellij" "{host_function}" (func $host_function))
+            (memory (export "memory") 1)
+            (data (i32.const 64) "{version}")
+            (data (i32.const 128) "{subscriptions}")
+            (data (i32.const 256) "updated")
+            (data (i32.const 264) "waiting")
+            (data (i32.const 512) "{object}")
+            (func $print (param $ptr i32) (param $len i32)
+                (i32.store (i32.const 0) (local.get $ptr))
+                (i32.store (i32.const 4) (local.get $len))
+                (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8))))
+            (func (export "_start")
+                (call $print (i32.const 128) (i32.const {subscriptions_len}))
+                (call $subscribe)
+                (call $print (i32.const 512) (i32.const {object_len}))
+                (call $host_function))
+            (func (export "plugin_version") (call $print (i32.const 64) (i32.const {version_len})))
+            (func (export "update") (i32.store (i32.const 16) (i32.const 1)))
+            (func (export "render") (param i32 i32)
+                (if (i32.load (i32.const 16))
+                    (then (call $print (i32.const 256) (i32.const 7)))
+                    (else (call $print (i32.const 264) (i32.const 7))))))"#,
+        host_function = host_function,
+        version = VERSION,
+        version_len = VERSION.len(),
+        subscriptions = subscriptions.replace('"', "\\\""),
+        subscriptions_len = subscriptions.len(),
+        object = object.replace('"', "\\\""),
+        object_len = object.len(),
+    )
+}
+
+struct MockPluginThread {
+    to_plugin: SenderWithContext<PluginInstruction>,
+    thread: Option<thread::JoinHandle<Result<()>>>,
+    // the receivers are kept so that the plugin thread can send to the other threads
+    screen_receiver: channels::Receiver<(ScreenInstruction, ErrorContext)>,
+    pty_receiver: channels::Receiver<(PtyInstruction, ErrorContext)>,
+    server_receiver: channels::Receiver<(ServerInstruction, ErrorContext)>,
+    data_dir: TempDir,
+}
+
+impl MockPluginThread {
+    fn start() -> Self {
+        MockPluginThread::start_with_plugins(PluginsConfig::default())
+    }
+    fn start_with_plugins(plugins: PluginsConfig) -> Self {
+        let (to_plugin, plugin_receiver): ChannelWithContext<PluginInstruction> =
+            channels::unbounded();
+        let to_plugin = SenderWithContext::new(to_plugin);
+        let (to_screen, screen_receiver): ChannelWithContext<ScreenInstruction> =
+            channels::unbounded();
+        let to_screen = SenderWithContext::new(to_screen);
+        let (to_pty, pty_receiver): ChannelWithContext<PtyInstruction> = channels::unbounded();
+        let to_pty = SenderWithContext::new(to_pty);
+        let (to_server, server_receiver): ChannelWithContext<ServerInstruction> =
+            channels::unbounded();
+        let to_server = SenderWithContext::new(to_server);
+        let bus = Bus::new(
+            vec![plugin_receiver],
+            Some(&to_screen),
+            Some(&to_pty),
+            Some(&to_plugin),
+            Some(&to_server),
+            None,
+            None,
+        );
+        let data_dir = tempdir().unwrap();
+        let plugin_data_dir = data_dir.path().to_path_buf();
+        // keeps the permissions granted in the tests away from the user's own
+        let permission_cache_path = data_dir.path().join("permissions.kdl");
+        let thread = thread::spawn(move || {
+            wasm_thread_main(
+                bus,
+                metered_store(),
+                plugin_data_dir,
+                permission_cache_path,
+                plugins,
+                None,
+            )
+        });
+        MockPluginThread {
+            to_plugin,
+            thread: Some(thread),
+            screen_receiver,
+            pty_receiver,
+            server_receiver,
+            data_dir,
+        }
+    }
+    fn send(&self, instruction: PluginInstruction) {
+        self.to_plugin.send(instruction).unwrap();
+    }
+    fn load(&self, location: &RunPluginLocation, client_id: ClientId) -> u32 {
+        self.load_with(location, client_id, false)
+    }
+    // loads the plugin, granting it the RunCommands permission if `allow_exec_host_cmd` is set
+    fn load_with(
+        &self,
+        location: &RunPluginLocation,
+        client_id: ClientId,
+        allow_exec_host_cmd: bool,
+    ) -> u32 {
+        let (pid_tx, pid_rx) = std::sync::mpsc::channel();
+        let run = RunPlugin {
+            _allow_exec_host_cmd: allow_exec_host_cmd,
+            location: location.clone(),
+            configuration: PluginUserConfiguration::default(),
+        };
+        self.send(PluginInstruction::Load(pid_tx, run, 0, client_id));
+        pid_rx.recv_timeout(Duration::from_secs(10)).unwrap()
+    }
+    fn render(&self, plugin_id: u32, client_id: ClientId) -> String {
+        let (buf_tx, buf_rx) = std::sync::mpsc::channel();
+        self.send(PluginInstruction::Render(
+            buf_tx, plugin_id, client_id, 10, 10,
+        ));
+        buf_rx.recv_timeout(Duration::from_secs(10)).unwrap()
+    }
+    // the output sent to cli clients until the pipe of `cli_client_id` is closed
+    fn pipe_output_until_closed(&self, cli_client_id: ClientId) -> Vec<(ClientId, String)> {
+        let mut pipe_output = vec![];
+        loop {
+            match self.server_receiver.recv_timeout(Duration::from_secs(10)) {
+                Ok((ServerInstruction::PipeOutput(client_id, output), _)) => {
+                    pipe_output.push((client_id, output))
+                },
+                Ok((ServerInstruction::ClosePipe(client_id), _)) if client_id == cli_client_id => {
+                    return pipe_output
+                },
+                Ok(_) => {},
+                Err(e) => panic!("pipe of client {} was not closed: {}", cli_client_id, e),
+            }
+        }
+    }
+    // the key presses and mouse motion last reported to the client
+    fn input_subscriptions(&self, client_id: ClientId) -> (bool, bool) {
+        let mut input_subscriptions = None;
+        while let Ok((instruction, _)) = self
+            .server_receiver
+            .recv_timeout(Duration::from_millis(500))
+        {
+            if let ServerInstruction::InputSubscriptions(id, key_presses, mouse_motion) =
+                instruction
+            {
+                if id == client_id {
+                    input_subscriptions = Some((key_presses, mouse_motion));
+                }
+            }
+        }
+        input_subscriptions.expect("no input subscriptions were reported to the client")
+    }
+    // the lines of JSON streamed to the subscribed cli client
+    fn subscription_lines(&self, cli_client_id: ClientId) -> Vec<serde_json::Value> {
+        let mut lines = vec![];
+        while let Ok((instruction, _)) = self
+            .server_receiver
+            .recv_timeout(Duration::from_millis(500))
+        {
+            if let ServerInstruction::SubscriptionEvent(id, line) = instruction {
+                if id == cli_client_id {
+                    lines.push(serde_json::from_str(&line).unwrap());
+                }
+            }
+        }
+        lines
+    }
+    // the text the plugin renders once it is `expected`, or after 10 seconds
+    fn render_until(&self, plugin_id: u32, client_id: ClientId, expected: &str) -> String {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            let rendered = self.render(plugin_id, client_id);
+            if rendered == expected || Instant::now() > deadline {
+                return rendered;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+    // the instructions sent to the pty thread
+    fn pty_instructions(&self) -> Vec<PtyInstruction> {
+        let mut pty_instructions = vec![];
+        while let Ok((instruction, _)) = self.pty_receiver.recv_timeout(Duration::from_millis(500))
+        {
+            pty_instructions.push(instruction);
+        }
+        pty_instructions
+    }
+    // the clients that tabs were switched for
+    fn tab_switches(&self) -> Vec<Option<ClientId>> {
+        let mut tab_switches = vec![];
+        while let Ok((instruction, _)) = self
+            .screen_receiver
+            .recv_timeout(Duration::from_millis(500))
+        {
+            if let ScreenInstruction::GoToTab(_, client_id) = instruction {
+                tab_switches.push(client_id);
+            }
+        }
+        tab_switches
+    }
+    fn exit(mut self) -> Result<()> {
+        self.send(PluginInstruction::Exit);
+        self.thread.take().unwrap().join().unwrap()
+    }
+}
+
+#[test]
+fn plugin_failing_to_start_displays_its_error_until_it_is_reloaded() {
+    let plugin_thread = MockPluginThread::start();
+    let plugin_dir = tempdir().unwrap();
+    let wasm_path = plugin_dir.path().join("plugin.wasm");
+    let location = RunPluginLocation::File(wasm_path.clone());
+    let client_id = 1;
+    plugin_thread.send(PluginInstruction::AddClient(client_id));
+
+    let plugin_id = plugin_thread.load(&location, client_id);
+    let rendered = plugin_thread.render(plugin_id, client_id);
+    assert!(rendered.contains("ERROR"), "got: {:?}", rendered);
+    assert!(rendered.contains("failed to start"), "got: {:?}", rendered);
+
+    fs::write(&wasm_path, plugin_wat(VERSION, "hello from the plugin")).unwrap();
+    plugin_thread.send(PluginInstruction::Reload(location, false));
+    assert_eq!(
+        plugin_thread.render(plugin_id, client_id),
+        "hello from the plugin"
+    );
+    plugin_thread.exit().unwrap();
+}
+
+#[test]
+fn plugin_with_another_version_fails_to_start_without_bringing_down_the_session() {
+    let plugin_thread = MockPluginThread::start();
+    let plugin_dir = tempdir().unwrap();
+    let wasm_path = plugin_dir.path().join("plugin.wasm");
+    fs::write(&wasm_path, plugin_wat("0.0.1", "outdated")).unwrap();
+    let location = RunPluginLocation::File(wasm_path);
+    let client_id = 1;
+    plugin_thread.send(PluginInstruction::AddClient(client_id));
+
+    let plugin_id = plugin_thread.load(&location, client_id);
+    let rendered = plugin_thread.render(plugin_id, client_id);
+    assert!(rendered.contains("ERROR"), "got: {:?}", rendered);
+    assert!(
+        rendered.contains("Plugin version: 0.0.1"),
+        "got: {:?}",
+        rendered
+    );
+    plugin_thread.exit().unwrap();
+}
+
+#[test]
+fn plugin_with_an_unknown_tag_displays_its_error_without_bringing_down_the_session() {
+    let plugin_thread = MockPluginThread::start();
+    let location = RunPluginLocation::Zellij(PluginTag::new("typo"));
+    let client_id = 1;
+    plugin_thread.send(PluginInstruction::AddClient(client_id));
+
+    let plugin_id = plugin_thread.load(&location, client_id);
+    let rendered = plugin_thread.render(plugin_id, client_id);
+    assert!(rendered.contains("ERROR"), "got: {:?}", rendered);
+    assert!(
+        rendered.contains("no plugin is configured for zellij:typo"),
+        "got: {:?}",
+        rendered
+    );
+    plugin_thread.exit().unwrap();
+}
+
+#[test]
+fn reloading_a_broken_build_keeps_the_running_plugin() {
+    let plugin_thread = MockPluginThread::start();
+    let plugin_dir = tempdir().unwrap();
+    let wasm_path = plugin_dir.path().join("plugin.wasm");
+    fs::write(&wasm_path, plugin_wat(VERSION, "first build")).unwrap();
+    let location = RunPluginLocation::File(wasm_path.clone());
+    let client_id = 1;
+    plugin_thread.send(PluginInstruction::AddClient(client_id));
+
+    let plugin_id = plugin_thread.load(&location, client_id);
+    assert_eq!(plugin_thread.render(plugin_id, client_id), "first build");
+
+    fs::write(&wasm_path, "(module (this is not wasm))").unwrap();
+    plugin_thread.send(PluginInstruction::Reload(location.clone(), false));
+    assert_eq!(plugin_thread.render(plugin_id, client_id), "first build");
+
+    fs::write(&wasm_path, plugin_wat(VERSION, "second build")).unwrap();
+    plugin_thread.send(PluginInstruction::Reload(location, false));
+    assert_eq!(plugin_thread.render(plugin_id, client_id), "second build");
+    plugin_thread.exit().unwrap();
+}
+
+#[test]
+fn actions_starting_commands_or_writing_files_require_a_permission() {
+    assert_eq!(
+        required_permission(&Action::NewTab(Some(PaneLayout::default()), None)),
+        Some(PermissionType::RunCommands)
+    );
+    assert_eq!(
+        required_permission(&Action::NewTab(None, None)),
+        Some(PermissionType::RunCommands)
+    );
+    assert_eq!(
+        required_permission(&Action::ToggleFloatingPanes),
+        Some(PermissionType::RunCommands)
+    );
+    assert_eq!(
+        required_permission(&Action::DumpScreen("/tmp/screen".into(), false)),
+        Some(PermissionType::ChangeFilesystem)
+    );
+    assert_eq!(
+        required_permission(&Action::SkipConfirm(Box::new(Action::Run(
+            RunCommandAction::default()
+        )))),
+        Some(PermissionType::RunCommands)
+    );
+    assert_eq!(
+        required_permission(&Action::NewFloatingPane(None, None)),
+        Some(PermissionType::RunCommands)
+    );
+    assert_eq!(
+        required_permission(&Action::NewPane(None, None)),
+        Some(PermissionType::RunCommands)
+    );
+    assert_eq!(
+        required_permission(&Action::LaunchOrFocusPlugin(
+            RunPluginLocation::File(PathBuf::from("/path/to/plugin.wasm")),
+            true
+        )),
+        Some(PermissionType::RunCommands)
+    );
+    assert_eq!(required_permission(&Action::FocusNextPane), None);
+}
+
+#[test]
+fn actions_replying_to_the_cli_client_are_not_available_to_plugins() {
+    assert!(is_unavailable_to_plugins(&Action::ListTabs(false)));
+    assert!(is_unavailable_to_plugins(&Action::ListPanes(true)));
+    assert!(is_unavailable_to_plugins(&Action::ListClients(false)));
+    assert!(is_unavailable_to_plugins(&Action::Subscribe(
+        vec![],
+        vec![]
+    )));
+    assert!(is_unavailable_to_plugins(&Action::WaitForCommand(true)));
+    assert!(is_unavailable_to_plugins(&Action::SkipConfirm(Box::new(
+        Action::WaitForCommand(false)
+    ))));
+    assert!(!is_unavailable_to_plugins(&Action::FocusNextPane));
+}
+
+#[test]
+fn plugins_cannot_answer_prompts() {
+    assert!(is_unavailable_to_plugins(&Action::Confirm));
+    assert!(is_unavailable_to_plugins(&Action::Deny));
+    assert!(is_unavailable_to_plugins(&Action::SkipConfirm(Box::new(
+        Action::Confirm
+    ))));
+    assert!(is_unavailable_to_plugins(&Action::SkipConfirm(Box::new(
+        Action::Deny
+    ))));
+}
+
+#[test]
+fn plugins_can_only_output_to_the_cli_clients_piping_to_them() {
+    let plugin_thread = MockPluginThread::start();
+    let plugin_dir = tempdir().unwrap();
+    let wasm_path = plugin_dir.path().join("plugin.wasm");
+    let piping_client_id = 7;
+    let other_client_id = 8;
+    fs::write(&wasm_path, piping_plugin_wat(piping_client_id)).unwrap();
+    let location = RunPluginLocation::File(wasm_path);
+    plugin_thread.send(PluginInstruction::AddClient(1));
+    plugin_thread.load(&location, 1);
+
+    let pipe = |payload: Option<&str>, cli_client_id| {
+        plugin_thread.send(PluginInstruction::Pipe(
+            location.clone(),
+            None,
+            payload.map(String::from),
+            cli_client_id,
+        ))
+    };
+    pipe(Some("input"), piping_client_id);
+    pipe(None, piping_client_id);
+    assert_eq!(
+        plugin_thread.pipe_output_until_closed(piping_client_id),
+        vec![
+            (piping_client_id, "done".to_owned()),
+            (piping_client_id, "done".to_owned())
+        ],
+        "plugin replies to the client piping to it, until the end of its input"
+    );
+
+    pipe(Some("input"), other_client_id);
+    pipe(None, other_client_id);
+    assert_eq!(
+        plugin_thread.pipe_output_until_closed(other_client_id),
+        vec![],
+        "plugin cannot reply to a client once its pipe is closed"
+    );
+    plugin_thread.exit().unwrap();
+}
+
+#[test]
+fn clients_report_mouse_motion_while_plugins_subscribe_to_hover_events() {
+    let plugin_thread = MockPluginThread::start();
+    let plugin_dir = tempdir().unwrap();
+    let wasm_path = plugin_dir.path().join("plugin.wasm");
+    fs::write(&wasm_path, subscribing_plugin_wat(&["MouseHover"])).unwrap();
+    let client_id = 1;
+    plugin_thread.send(PluginInstruction::AddClient(client_id));
+    assert_eq!(plugin_thread.input_subscriptions(client_id), (false, false));
+
+    let plugin_id = plugin_thread.load(&RunPluginLocation::File(wasm_path), client_id);
+    assert_eq!(plugin_thread.input_subscriptions(client_id), (false, true));
+    assert_eq!(plugin_thread.render(plugin_id, client_id), "waiting");
+    plugin_thread.send(PluginInstruction::Update(
+        Some(plugin_id),
+        Some(client_id),
+        Event::MouseHover(2, 3),
+    ));
+    assert_eq!(plugin_thread.render(plugin_id, client_id), "updated");
+
+    plugin_thread.send(PluginInstruction::Unload(plugin_id));
+    assert_eq!(plugin_thread.input_subscriptions(client_id), (false, false));
+    plugin_thread.exit().unwrap();
+}
+
+#[test]
+fn key_presses_are_neither_sent_nor_delivered_without_the_read_keystrokes_permission() {
+    let plugin_thread = MockPluginThread::start();
+    let plugin_dir = tempdir().unwrap();
+    let wasm_path = plugin_dir.path().join("plugin.wasm");
+    fs::write(&wasm_path, subscribing_plugin_wat(&["KeyPress"])).unwrap();
+    let client_id = 1;
+    plugin_thread.send(PluginInstruction::AddClient(client_id));
+
+    let plugin_id = plugin_thread.load(&RunPluginLocation::File(wasm_path), client_id);
+    assert_eq!(plugin_thread.input_subscriptions(client_id), (false, false));
+    plugin_thread.send(PluginInstruction::Update(
+        None,
+        Some(client_id),
+        Event::KeyPress(Key::Char('a'), KeyModifiers::default()),
+    ));
+    assert_eq!(plugin_thread.render(plugin_id, client_id), "waiting");
+    plugin_thread.exit().unwrap();
+}
+
+#[test]
+fn subscribers_receive_the_state_of_each_client_in_order() {
+    let plugin_thread = MockPluginThread::start();
+    let mode_update = |mode| {
+        Event::ModeUpdate(ModeInfo {
+            mode,
+            ..Default::default()
+        })
+    };
+    plugin_thread.send(PluginInstruction::Update(
+        None,
+        Some(2),
+        mode_update(InputMode::Locked),
+    ));
+    plugin_thread.send(PluginInstruction::Update(
+        None,
+        Some(1),
+        mode_update(InputMode::Normal),
+    ));
+    plugin_thread.send(PluginInstruction::Update(
+        None,
+        None,
+        Event::TabUpdate(vec![]),
+    ));
+    let cli_client_id = 10;
+    plugin_thread.send(PluginInstruction::Subscribe(
+        vec![SubscriptionKind::Tab, SubscriptionKind::Mode],
+        cli_client_id,
+    ));
+    let summary = |lines: Vec<serde_json::Value>| -> Vec<(String, serde_json::Value)> {
+        lines
+            .iter()
+            .map(|line| {
+                let event_type = line
+                    .as_object()
+                    .unwrap()
+                    .keys()
+                    .find(|key| *key != "client_id")
+                    .unwrap()
+                    .clone();
+                (event_type, line["client_id"].clone())
+            })
+            .collect()
+    };
+    assert_eq!(
+        summary(plugin_thread.subscription_lines(cli_client_id)),
+        vec![
+            ("ModeUpdate".to_owned(), serde_json::json!(1)),
+            ("ModeUpdate".to_owned(), serde_json::json!(2)),
+            ("TabUpdate".to_owned(), serde_json::Value::Null),
+        ],
+        "the current state is replayed in order, with the client each event was sent to"
+    );
+    plugin_thread.send(PluginInstruction::Update(
+        None,
+        Some(2),
+        mode_update(InputMode::Locked),
+    ));
+    plugin_thread.send(PluginInstruction::Update(
+        None,
+        Some(1),
+        mode_update(InputMode::Locked),
+    ));
+    assert_eq!(
+        summary(plugin_thread.subscription_lines(cli_client_id)),
+        vec![("ModeUpdate".to_owned(), serde_json::json!(1))],
+        "only the client whose state changed is reported"
+    );
+    plugin_thread.exit().unwrap();
+}
+
+#[test]
+fn filesystem_changes_made_together_are_sent_at_once() {
+    use notify::event::{CreateKind, ModifyKind, RemoveKind};
+
+    let (to_plugin, plugin_receiver): ChannelWithContext<PluginInstruction> = channels::unbounded();
+    let senders = ThreadSenders {
+        to_plugin: Some(SenderWithContext::new(to_plugin)),
+        ..Default::default()
+    };
+    let host_root = PathBuf::from("/path/to/project");
+    let change = |kind, path: &str| FsEvent::new(kind).add_path(host_root.join(path));
+    let (changes_tx, changes_rx) = channel();
+    changes_tx
+        .send(change(EventKind::Create(CreateKind::File), "a"))
+        .unwrap();
+    changes_tx
+        .send(change(EventKind::Modify(ModifyKind::Any), "b"))
+        .unwrap();
+    changes_tx
+        .send(change(EventKind::Modify(ModifyKind::Any), "a"))
+        .unwrap();
+    changes_tx
+        .send(change(EventKind::Modify(ModifyKind::Any), "b"))
+        .unwrap();
+    changes_tx
+        .send(FsEvent::new(EventKind::Remove(RemoveKind::Any)))
+        .unwrap();
+    drop(changes_tx);
+
+    send_filesystem_changes(changes_rx, &host_root, &senders);
+    let events: Vec<Event> = plugin_receiver
+        .try_iter()
+        .map(|(instruction, _)| match instruction {
+            PluginInstruction::Update(None, None, event) => event,
+            _ => panic!("only filesystem events are sent"),
+        })
+        .collect();
+    assert_eq!(
+        events,
+        vec![
+            Event::FileSystemCreate(vec![PathBuf::from("/host/a")]),
+            Event::FileSystemUpdate(vec![PathBuf::from("/host/a"), PathBuf::from("/host/b")]),
+        ],
+        "changes are sent once per kind, and changes without paths are dropped"
+    );
+}
+
+#[test]
+fn only_the_directories_near_the_root_are_watched() {
+    let root = tempdir().unwrap();
+    fs::create_dir_all(root.path().join("src/deep/deeper")).unwrap();
+    fs::create_dir_all(root.path().join("docs")).unwrap();
+    fs::write(root.path().join("README.md"), "").unwrap();
+
+    let mut watched = subdirectories(root.path(), 1);
+    watched.sort();
+    assert_eq!(
+        watched,
+        vec![root.path().join("docs"), root.path().join("src")]
+    );
+    assert_eq!(subdirectories(root.path(), 2).len(), 3);
+}
+
+#[test]
+fn subscribers_receive_session_updates_without_plugins_subscribing_to_them() {
+    let plugin_thread = MockPluginThread::start();
+    let cli_client_id = 10;
+    plugin_thread.send(PluginInstruction::Subscribe(
+        vec![SubscriptionKind::Session],
+        cli_client_id,
+    ));
+    let lines = plugin_thread.subscription_lines(cli_client_id);
+    assert!(
+        lines.iter().any(|line| line.get("SessionUpdate").is_some()),
+        "the sessions are scanned for the subscriber, got: {:?}",
+        lines
+    );
+    plugin_thread.exit().unwrap();
+}
+
+#[test]
+fn headless_plugins_act_for_the_client_whose_event_they_handle() {
+    let plugin_dir = tempdir().unwrap();
+    let wasm_path = plugin_dir.path().join("plugin.wasm");
+    fs::write(&wasm_path, tab_switching_plugin_wat()).unwrap();
+    let headless_plugin = PluginConfig {
+        path: wasm_path.clone(),
+        run: PluginType::Headless,
+        location: RunPluginLocation::File(wasm_path),
+        ..plugin_config(None)
+    };
+    let plugins = PluginsConfig::from_data(HashMap::from([(
+        PluginTag::new("headless"),
+        headless_plugin,
+    )]));
+    let plugin_thread = MockPluginThread::start_with_plugins(plugins);
+    plugin_thread.send(PluginInstruction::AddClient(1));
+    plugin_thread.send(PluginInstruction::AddClient(2));
+
+    let mode_update = Event::ModeUpdate(ModeInfo::default());
+    plugin_thread.send(PluginInstruction::Update(
+        None,
+        Some(2),
+        mode_update.clone(),
+    ));
+    assert_eq!(plugin_thread.tab_switches(), vec![Some(2)]);
+
+    // the client it acted for is gone, it acts for the other one
+    plugin_thread.send(PluginInstruction::RemoveClient(2));
+    plugin_thread.send(PluginInstruction::Update(None, None, mode_update));
+    assert_eq!(plugin_thread.tab_switches(), vec![Some(1)]);
+    plugin_thread.exit().unwrap();
+}
+
+#[test]
+fn plugins_open_terminals_only_with_the_run_commands_permission() {
+    let plugin_thread = MockPluginThread::start();
+    let plugin_dir = tempdir().unwrap();
+    let client_id = 1;
+    let terminal = r#"[{"command":"htop"},"/tmp","monitor"]"#;
+    let open_terminal = |host_function: &str, allow_exec_host_cmd: bool| {
+        let wasm_path = plugin_dir
+            .path()
+            .join(format!("{host_function}_{allow_exec_host_cmd}.wasm"));
+        fs::write(
+            &wasm_path,
+            host_calling_plugin_wat(&[], host_function, terminal),
+        )
+        .unwrap();
+        let location = RunPluginLocation::File(wasm_path);
+        plugin_thread.load_with(&location, client_id, allow_exec_host_cmd);
+        plugin_thread.pty_instructions()
+    };
+    let is_htop_in_tmp = |terminal_action: &Option<TerminalAction>| match terminal_action {
+        Some(TerminalAction::RunCommand(run_command)) => {
+            run_command.command == PathBuf::from("htop")
+                && run_command.cwd == Some(PathBuf::from("/tmp"))
+        },
+        _ => false,
+    };
+
+    for host_function in [
+        "host_open_terminal",
+        "host_open_terminal_floating",
+        "host_open_terminal_in_new_tab",
+    ] {
+        let pty_instructions = open_terminal(host_function, false);
+        assert!(
+            pty_instructions.is_empty(),
+            "{} opens nothing without the permission, got: {:?}",
+            host_function,
+            pty_instructions
+        );
+    }
+
+    let pty_instructions = open_terminal("host_open_terminal", true);
+    assert!(
+        matches!(
+            pty_instructions.as_slice(),
+            [PtyInstruction::SpawnTerminal(terminal_action, Some(false), Some(name), _)]
+                if is_htop_in_tmp(terminal_action) && name == "monitor"
+        ),
+        "a tiled pane is opened, got: {:?}",
+        pty_instructions
+    );
+    let pty_instructions = open_terminal("host_open_terminal_floating", true);
+    assert!(
+        matches!(
+            pty_instructions.as_slice(),
+            [PtyInstruction::SpawnTerminal(terminal_action, Some(true), Some(name), _)]
+                if is_htop_in_tmp(terminal_action) && name == "monitor"
+        ),
+        "a floating pane is opened, got: {:?}",
+        pty_instructions
+    );
+    let pty_instructions = open_terminal("host_open_terminal_in_new_tab", true);
+    assert!(
+        matches!(
+            pty_instructions.as_slice(),
+            [PtyInstruction::NewTab(terminal_action, None, Some(name), id)]
+                if is_htop_in_tmp(terminal_action) && name == "monitor" && *id == client_id
+        ),
+        "a tab named after the terminal is opened, got: {:?}",
+        pty_instructions
+    );
+    plugin_thread.exit().unwrap();
+}
+
+#[test]
+fn messages_are_posted_to_the_subscribed_plugins_with_the_given_name() {
+    let plugin_thread = MockPluginThread::start();
+    let plugin_dir = tempdir().unwrap();
+    let client_id = 1;
+    let recipient_path = plugin_dir.path().join("recipient.wasm");
+    let other_path = plugin_dir.path().join("other.wasm");
+    let sender_path = plugin_dir.path().join("sender.wasm");
+    fs::write(&recipient_path, subscribing_plugin_wat(&["CustomMessage"])).unwrap();
+    fs::write(&other_path, subscribing_plugin_wat(&["CustomMessage"])).unwrap();
+    let recipient = RunPluginLocation::File(recipient_path);
+    let recipient_id = plugin_thread.load(&recipient, client_id);
+    let other_id = plugin_thread.load(&RunPluginLocation::File(other_path), client_id);
+
+    let message = serde_json::to_string(&(recipient.to_string(), "greeting", "hello")).unwrap();
+    fs::write(
+        &sender_path,
+        host_calling_plugin_wat(&[], "host_post_message_to", &message),
+    )
+    .unwrap();
+    plugin_thread.load(&RunPluginLocation::File(sender_path), client_id);
+    assert_eq!(plugin_thread.render(recipient_id, client_id), "updated");
+    assert_eq!(
+        plugin_thread.render(other_id, client_id),
+        "waiting",
+        "plugins with another name don't receive the message"
+    );
+    plugin_thread.exit().unwrap();
+}
+
+#[test]
+fn plugins_receive_the_result_of_their_commands_even_if_they_fail_to_run() {
+    let plugin_thread = MockPluginThread::start();
+    let plugin_dir = tempdir().unwrap();
+    let client_id = 1;
+    for (i, command) in ["true", "/path/to/no/such/command"].iter().enumerate() {
+        let wasm_path = plugin_dir.path().join(format!("plugin_{i}.wasm"));
+        let run_command =
+            serde_json::to_string(&([command], BTreeMap::<String, String>::new())).unwrap();
+        fs::write(
+            &wasm_path,
+            host_calling_plugin_wat(&["RunCommandResult"], "host_run_command", &run_command),
+        )
+        .unwrap();
+        let plugin_id =
+            plugin_thread.load_with(&RunPluginLocation::File(wasm_path), client_id, true);
+        assert_eq!(
+            plugin_thread.render_until(plugin_id, client_id, "updated"),
+            "updated",
+            "the result of running {} is sent to the plugin",
+            command
+        );
+    }
+    plugin_thread.exit().unwrap();
+}
+
+#[test]
+fn plugins_open_files_only_with_the_run_commands_permission() {
+    let plugin_thread = MockPluginThread::start();
+    let plugin_dir = tempdir().unwrap();
+    let client_id = 1;
+    let open_file = |allow_exec_host_cmd: bool| {
+        let wasm_path = plugin_dir
+            .path()
+            .join(format!("open_file_{allow_exec_host_cmd}.wasm"));
+        fs::write(
+            &wasm_path,
+            host_calling_plugin_wat(&[], "host_open_file", r#""/tmp/notes.txt""#),
+        )
+        .unwrap();
+        let location = RunPluginLocation::File(wasm_path);
+        plugin_thread.load_with(&location, client_id, allow_exec_host_cmd);
+        plugin_thread.pty_instructions()
+    };
+
+    let pty_instructions = open_file(false);
+    assert!(
+        pty_instructions.is_empty(),
+        "no editor is opened without the permission, got: {:?}",
+        pty_instructions
+    );
+    let pty_instructions = open_file(true);
+    assert!(
+        matches!(
+            pty_instructions.as_slice(),
+            [PtyInstruction::SpawnTerminal(Some(TerminalAction::OpenFile(path, None)), None, None, _)]
+                if path == &PathBuf::from("/tmp/notes.txt")
+        ),
+        "an editor pane is opened, got: {:?}",
+        pty_instructions
+    );
+    plugin_thread.exit().unwrap();
+}
+
+#[test]
+fn granted_permissions_are_cached_in_the_given_file() {
+    let plugin_thread = MockPluginThread::start();
+    let plugin_dir = tempdir().unwrap();
+    let wasm_path = plugin_dir.path().join("plugin.wasm");
+    fs::write(&wasm_path, plugin_wat(VERSION, "ok")).unwrap();
+    let location = RunPluginLocation::File(wasm_path);
+    let client_id = 1;
+
+    let plugin_id = plugin_thread.load(&location, client_id);
+    plugin_thread.send(PluginInstruction::PermissionRequestResult(
+        plugin_id,
+        client_id,
+        vec![PermissionType::ReadApplicationState],
+        PermissionStatus::Granted,
+    ));
+    // instructions are handled in order, so the permissions are cached once this returns
+    plugin_thread.render(plugin_id, client_id);
+
+    let permission_cache_path = plugin_thread.data_dir.path().join("permissions.kdl");
+    let cache = PermissionCache::from_path_or_default(Some(permission_cache_path));
+    assert_eq!(
+        cache.get_permissions(&location.to_string()),
+        Some(&vec![PermissionType::ReadApplicationState])
+    );
+    plugin_thread.exit().unwrap();
+}
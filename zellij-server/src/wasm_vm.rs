@@ -1,50 +1,74 @@
 use highway::{HighwayHash, PortableHash};
 use log::{debug, info, warn};
+use notify::{
+    event::{Event as FsEvent, EventKind},
+    RecommendedWatcher, RecursiveMode, Watcher,
+};
 use semver::Version;
 use serde::{de::DeserializeOwned, Serialize};
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{hash_map::Entry, BTreeMap, BTreeSet, HashMap, HashSet},
     fmt, fs,
     path::{Path, PathBuf},
     process,
+    ptr::NonNull,
     str::FromStr,
-    sync::{mpsc::Sender, Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, AtomicU16, Ordering},
+        mpsc::{channel, Receiver, Sender},
+        Arc, Mutex,
+    },
     thread,
     time::{Duration, Instant},
 };
 use url::Url;
 use wasmer::{
-    imports, ChainableNamedResolver, Function, ImportObject, Instance, Module, Store, Value,
-    WasmerEnv,
+    imports,
+    vm::{self, MemoryError, MemoryStyle, TableStyle, VMMemoryDefinition, VMTableDefinition},
+    wasmparser::Operator,
+    BaseTunables, ChainableNamedResolver, CompilerConfig, Cranelift, Function, ImportObject,
+    Instance, MemoryType, Module, Pages, Store, TableType, Target, Tunables, Value, WasmerEnv, JIT,
+    WASM_MAX_PAGES, WASM_PAGE_SIZE,
+};
+use wasmer_middlewares::{
+    metering::{get_remaining_points, set_remaining_points, MeteringPoints},
+    Metering,
 };
 use wasmer_wasi::{Pipe, WasiEnv, WasiState};
 
 use crate::{
     logging_pipe::LoggingPipe,
     panes::PaneId,
-    pty::{ClientOrTabIndex, PtyInstruction},
+    pty::{get_default_terminal, ClientOrTabIndex, PtyInstruction},
     screen::ScreenInstruction,
+    session_scanner::SessionScanner,
     thread_bus::{Bus, ThreadSenders},
-    ClientId,
+    ClientId, ServerInstruction,
 };
 
 use zellij_utils::{
     consts::{DEBUG_MODE, VERSION, ZELLIJ_CACHE_DIR, ZELLIJ_PROJ_DIR, ZELLIJ_TMP_DIR},
-    data::{Event, EventType, PluginIds},
+    data::{
+        Event, EventType, PaneId as PluginPaneId, PermissionStatus, PermissionType, PluginIds,
+        SubscriptionKind,
+    },
     errors::{prelude::*, ContextType, PluginContext},
     input::{
-        command::TerminalAction,
-        layout::RunPlugin,
+        actions::Action,
+        command::{RunCommand, TerminalAction},
+        layout::{RunPlugin, RunPluginLocation},
+        permission::PermissionCache,
         plugins::{PluginConfig, PluginType, PluginsConfig},
     },
     serde,
+    sessions::validate_session_name,
+    setup::{get_plugin_data_dir, get_plugins_data_dir, migrate_plugin_data_dir},
 };
 
 /// Custom error for plugin version mismatch.
 ///
-/// This is thrown when, during starting a plugin, it is detected that the plugin version doesn't
-/// match the zellij version. This is treated as a fatal error and leads to instantaneous
-/// termination.
+/// This is returned when, during starting a plugin, it is detected that the plugin version doesn't
+/// match the zellij version. The plugin isn't loaded and its pane displays this error instead.
 #[derive(Debug)]
 pub struct VersionMismatchError {
     zellij_version: String,
@@ -94,6 +118,58 @@ folder from the output of the `zellij setup --check` command.
     }
 }
 
+/// Number of wasm instructions a plugin may run while handling a single call, unless its
+/// configuration says otherwise
+const DEFAULT_FUEL_LIMIT: u64 = 5_000_000_000;
+/// Size in MiB the linear memory of a plugin may grow to, unless its configuration says otherwise
+const DEFAULT_MEMORY_LIMIT: u64 = 512;
+
+/// How many levels of directories below the one Zellij was started in are watched for changes
+const FILESYSTEM_WATCH_DEPTH: usize = 1;
+/// How long changes to the filesystem are collected before being sent to plugins
+const FILESYSTEM_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Events describing the state of the session, sent again to plugins when they are reloaded
+const STATE_EVENTS: [EventType; 4] = [
+    EventType::ModeUpdate,
+    EventType::TabUpdate,
+    EventType::PaneUpdate,
+    EventType::SessionUpdate,
+];
+/// State events only sent when something changes outside of the session, so new plugins would
+/// otherwise wait for the next change to receive them
+const STATE_EVENTS_ON_LOAD: [EventType; 1] = [EventType::SessionUpdate];
+
+/// Custom error for plugins going over their limits.
+///
+/// This is thrown when a plugin runs for too long or grows its memory past what it's allowed. The
+/// offending plugin is unloaded and its pane displays this error, the rest of the session keeps
+/// working.
+#[derive(Debug)]
+pub enum PluginLimitError {
+    Fuel(u64),   // fuel limit
+    Memory(u64), // memory limit, in MiB
+}
+
+impl std::error::Error for PluginLimitError {}
+
+impl fmt::Display for PluginLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PluginLimitError::Fuel(limit) => write!(
+                f,
+                "the plugin ran for more than {} instructions (see the `fuel_limit` plugin option)",
+                limit
+            ),
+            PluginLimitError::Memory(limit) => write!(
+                f,
+                "the plugin needed more than {} MiB of memory (see the `memory_limit` plugin option)",
+                limit
+            ),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(crate) enum PluginInstruction {
     Load(Sender<u32>, RunPlugin, usize, ClientId), // tx_pid, plugin metadata, tab_index, client_ids
@@ -102,6 +178,11 @@ pub(crate) enum PluginInstruction {
     Unload(u32),                                         // plugin_id
     AddClient(ClientId),
     RemoveClient(ClientId),
+    PostMessageTo(String, String, String), // plugin name, message, payload
+    PermissionRequestResult(u32, ClientId, Vec<PermissionType>, PermissionStatus), // plugin_id, client_id, permissions, status
+    Reload(RunPluginLocation, bool), // plugin location, watch its wasm file for changes
+    Pipe(RunPluginLocation, Option<String>, Option<String>, ClientId), // plugin location, name, payload, cli client id
+    Subscribe(Vec<SubscriptionKind>, ClientId),                        // cli client id
     Exit,
 }
 
@@ -115,6 +196,13 @@ impl From<&PluginInstruction> for PluginContext {
             PluginInstruction::Exit => PluginContext::Exit,
             PluginInstruction::AddClient(_) => PluginContext::AddClient,
             PluginInstruction::RemoveClient(_) => PluginContext::RemoveClient,
+            PluginInstruction::PostMessageTo(..) => PluginContext::PostMessageTo,
+            PluginInstruction::PermissionRequestResult(..) => {
+                PluginContext::PermissionRequestResult
+            },
+            PluginInstruction::Reload(..) => PluginContext::Reload,
+            PluginInstruction::Pipe(..) => PluginContext::Pipe,
+            PluginInstruction::Subscribe(..) => PluginContext::Subscribe,
         }
     }
 }
@@ -128,29 +216,108 @@ pub(crate) struct PluginEnv {
     pub subscriptions: Arc<Mutex<HashSet<EventType>>>,
     pub tab_index: usize,
     pub client_id: ClientId,
+    // headless plugins are shared by all clients, they act for the client whose event they
+    // handled last rather than for `client_id` (the one they were started for)
+    pub acting_client_id: Option<Arc<AtomicU16>>,
+    pub default_shell: Option<TerminalAction>,
+    pub permissions: Arc<Mutex<HashSet<PermissionType>>>,
+    pub open_pipes: Arc<Mutex<HashSet<ClientId>>>, // the cli clients piping input to the plugin
+    pub memory_limit_reached: Arc<AtomicBool>,     // set when the plugin's memory could not grow
     #[allow(dead_code)]
     plugin_own_data_dir: PathBuf,
 }
 
+impl PluginEnv {
+    /// The client the plugin acts for
+    fn client_id(&self) -> ClientId {
+        self.acting_client_id
+            .as_ref()
+            .map(|client_id| client_id.load(Ordering::SeqCst))
+            .unwrap_or(self.client_id)
+    }
+    /// Where the panes opened by the plugin go: its own tab or, for headless plugins which have
+    /// none, the tab of the client they act for
+    fn pane_destination(&self) -> ClientOrTabIndex {
+        match self.acting_client_id {
+            Some(_) => ClientOrTabIndex::ClientId(self.client_id()),
+            None => ClientOrTabIndex::TabIndex(self.tab_index),
+        }
+    }
+    /// Makes a headless plugin act for `client_id`, does nothing for other plugins
+    fn act_for(&self, client_id: ClientId) {
+        if let Some(acting_client_id) = &self.acting_client_id {
+            acting_client_id.store(client_id, Ordering::SeqCst);
+        }
+    }
+}
+
+/// A plugin that couldn't be started or was unloaded, its pane displays the error until the plugin
+/// is reloaded
+pub(crate) struct FailedPlugin {
+    pub error: String,
+    pub plugin: PluginConfig,
+    pub tab_index: usize,
+    pub client_id: ClientId, // the client headless plugins were started for
+}
+
+impl FailedPlugin {
+    fn failed_to_start(
+        error: anyError,
+        plugin: PluginConfig,
+        tab_index: usize,
+        client_id: ClientId,
+    ) -> Self {
+        let message = format!("Plugin {} failed to start: {:#}", plugin.location, error);
+        log::error!("{}", message);
+        FailedPlugin {
+            error: message,
+            plugin,
+            tab_index,
+            client_id,
+        }
+    }
+}
+
 // Thread main --------------------------------------------------------------------------------------------------------
 pub(crate) fn wasm_thread_main(
     bus: Bus<PluginInstruction>,
     store: Store,
     data_dir: PathBuf,
+    permission_cache_path: PathBuf,
     plugins: PluginsConfig,
+    default_shell: Option<TerminalAction>,
 ) -> Result<()> {
     info!("Wasm main thread starts");
 
     let mut plugin_id = 0;
-    let mut headless_plugins = HashMap::new();
+    // headless plugins have no pane and are shared by all clients
+    let mut headless_plugins: HashMap<u32, (Instance, PluginEnv)> = HashMap::new(); // u32 => pid
+    let mut headless_plugins_loaded = false;
     let mut plugin_map: HashMap<(u32, ClientId), (Instance, PluginEnv)> = HashMap::new(); // u32 => pid
     let mut connected_clients: Vec<ClientId> = vec![];
-    let plugin_dir = data_dir.join("plugins/");
-    let plugin_global_data_dir = plugin_dir.join("data");
+    // plugins unloaded for going over their limits, their panes display the error instead
+    let mut failed_plugins: HashMap<u32, FailedPlugin> = HashMap::new();
+    // the latest state events sent to all clients (None) or to a specific one
+    let mut state_events: HashMap<(EventType, Option<ClientId>), Event> = HashMap::new();
+    // wasm files of the plugins reloaded whenever they change
+    let mut plugin_file_watchers: HashMap<PathBuf, RecommendedWatcher> = HashMap::new();
+    // cli clients streaming events (see `zellij subscribe`)
+    let mut subscribers: Subscribers = HashMap::new();
+    // persists between sessions, see `zellij setup --clean-plugin-data`
+    let plugin_global_data_dir = get_plugins_data_dir(&data_dir);
 
     #[cfg(not(feature = "disable_automatic_asset_installation"))]
     fs::create_dir_all(&plugin_global_data_dir).unwrap_or_else(|e| log::error!("{:?}", e));
 
+    // only kept alive while plugins are subscribed to filesystem events
+    let mut filesystem_watcher: Option<RecommendedWatcher> = None;
+    let mut watches_filesystem = false;
+    // likewise for session updates
+    let mut session_scanner: Option<SessionScanner> = None;
+    let mut scans_sessions = false;
+    // whether plugins receive every key press and mouse motion, which clients only send if so
+    let mut input_subscriptions = (false, false);
+
     loop {
         let (event, mut err_ctx) = bus.recv().expect("failed to receive event on channel");
         err_ctx.add_call(ContextType::Plugin((&event).into()));
@@ -158,24 +325,71 @@ pub(crate) fn wasm_thread_main(
             PluginInstruction::Load(pid_tx, run, tab_index, client_id) => {
                 let err_context = || format!("failed to load plugin for client {client_id}");
 
-                let plugin = plugins
-                    .get(&run)
-                    .with_context(|| format!("failed to resolve plugin {run:?}"))
-                    .with_context(err_context)
-                    .fatal();
+                let plugin = match plugins.get(&run) {
+                    Some(plugin) => plugin,
+                    None => {
+                        // eg. a typo in the tag of a built-in plugin, its pane displays the error
+                        let error =
+                            anyhow!("no plugin is configured for {}", Url::from(&run.location));
+                        let failed_plugin = FailedPlugin::failed_to_start(
+                            error,
+                            unresolved_plugin(&run),
+                            tab_index,
+                            client_id,
+                        );
+                        failed_plugins.insert(plugin_id, failed_plugin);
+                        pid_tx.send(plugin_id).with_context(err_context)?;
+                        plugin_id += 1;
+                        continue;
+                    },
+                };
 
-                let (instance, plugin_env) = start_plugin(
-                    plugin_id, client_id, &plugin, tab_index, &bus, &store, &data_dir,
-                )
-                .with_context(err_context)?;
+                let started = start_plugin(
+                    plugin_id,
+                    client_id,
+                    &plugin,
+                    tab_index,
+                    &bus,
+                    &store,
+                    &data_dir,
+                    &permission_cache_path,
+                    &default_shell,
+                );
+                let (instance, plugin_env) = match started {
+                    Ok(started) => started,
+                    Err(e) => {
+                        let failed_plugin =
+                            FailedPlugin::failed_to_start(e, plugin, tab_index, client_id);
+                        failed_plugins.insert(plugin_id, failed_plugin);
+                        pid_tx.send(plugin_id).with_context(err_context)?;
+                        plugin_id += 1;
+                        continue;
+                    },
+                };
 
                 let mut main_user_instance = instance.clone();
                 let main_user_env = plugin_env.clone();
-                load_plugin(&mut main_user_instance).with_context(err_context)?;
+                if let Err(e) = load_plugin(&mut main_user_instance, &main_user_env) {
+                    let mut over_limit = vec![];
+                    collect_over_limit(e, plugin_id, &plugin_env, &mut over_limit)
+                        .with_context(err_context)?;
+                    failed_plugins.extend(over_limit);
+                    pid_tx.send(plugin_id).with_context(err_context)?;
+                    plugin_id += 1;
+                    continue;
+                }
 
+                send_state_events(
+                    &main_user_instance,
+                    &main_user_env,
+                    &state_events,
+                    &STATE_EVENTS_ON_LOAD,
+                )
+                .non_fatal();
                 plugin_map.insert((plugin_id, client_id), (main_user_instance, main_user_env));
 
                 // clone plugins for the rest of the client ids if they exist
+                let mut over_limit = vec![];
                 for client_id in connected_clients.iter() {
                     let mut new_plugin_env = plugin_env.clone();
                     new_plugin_env.client_id = *client_id;
@@ -184,12 +398,27 @@ pub(crate) fn wasm_thread_main(
                         .wasi_env
                         .import_object(&module)
                         .with_context(err_context)?;
-                    let zellij = zellij_exports(&store, &new_plugin_env);
+                    let zellij = zellij_exports(module.store(), &new_plugin_env);
                     let mut instance = Instance::new(&module, &zellij.chain_back(wasi))
                         .with_context(err_context)?;
-                    load_plugin(&mut instance).with_context(err_context)?;
+                    if let Err(e) = load_plugin(&mut instance, &new_plugin_env) {
+                        collect_over_limit(e, plugin_id, &new_plugin_env, &mut over_limit)
+                            .with_context(err_context)?;
+                        break;
+                    }
+                    send_state_events(
+                        &instance,
+                        &new_plugin_env,
+                        &state_events,
+                        &STATE_EVENTS_ON_LOAD,
+                    )
+                    .non_fatal();
                     plugin_map.insert((plugin_id, *client_id), (instance, new_plugin_env));
                 }
+                for (plugin_id, error) in over_limit {
+                    plugin_map.retain(|&(id, _), _| id != plugin_id);
+                    failed_plugins.insert(plugin_id, error);
+                }
                 pid_tx.send(plugin_id).with_context(err_context)?;
                 plugin_id += 1;
             },
@@ -202,41 +431,64 @@ pub(crate) fn wasm_thread_main(
                     }
                 };
 
+                // FIXME: This is very janky... Maybe I should write my own macro for Event -> EventType?
+                let event_type =
+                    EventType::from_str(&event.to_string()).with_context(err_context)?;
+                if pid.is_none() && STATE_EVENTS.contains(&event_type) {
+                    if cid.is_none() {
+                        state_events.retain(|(t, _), _| *t != event_type);
+                    }
+                    state_events.insert((event_type, cid), event.clone());
+                }
+                if pid.is_none() {
+                    send_to_subscribers(&mut subscribers, event_type, cid, &event, &bus.senders);
+                }
+
+                let mut over_limit = vec![];
                 for (&(plugin_id, client_id), (instance, plugin_env)) in &plugin_map {
                     let subs = plugin_env
                         .subscriptions
                         .lock()
                         .to_anyhow()
                         .with_context(err_context)?;
-                    // FIXME: This is very janky... Maybe I should write my own macro for Event -> EventType?
-                    let event_type =
-                        EventType::from_str(&event.to_string()).with_context(err_context)?;
                     if subs.contains(&event_type)
+                        && may_receive(plugin_env, event_type)
                         && ((pid.is_none() && cid.is_none())
                             || (pid.is_none() && cid == Some(client_id))
                             || (cid.is_none() && pid == Some(plugin_id))
                             || (cid == Some(client_id) && pid == Some(plugin_id)))
                     {
-                        let update = instance
-                            .exports
-                            .get_function("update")
-                            .with_context(err_context)?;
-                        wasi_write_object(&plugin_env.wasi_env, &event);
-                        update.call(&[]).or_else::<anyError, _>(|e| {
-                            match e.downcast::<serde_json::Error>() {
-                                Ok(_) => panic!(
-                                    "{}",
-                                    anyError::new(VersionMismatchError::new(
-                                        VERSION,
-                                        "Unavailable",
-                                        &plugin_env.plugin.path
-                                    ))
-                                ),
-                                Err(e) => Err(e).with_context(err_context),
-                            }
-                        })?;
+                        if let Err(e) = apply_event_to_plugin(instance, plugin_env, &event) {
+                            collect_over_limit(e, plugin_id, plugin_env, &mut over_limit)
+                                .with_context(err_context)?;
+                        }
                     }
                 }
+                for (&plugin_id, (instance, plugin_env)) in &headless_plugins {
+                    let subs = plugin_env
+                        .subscriptions
+                        .lock()
+                        .to_anyhow()
+                        .with_context(err_context)?;
+                    // headless plugins receive the events of all clients
+                    if subs.contains(&event_type)
+                        && may_receive(plugin_env, event_type)
+                        && (pid.is_none() || pid == Some(plugin_id))
+                    {
+                        if let Some(client_id) = cid.filter(|c| connected_clients.contains(c)) {
+                            plugin_env.act_for(client_id);
+                        }
+                        if let Err(e) = apply_event_to_plugin(instance, plugin_env, &event) {
+                            collect_over_limit(e, plugin_id, plugin_env, &mut over_limit)
+                                .with_context(err_context)?;
+                        }
+                    }
+                }
+                for (plugin_id, error) in over_limit {
+                    plugin_map.retain(|&(id, _), _| id != plugin_id);
+                    headless_plugins.remove(&plugin_id);
+                    failed_plugins.insert(plugin_id, error);
+                }
                 drop(bus.senders.send_to_screen(ScreenInstruction::Render));
             },
             PluginInstruction::Render(buf_tx, pid, cid, rows, cols) => {
@@ -248,6 +500,10 @@ pub(crate) fn wasm_thread_main(
 
                 if rows == 0 || cols == 0 {
                     buf_tx.send(String::new()).with_context(err_context)?;
+                } else if let Some(failed_plugin) = failed_plugins.get(&pid) {
+                    buf_tx
+                        .send(render_plugin_error(&failed_plugin.error))
+                        .with_context(err_context)?;
                 } else {
                     let (instance, plugin_env) = plugin_map
                         .get(&(pid, cid))
@@ -258,13 +514,34 @@ pub(crate) fn wasm_thread_main(
                         .get_function("render")
                         .with_context(err_context)?;
 
-                    render
-                        .call(&[Value::I32(rows as i32), Value::I32(cols as i32)])
-                        .with_context(err_context)?;
-
-                    buf_tx
-                        .send(wasi_read_string(&plugin_env.wasi_env))
-                        .with_context(err_context)?;
+                    set_remaining_points(instance, fuel_limit(&plugin_env.plugin));
+                    let result = render.call(&[Value::I32(rows as i32), Value::I32(cols as i32)]);
+                    let rendered = check_limits(
+                        instance,
+                        &plugin_env.plugin,
+                        &plugin_env.memory_limit_reached,
+                        result.is_err(),
+                    )
+                    .and_then(|_| result.map(drop).map_err(anyError::new));
+                    match rendered {
+                        Ok(_) => {
+                            buf_tx
+                                .send(wasi_read_string(&plugin_env.wasi_env))
+                                .with_context(err_context)?;
+                        },
+                        Err(e) => {
+                            let mut over_limit = vec![];
+                            collect_over_limit(e, pid, plugin_env, &mut over_limit)
+                                .with_context(err_context)?;
+                            for (plugin_id, failed_plugin) in over_limit {
+                                buf_tx
+                                    .send(render_plugin_error(&failed_plugin.error))
+                                    .with_context(err_context)?;
+                                failed_plugins.insert(plugin_id, failed_plugin);
+                            }
+                        },
+                    }
+                    plugin_map.retain(|&(id, _), _| !failed_plugins.contains_key(&id));
                 }
             },
             PluginInstruction::Unload(pid) => {
@@ -276,11 +553,14 @@ pub(crate) fn wasm_thread_main(
                         drop(plugin_map.remove(&(plugin_id, client_id)));
                     }
                 }
+                drop(headless_plugins.remove(&pid));
+                failed_plugins.remove(&pid);
             },
             PluginInstruction::AddClient(client_id) => {
                 let err_context = || format!("failed to add plugins for client {client_id}");
 
                 connected_clients.push(client_id);
+                report_input_subscriptions(&bus.senders, client_id, input_subscriptions);
 
                 let mut seen = HashSet::new();
                 let mut new_plugins = HashMap::new();
@@ -294,38 +574,493 @@ pub(crate) fn wasm_thread_main(
                     new_plugin_env.client_id = client_id;
                     new_plugins.insert(plugin_id, (instance.module().clone(), new_plugin_env));
                 }
+                let mut over_limit = vec![];
                 for (plugin_id, (module, mut new_plugin_env)) in new_plugins.drain() {
                     let wasi = new_plugin_env
                         .wasi_env
                         .import_object(&module)
                         .with_context(err_context)?;
-                    let zellij = zellij_exports(&store, &new_plugin_env);
+                    let zellij = zellij_exports(module.store(), &new_plugin_env);
                     let mut instance = Instance::new(&module, &zellij.chain_back(wasi))
                         .with_context(err_context)?;
-                    load_plugin(&mut instance).with_context(err_context)?;
-                    plugin_map.insert((plugin_id, client_id), (instance, new_plugin_env));
+                    match load_plugin(&mut instance, &new_plugin_env) {
+                        Ok(_) => {
+                            plugin_map.insert((plugin_id, client_id), (instance, new_plugin_env));
+                        },
+                        Err(e) => {
+                            collect_over_limit(e, plugin_id, &new_plugin_env, &mut over_limit)
+                                .with_context(err_context)?
+                        },
+                    }
                 }
 
-                // load headless plugins
-                for plugin in plugins.iter() {
-                    if let PluginType::Headless = plugin.run {
-                        let (instance, plugin_env) =
-                            start_plugin(plugin_id, client_id, plugin, 0, &bus, &store, &data_dir)
-                                .with_context(err_context)?;
-                        headless_plugins.insert(plugin_id, (instance, plugin_env));
-                        plugin_id += 1;
+                // load headless plugins once per session, when the first client connects
+                if !headless_plugins_loaded {
+                    headless_plugins_loaded = true;
+                    for plugin in plugins.iter() {
+                        if let PluginType::Headless = plugin.run {
+                            let started = start_plugin(
+                                plugin_id,
+                                client_id,
+                                plugin,
+                                0,
+                                &bus,
+                                &store,
+                                &data_dir,
+                                &permission_cache_path,
+                                &default_shell,
+                            );
+                            let (mut instance, plugin_env) = match started {
+                                Ok(started) => started,
+                                Err(e) => {
+                                    let failed_plugin = FailedPlugin::failed_to_start(
+                                        e,
+                                        plugin.clone(),
+                                        0,
+                                        client_id,
+                                    );
+                                    failed_plugins.insert(plugin_id, failed_plugin);
+                                    plugin_id += 1;
+                                    continue;
+                                },
+                            };
+                            match load_plugin(&mut instance, &plugin_env) {
+                                Ok(_) => {
+                                    send_state_events(
+                                        &instance,
+                                        &plugin_env,
+                                        &state_events,
+                                        &STATE_EVENTS_ON_LOAD,
+                                    )
+                                    .non_fatal();
+                                    headless_plugins.insert(plugin_id, (instance, plugin_env));
+                                },
+                                Err(e) => {
+                                    collect_over_limit(e, plugin_id, &plugin_env, &mut over_limit)
+                                        .with_context(err_context)?
+                                },
+                            }
+                            plugin_id += 1;
+                        }
                     }
                 }
+                for (plugin_id, error) in over_limit {
+                    plugin_map.retain(|&(id, _), _| id != plugin_id);
+                    failed_plugins.insert(plugin_id, error);
+                }
             },
             PluginInstruction::RemoveClient(client_id) => {
                 connected_clients.retain(|c| c != &client_id);
+                subscribers.remove(&client_id);
+                // headless plugins acting for the client act for one of those left
+                if let Some(&remaining_client_id) = connected_clients.first() {
+                    for (_, plugin_env) in headless_plugins.values() {
+                        if plugin_env.client_id() == client_id {
+                            plugin_env.act_for(remaining_client_id);
+                        }
+                    }
+                    for failed_plugin in failed_plugins.values_mut() {
+                        if let PluginType::Headless = failed_plugin.plugin.run {
+                            if failed_plugin.client_id == client_id {
+                                failed_plugin.client_id = remaining_client_id;
+                            }
+                        }
+                    }
+                }
+                // cli clients can disconnect before the end of their input
+                close_pipe(
+                    plugin_map.values().chain(headless_plugins.values()),
+                    client_id,
+                );
+            },
+            PluginInstruction::Subscribe(kinds, client_id) => {
+                let event_types = kinds
+                    .iter()
+                    .filter_map(|kind| kind.event_type())
+                    .map(|event_type| (event_type, HashMap::new()))
+                    .collect();
+                subscribers.insert(client_id, event_types);
+                // start the stream with the current state of the session, always in the same order
+                for event_type in STATE_EVENTS {
+                    let mut events: Vec<_> = state_events
+                        .iter()
+                        .filter(|((t, _), _)| *t == event_type)
+                        .map(|((_, cid), event)| (*cid, event))
+                        .collect();
+                    events.sort_by_key(|(cid, _)| *cid);
+                    for (cid, event) in events {
+                        send_to_subscribers(&mut subscribers, event_type, cid, event, &bus.senders);
+                    }
+                }
+            },
+            PluginInstruction::PostMessageTo(plugin_name, message, payload) => {
+                let err_context = || format!("failed to post message to plugin {plugin_name}");
+
+                let event = Event::CustomMessage(message, payload);
+                let mut over_limit = vec![];
+                for (instance, plugin_env) in plugin_map.values().chain(headless_plugins.values()) {
+                    let is_recipient = plugin_env.plugin.location.to_string() == plugin_name
+                        || Url::from(&plugin_env.plugin.location).to_string() == plugin_name;
+                    let is_subscribed = plugin_env
+                        .subscriptions
+                        .lock()
+                        .to_anyhow()
+                        .with_context(err_context)?
+                        .contains(&EventType::CustomMessage);
+                    if is_recipient && is_subscribed {
+                        if let Err(e) = apply_event_to_plugin(instance, plugin_env, &event) {
+                            collect_over_limit(
+                                e,
+                                plugin_env.plugin_id,
+                                plugin_env,
+                                &mut over_limit,
+                            )
+                            .with_context(err_context)?;
+                        }
+                    }
+                }
+                for (plugin_id, error) in over_limit {
+                    plugin_map.retain(|&(id, _), _| id != plugin_id);
+                    headless_plugins.remove(&plugin_id);
+                    failed_plugins.insert(plugin_id, error);
+                }
+                drop(bus.senders.send_to_screen(ScreenInstruction::Render));
+            },
+            PluginInstruction::PermissionRequestResult(pid, cid, permissions, status) => {
+                let err_context = || format!("failed to update permissions of plugin {pid}");
+
+                let mut plugin_location = None;
+                // write access to /host is given to the wasi env when it's created, so the
+                // plugin has to be restarted to get it
+                let mut to_restart = vec![];
+                if status == PermissionStatus::Granted && !permissions.is_empty() {
+                    let all_plugins = plugin_map
+                        .iter()
+                        .map(|(&(plugin_id, _), plugin)| (plugin_id, plugin))
+                        .chain(headless_plugins.iter().map(|(&id, plugin)| (id, plugin)));
+                    for (plugin_id, (_, plugin_env)) in all_plugins {
+                        if plugin_id == pid {
+                            let mut granted = plugin_env
+                                .permissions
+                                .lock()
+                                .to_anyhow()
+                                .with_context(err_context)?;
+                            if permissions.contains(&PermissionType::ChangeFilesystem)
+                                && !granted.contains(&PermissionType::ChangeFilesystem)
+                            {
+                                to_restart.push(plugin_env.clone());
+                            }
+                            granted.extend(permissions.iter().copied());
+                            plugin_location = Some(plugin_env.plugin.location.to_string());
+                        }
+                    }
+                }
+                if let Some(plugin_location) = plugin_location {
+                    PermissionCache::from_path_or_default(Some(permission_cache_path.clone()))
+                        .cache(plugin_location, permissions.clone())
+                        .context("failed to persist granted permissions")
+                        .with_context(err_context)
+                        .non_fatal();
+                }
+                for plugin_env in to_restart {
+                    let client_id = plugin_env.client_id();
+                    let restarted = reload_plugin(
+                        pid,
+                        client_id,
+                        &plugin_env.plugin,
+                        plugin_env.tab_index,
+                        &bus,
+                        &store,
+                        &data_dir,
+                        &permission_cache_path,
+                        &default_shell,
+                        &state_events,
+                    );
+                    match restarted {
+                        Ok((instance, plugin_env)) => {
+                            // the permissions might not have been persisted
+                            plugin_env
+                                .permissions
+                                .lock()
+                                .to_anyhow()
+                                .with_context(err_context)?
+                                .extend(permissions.iter().copied());
+                            if let Some(headless_plugin) = headless_plugins.get_mut(&pid) {
+                                *headless_plugin = (instance, plugin_env);
+                            } else {
+                                plugin_map.insert((pid, client_id), (instance, plugin_env));
+                            }
+                        },
+                        Err(e) => Err::<(), _>(e).with_context(err_context).non_fatal(),
+                    }
+                }
+
+                let event = Event::PermissionRequestResult(status);
+                let mut over_limit = vec![];
+                if let Some((instance, plugin_env)) = plugin_map
+                    .get(&(pid, cid))
+                    .or_else(|| headless_plugins.get(&pid))
+                {
+                    let is_subscribed = plugin_env
+                        .subscriptions
+                        .lock()
+                        .to_anyhow()
+                        .with_context(err_context)?
+                        .contains(&EventType::PermissionRequestResult);
+                    if is_subscribed {
+                        if let Err(e) = apply_event_to_plugin(instance, plugin_env, &event) {
+                            collect_over_limit(e, pid, plugin_env, &mut over_limit)
+                                .with_context(err_context)?;
+                        }
+                    }
+                }
+                for (plugin_id, error) in over_limit {
+                    plugin_map.retain(|&(id, _), _| id != plugin_id);
+                    headless_plugins.remove(&plugin_id);
+                    failed_plugins.insert(plugin_id, error);
+                }
+                drop(bus.senders.send_to_screen(ScreenInstruction::Render));
+            },
+            PluginInstruction::Reload(location, watch) => {
+                let err_context = || format!("failed to reload plugin {location}");
+
+                // (plugin id, client id, plugin, tab index) of every instance to start again
+                let mut to_reload: Vec<(u32, ClientId, PluginConfig, usize)> = plugin_map
+                    .iter()
+                    .map(|(&(plugin_id, _), (_, plugin_env))| (plugin_id, plugin_env))
+                    .chain(
+                        headless_plugins
+                            .iter()
+                            .map(|(&plugin_id, (_, plugin_env))| (plugin_id, plugin_env)),
+                    )
+                    .filter(|(_, plugin_env)| plugin_env.plugin.location == location)
+                    .map(|(plugin_id, plugin_env)| {
+                        (
+                            plugin_id,
+                            plugin_env.client_id(),
+                            plugin_env.plugin.clone(),
+                            plugin_env.tab_index,
+                        )
+                    })
+                    .collect();
+                // failed plugins have no instance left, they're started again for every client
+                for (&plugin_id, failed_plugin) in &failed_plugins {
+                    if failed_plugin.plugin.location != location {
+                        continue;
+                    }
+                    let client_ids = match failed_plugin.plugin.run {
+                        PluginType::Headless => vec![failed_plugin.client_id],
+                        PluginType::Pane(_) => connected_clients.clone(),
+                    };
+                    for client_id in client_ids {
+                        to_reload.push((
+                            plugin_id,
+                            client_id,
+                            failed_plugin.plugin.clone(),
+                            failed_plugin.tab_index,
+                        ));
+                    }
+                }
+                let wasm_path = match to_reload.first() {
+                    Some((_, _, plugin, _)) => plugin.resolve_wasm_path(&data_dir.join("plugins/")),
+                    None => {
+                        log::warn!("No running plugin at {} to reload", location);
+                        continue;
+                    },
+                };
+                let wasm_path = match wasm_path {
+                    Some(wasm_path) => wasm_path,
+                    None => {
+                        // eg. the plugin is being rebuilt, it'll be reloaded once it's written
+                        log::warn!("Cannot find the wasm file of {}, not reloading", location);
+                        continue;
+                    },
+                };
+                if watch {
+                    if let Entry::Vacant(entry) = plugin_file_watchers.entry(wasm_path) {
+                        if let Ok(watcher) =
+                            watch_plugin_file(bus.senders.clone(), entry.key(), location.clone())
+                                .with_context(err_context)
+                                .to_log()
+                        {
+                            entry.insert(watcher);
+                        }
+                    }
+                }
+
+                let previously_failed: HashSet<u32> = to_reload
+                    .iter()
+                    .map(|(plugin_id, ..)| *plugin_id)
+                    .filter(|plugin_id| failed_plugins.remove(plugin_id).is_some())
+                    .collect();
+                for (plugin_id, client_id, plugin, tab_index) in to_reload {
+                    let reloaded = reload_plugin(
+                        plugin_id,
+                        client_id,
+                        &plugin,
+                        tab_index,
+                        &bus,
+                        &store,
+                        &data_dir,
+                        &permission_cache_path,
+                        &default_shell,
+                        &state_events,
+                    );
+                    match reloaded {
+                        Ok(reloaded) => {
+                            info!("Reloaded plugin {} ({})", plugin_id, location);
+                            match plugin.run {
+                                PluginType::Headless => {
+                                    headless_plugins.insert(plugin_id, reloaded);
+                                },
+                                PluginType::Pane(_) => {
+                                    plugin_map.insert((plugin_id, client_id), reloaded);
+                                },
+                            }
+                        },
+                        // the pane keeps displaying why the plugin can't run
+                        Err(e) if previously_failed.contains(&plugin_id) => {
+                            let failed_plugin =
+                                FailedPlugin::failed_to_start(e, plugin, tab_index, client_id);
+                            failed_plugins.insert(plugin_id, failed_plugin);
+                        },
+                        // keep the running plugin, the new build might just be broken
+                        Err(e) => Err::<(), _>(e).with_context(err_context).non_fatal(),
+                    }
+                }
+                // a failed plugin is only back once it runs for every client
+                for plugin_id in previously_failed {
+                    if failed_plugins.contains_key(&plugin_id) {
+                        plugin_map.retain(|&(id, _), _| id != plugin_id);
+                        headless_plugins.remove(&plugin_id);
+                    }
+                }
+                drop(bus.senders.send_to_screen(ScreenInstruction::Render));
+            },
+            PluginInstruction::Pipe(location, name, payload, cli_client_id) => {
+                let err_context = || format!("failed to pipe input to plugin {location}");
+
+                let is_end_of_input = payload.is_none();
+                let event = Event::PipeMessage(name, payload, cli_client_id);
+                // plugins with an instance per client only get the input once, so that they
+                // reply to it once
+                let mut recipients: BTreeMap<u32, &(Instance, PluginEnv)> = BTreeMap::new();
+                for (&(plugin_id, _), plugin) in &plugin_map {
+                    if plugin.1.plugin.location == location {
+                        recipients.entry(plugin_id).or_insert(plugin);
+                    }
+                }
+                for (&plugin_id, plugin) in &headless_plugins {
+                    if plugin.1.plugin.location == location {
+                        recipients.insert(plugin_id, plugin);
+                    }
+                }
+                if recipients.is_empty() {
+                    log::warn!("No running plugin at {} to pipe input to", location);
+                }
+
+                let mut over_limit = vec![];
+                for (plugin_id, (instance, plugin_env)) in recipients {
+                    let is_subscribed = plugin_env
+                        .subscriptions
+                        .lock()
+                        .to_anyhow()
+                        .with_context(err_context)?
+                        .contains(&EventType::PipeMessage);
+                    if is_subscribed {
+                        plugin_env
+                            .open_pipes
+                            .lock()
+                            .to_anyhow()
+                            .with_context(err_context)?
+                            .insert(cli_client_id);
+                        if let Err(e) = apply_event_to_plugin(instance, plugin_env, &event) {
+                            collect_over_limit(e, plugin_id, plugin_env, &mut over_limit)
+                                .with_context(err_context)?;
+                        }
+                    }
+                }
+                for (plugin_id, error) in over_limit {
+                    plugin_map.retain(|&(id, _), _| id != plugin_id);
+                    headless_plugins.remove(&plugin_id);
+                    failed_plugins.insert(plugin_id, error);
+                }
+                // nobody is left to read the rest of the input
+                let has_recipients = plugin_map
+                    .values()
+                    .chain(headless_plugins.values())
+                    .any(|(_, plugin_env)| plugin_env.plugin.location == location);
+                if is_end_of_input || !has_recipients {
+                    close_pipe(
+                        plugin_map.values().chain(headless_plugins.values()),
+                        cli_client_id,
+                    );
+                    bus.senders
+                        .send_to_server(ServerInstruction::ClosePipe(cli_client_id))
+                        .with_context(err_context)?;
+                }
+                drop(bus.senders.send_to_screen(ScreenInstruction::Render));
             },
             PluginInstruction::Exit => break,
         }
+
+        // plugins (un)subscribe while handling the instruction, or are unloaded by it
+        let should_watch_filesystem = plugin_map
+            .values()
+            .chain(headless_plugins.values())
+            .any(|(_, plugin_env)| is_subscribed_to_filesystem(plugin_env));
+        if should_watch_filesystem != watches_filesystem {
+            watches_filesystem = should_watch_filesystem;
+            filesystem_watcher = if should_watch_filesystem {
+                watch_filesystem(bus.senders.clone())
+                    .context("failed to watch the filesystem for plugins")
+                    .to_log()
+                    .ok()
+            } else {
+                None
+            };
+        }
+        // cli clients subscribed to session updates (see `zellij subscribe`) need them as well
+        let should_scan_sessions = plugin_map
+            .values()
+            .chain(headless_plugins.values())
+            .any(|(_, plugin_env)| is_subscribed_to(plugin_env, EventType::SessionUpdate))
+            || subscribers
+                .values()
+                .any(|event_types| event_types.contains_key(&EventType::SessionUpdate));
+        if should_scan_sessions != scans_sessions {
+            scans_sessions = should_scan_sessions;
+            session_scanner = if should_scan_sessions {
+                SessionScanner::start(bus.senders.clone())
+                    .context("failed to scan sessions for plugins")
+                    .to_log()
+                    .ok()
+            } else {
+                None
+            };
+        }
+        let is_received = |event_type| {
+            plugin_map
+                .values()
+                .chain(headless_plugins.values())
+                .any(|(_, plugin_env)| {
+                    is_subscribed_to(plugin_env, event_type) && may_receive(plugin_env, event_type)
+                })
+        };
+        let should_report_input = (
+            is_received(EventType::KeyPress),
+            is_received(EventType::MouseHover),
+        );
+        if should_report_input != input_subscriptions {
+            input_subscriptions = should_report_input;
+            for client_id in &connected_clients {
+                report_input_subscriptions(&bus.senders, *client_id, input_subscriptions);
+            }
+        }
     }
+    drop(filesystem_watcher);
+    drop(session_scanner);
     info!("wasm main thread exits");
-    fs::remove_dir_all(&plugin_global_data_dir)
-        .context("failed to cleanup plugin data directory")?;
     Ok(())
 }
 
@@ -338,6 +1073,8 @@ fn start_plugin(
     bus: &Bus<PluginInstruction>,
     store: &Store,
     data_dir: &Path,
+    permission_cache_path: &Path,
+    default_shell: &Option<TerminalAction>,
 ) -> Result<(Instance, PluginEnv)> {
     let err_context = || format!("failed to start plugin {plugin:#?} for client {client_id}");
 
@@ -352,8 +1089,7 @@ fn start_plugin(
     let wasm_bytes = plugin
         .resolve_wasm_bytes(&data_dir.join("plugins/"))
         .context("cannot resolve wasm bytes")
-        .with_context(err_context)
-        .fatal();
+        .with_context(err_context)?;
 
     let hash: String = PortableHash::default()
         .hash256(&wasm_bytes)
@@ -361,7 +1097,14 @@ fn start_plugin(
         .map(ToString::to_string)
         .collect();
 
-    let cached_path = ZELLIJ_PROJ_DIR.cache_dir().join(&hash);
+    // the memory of the plugin can't grow past its limit, whatever a single call asks for
+    let memory_limit_reached = Arc::new(AtomicBool::new(false));
+    let store = &memory_limited_store(store, memory_limit(plugin), memory_limit_reached.clone());
+
+    // modules are compiled with metering, they can't be shared with unmetered ones cached before
+    let cached_path = ZELLIJ_PROJ_DIR
+        .cache_dir()
+        .join(format!("{}-metered", hash));
 
     let module = unsafe {
         match Module::deserialize_from_file(store, &cached_path) {
@@ -369,16 +1112,23 @@ fn start_plugin(
             Err(e) => {
                 let inner_context = || format!("failed to recover from {e:?}");
 
-                let m = Module::new(store, &wasm_bytes)
+                // the metering middleware can only instrument a single module, so every plugin is
+                // compiled in a store of its own and then loaded into the shared one
+                let serialized = Module::new(&metered_store(), &wasm_bytes)
+                    .with_context(inner_context)
+                    .with_context(err_context)?
+                    .serialize()
                     .with_context(inner_context)
                     .with_context(err_context)?;
                 fs::create_dir_all(ZELLIJ_PROJ_DIR.cache_dir())
                     .with_context(inner_context)
                     .with_context(err_context)?;
-                m.serialize_to_file(&cached_path)
+                fs::write(&cached_path, &serialized)
                     .with_context(inner_context)
                     .with_context(err_context)?;
-                m
+                Module::deserialize(store, &serialized)
+                    .with_context(inner_context)
+                    .with_context(err_context)?
             },
         }
     };
@@ -386,7 +1136,10 @@ fn start_plugin(
     let output = Pipe::new();
     let input = Pipe::new();
     let stderr = LoggingPipe::new(&plugin.location.to_string(), plugin_id);
-    let plugin_own_data_dir = ZELLIJ_CACHE_DIR.join(Url::from(&plugin.location).to_string());
+    let plugin_own_data_dir = get_plugin_data_dir(data_dir, &Url::from(&plugin.location));
+    migrate_plugin_data_dir(&ZELLIJ_CACHE_DIR, data_dir, &Url::from(&plugin.location))
+        .with_context(|| format!("failed to migrate the data of plugin {plugin:#?}"))
+        .non_fatal();
     fs::create_dir_all(&plugin_own_data_dir)
         .with_context(|| format!("failed to create datadir in {plugin_own_data_dir:?}"))
         .with_context(|| format!("while starting plugin {plugin:#?}"))
@@ -398,9 +1151,26 @@ fn start_plugin(
         .with_context(|| format!("while starting plugin {plugin:#?}"))
         .non_fatal();
 
+    let mut permissions: HashSet<PermissionType> =
+        PermissionCache::from_path_or_default(Some(permission_cache_path.to_path_buf()))
+            .get_permissions(&plugin.location.to_string())
+            .map(|granted| granted.iter().copied().collect())
+            .unwrap_or_default();
+    if plugin._allow_exec_host_cmd {
+        permissions.insert(PermissionType::RunCommands);
+    }
+    // the directory zellij was started in is only writable if the user allowed it
+    let can_change_filesystem = permissions.contains(&PermissionType::ChangeFilesystem);
+
     let mut wasi_env = WasiState::new("Zellij")
         .env("CLICOLOR_FORCE", "1")
-        .map_dir("/host", ".")
+        .preopen(|p| {
+            p.directory(".")
+                .alias("/host")
+                .read(true)
+                .write(can_change_filesystem)
+                .create(can_change_filesystem)
+        })
         .with_context(err_context)?
         .map_dir("/data", &plugin_own_data_dir)
         .with_context(err_context)?
@@ -425,6 +1195,14 @@ fn start_plugin(
         subscriptions: Arc::new(Mutex::new(HashSet::new())),
         plugin_own_data_dir,
         tab_index,
+        acting_client_id: match plugin.run {
+            PluginType::Headless => Some(Arc::new(AtomicU16::new(client_id))),
+            PluginType::Pane(_) => None,
+        },
+        default_shell: default_shell.clone(),
+        permissions: Arc::new(Mutex::new(permissions)),
+        open_pipes: Arc::new(Mutex::new(HashSet::new())),
+        memory_limit_reached,
     };
 
     let zellij = zellij_exports(store, &plugin_env);
@@ -433,14 +1211,13 @@ fn start_plugin(
     // Check plugin version
     let plugin_version_func = match instance.exports.get_function("plugin_version") {
         Ok(val) => val,
-        Err(_) => panic!(
-            "{}",
-            anyError::new(VersionMismatchError::new(
+        Err(_) => {
+            return Err(anyError::new(VersionMismatchError::new(
                 VERSION,
                 "Unavailable",
-                &plugin_env.plugin.path
-            ))
-        ),
+                &plugin_env.plugin.path,
+            )))
+        },
     };
     plugin_version_func.call(&[]).with_context(err_context)?;
     let plugin_version_str = wasi_read_string(&plugin_env.wasi_env);
@@ -451,31 +1228,392 @@ fn start_plugin(
         .context("failed to parse zellij version")
         .with_context(err_context)?;
     if plugin_version != zellij_version {
-        panic!(
+        return Err(anyError::new(VersionMismatchError::new(
+            VERSION,
+            &plugin_version_str,
+            &plugin_env.plugin.path,
+        )));
+    }
+
+    Ok((instance, plugin_env))
+}
+
+/// The configuration of a plugin whose location could not be resolved, kept to display why
+fn unresolved_plugin(run: &RunPlugin) -> PluginConfig {
+    PluginConfig {
+        path: PathBuf::from(run.location.to_string()),
+        run: PluginType::Pane(None),
+        _allow_exec_host_cmd: false,
+        location: run.location.clone(),
+        userspace_configuration: run.configuration.clone(),
+        fuel_limit: None,
+        memory_limit: None,
+    }
+}
+
+/// Starts and loads a new instance of a plugin, to replace the running or failed one
+#[allow(clippy::too_many_arguments)]
+fn reload_plugin(
+    plugin_id: u32,
+    client_id: ClientId,
+    plugin: &PluginConfig,
+    tab_index: usize,
+    bus: &Bus<PluginInstruction>,
+    store: &Store,
+    data_dir: &Path,
+    permission_cache_path: &Path,
+    default_shell: &Option<TerminalAction>,
+    state_events: &HashMap<(EventType, Option<ClientId>), Event>,
+) -> Result<(Instance, PluginEnv)> {
+    let (mut instance, plugin_env) = start_plugin(
+        plugin_id,
+        client_id,
+        plugin,
+        tab_index,
+        bus,
+        store,
+        data_dir,
+        permission_cache_path,
+        default_shell,
+    )?;
+    load_plugin(&mut instance, &plugin_env)?;
+    send_state_events(&instance, &plugin_env, state_events, &STATE_EVENTS)?;
+    Ok((instance, plugin_env))
+}
+
+fn apply_event_to_plugin(instance: &Instance, plugin_env: &PluginEnv, event: &Event) -> Result<()> {
+    let err_context = || format!("failed to apply event to plugin {}", plugin_env.plugin_id);
+
+    let update = instance
+        .exports
+        .get_function("update")
+        .with_context(err_context)?;
+    wasi_write_object(&plugin_env.wasi_env, event);
+    set_remaining_points(instance, fuel_limit(&plugin_env.plugin));
+    let result = update.call(&[]);
+    check_limits(
+        instance,
+        &plugin_env.plugin,
+        &plugin_env.memory_limit_reached,
+        result.is_err(),
+    )
+    .with_context(err_context)?;
+    result.or_else::<anyError, _>(|e| match e.downcast::<serde_json::Error>() {
+        Ok(_) => panic!(
             "{}",
             anyError::new(VersionMismatchError::new(
                 VERSION,
-                &plugin_version_str,
+                "Unavailable",
                 &plugin_env.plugin.path
             ))
-        );
-    }
-
-    Ok((instance, plugin_env))
+        ),
+        Err(e) => Err(e).with_context(err_context),
+    })?;
+    Ok(())
 }
 
-fn load_plugin(instance: &mut Instance) -> Result<()> {
+fn load_plugin(instance: &mut Instance, plugin_env: &PluginEnv) -> Result<()> {
     let err_context = || format!("failed to load plugin from instance {instance:#?}");
 
     let load_function = instance
         .exports
         .get_function("_start")
         .with_context(err_context)?;
+    set_remaining_points(instance, fuel_limit(&plugin_env.plugin));
     // This eventually calls the `.load()` method
-    load_function.call(&[]).with_context(err_context)?;
+    let result = load_function.call(&[]);
+    check_limits(
+        instance,
+        &plugin_env.plugin,
+        &plugin_env.memory_limit_reached,
+        result.is_err(),
+    )
+    .with_context(err_context)?;
+    result.with_context(err_context)?;
+    Ok(())
+}
+
+/// Creates a store whose modules count the instructions they run, so that plugins stuck in a loop
+/// can be interrupted once they run out of fuel
+pub(crate) fn metered_store() -> Store {
+    let metering = Arc::new(Metering::new(DEFAULT_FUEL_LIMIT, |_: &Operator| -> u64 {
+        1
+    }));
+    let mut compiler_config = Cranelift::default();
+    compiler_config.push_middleware(metering);
+    Store::new(&JIT::new(compiler_config).engine())
+}
+
+fn fuel_limit(plugin: &PluginConfig) -> u64 {
+    plugin.fuel_limit.unwrap_or(DEFAULT_FUEL_LIMIT)
+}
+
+fn memory_limit(plugin: &PluginConfig) -> u64 {
+    plugin.memory_limit.unwrap_or(DEFAULT_MEMORY_LIMIT)
+}
+
+/// Creates a store sharing the engine of `store`, whose instances can't grow their memory past
+/// `memory_limit` MiB. Refused growth is recorded in `memory_limit_reached`.
+fn memory_limited_store(
+    store: &Store,
+    memory_limit: u64,
+    memory_limit_reached: Arc<AtomicBool>,
+) -> Store {
+    let pages_per_mib = (1024 * 1024 / WASM_PAGE_SIZE) as u64;
+    let limit = memory_limit
+        .saturating_mul(pages_per_mib)
+        .min(WASM_MAX_PAGES as u64);
+    let tunables = LimitingTunables {
+        base: BaseTunables::for_target(&Target::default()),
+        limit: Pages(limit as u32),
+        limit_reached: memory_limit_reached,
+    };
+    Store::new_with_tunables(store.engine().as_ref(), tunables)
+}
+
+/// Creates memories with their maximum size capped to the limit, so that a plugin can't exhaust
+/// the memory of the host while handling a single call
+struct LimitingTunables {
+    base: BaseTunables,
+    limit: Pages,
+    limit_reached: Arc<AtomicBool>,
+}
+
+impl LimitingTunables {
+    fn adjust_memory(&self, requested: &MemoryType) -> Result<MemoryType, MemoryError> {
+        if requested.minimum > self.limit {
+            return Err(MemoryError::Generic(format!(
+                "the plugin needs more memory than its limit of {} pages",
+                self.limit.0
+            )));
+        }
+        let mut adjusted = requested.clone();
+        adjusted.maximum = Some(
+            requested
+                .maximum
+                .map_or(self.limit, |max| max.min(self.limit)),
+        );
+        Ok(adjusted)
+    }
+    fn limit_memory(&self, memory: Arc<dyn vm::Memory>) -> Arc<dyn vm::Memory> {
+        Arc::new(LimitedMemory {
+            memory,
+            limit_reached: self.limit_reached.clone(),
+        })
+    }
+}
+
+impl Tunables for LimitingTunables {
+    fn memory_style(&self, memory: &MemoryType) -> MemoryStyle {
+        match self.adjust_memory(memory) {
+            Ok(adjusted) => self.base.memory_style(&adjusted),
+            Err(_) => self.base.memory_style(memory),
+        }
+    }
+    fn table_style(&self, table: &TableType) -> TableStyle {
+        self.base.table_style(table)
+    }
+    fn create_host_memory(
+        &self,
+        ty: &MemoryType,
+        style: &MemoryStyle,
+    ) -> Result<Arc<dyn vm::Memory>, MemoryError> {
+        let adjusted = self.adjust_memory(ty)?;
+        let memory = self.base.create_host_memory(&adjusted, style)?;
+        Ok(self.limit_memory(memory))
+    }
+    unsafe fn create_vm_memory(
+        &self,
+        ty: &MemoryType,
+        style: &MemoryStyle,
+        vm_definition_location: NonNull<VMMemoryDefinition>,
+    ) -> Result<Arc<dyn vm::Memory>, MemoryError> {
+        let adjusted = self.adjust_memory(ty)?;
+        let memory = self
+            .base
+            .create_vm_memory(&adjusted, style, vm_definition_location)?;
+        Ok(self.limit_memory(memory))
+    }
+    fn create_host_table(
+        &self,
+        ty: &TableType,
+        style: &TableStyle,
+    ) -> Result<Arc<dyn vm::Table>, String> {
+        self.base.create_host_table(ty, style)
+    }
+    unsafe fn create_vm_table(
+        &self,
+        ty: &TableType,
+        style: &TableStyle,
+        vm_definition_location: NonNull<VMTableDefinition>,
+    ) -> Result<Arc<dyn vm::Table>, String> {
+        self.base.create_vm_table(ty, style, vm_definition_location)
+    }
+}
+
+/// A memory recording when it is asked to grow past its maximum size
+#[derive(Debug)]
+struct LimitedMemory {
+    memory: Arc<dyn vm::Memory>,
+    limit_reached: Arc<AtomicBool>,
+}
+
+impl vm::Memory for LimitedMemory {
+    fn ty(&self) -> &MemoryType {
+        self.memory.ty()
+    }
+    fn style(&self) -> &MemoryStyle {
+        self.memory.style()
+    }
+    fn size(&self) -> Pages {
+        self.memory.size()
+    }
+    fn grow(&self, delta: Pages) -> Result<Pages, MemoryError> {
+        self.memory.grow(delta).map_err(|e| {
+            self.limit_reached.store(true, Ordering::SeqCst);
+            e
+        })
+    }
+    fn vmmemory(&self) -> NonNull<VMMemoryDefinition> {
+        self.memory.vmmemory()
+    }
+}
+
+/// Checks whether the last call into the plugin failed because it went over its limits. Running
+/// out of fuel traps the call, while growing the memory past its limit is refused, which plugins
+/// usually can't recover from. In both cases, the call failing is reported as going over the
+/// limit.
+///
+/// Limits are only set for the plugins configured in the `plugins` block of the config, plugins
+/// loaded straight from a `file:` location always run with the defaults.
+fn check_limits(
+    instance: &Instance,
+    plugin: &PluginConfig,
+    memory_limit_reached: &AtomicBool,
+    call_failed: bool,
+) -> Result<()> {
+    // cleared for the next call, a plugin handling the refusal gracefully keeps running
+    let memory_limit_reached = memory_limit_reached.swap(false, Ordering::SeqCst);
+    if call_failed && get_remaining_points(instance) == MeteringPoints::Exhausted {
+        return Err(anyError::new(PluginLimitError::Fuel(fuel_limit(plugin))));
+    }
+    if call_failed && memory_limit_reached {
+        return Err(anyError::new(PluginLimitError::Memory(memory_limit(
+            plugin,
+        ))));
+    }
+    Ok(())
+}
+
+/// Plugins going over their limits are collected to be unloaded, any other error is passed on
+fn collect_over_limit(
+    error: anyError,
+    plugin_id: u32,
+    plugin_env: &PluginEnv,
+    over_limit: &mut Vec<(u32, FailedPlugin)>,
+) -> Result<()> {
+    match error.downcast_ref::<PluginLimitError>() {
+        Some(limit_error) => {
+            let message = format!(
+                "Plugin {} was unloaded: {}",
+                plugin_env.plugin.location, limit_error
+            );
+            log::error!("{}", message);
+            over_limit.push((
+                plugin_id,
+                FailedPlugin {
+                    error: message,
+                    plugin: plugin_env.plugin.clone(),
+                    tab_index: plugin_env.tab_index,
+                    client_id: plugin_env.client_id(),
+                },
+            ));
+            Ok(())
+        },
+        None => Err(error),
+    }
+}
+
+/// Sends the latest state of the session to a freshly (re)loaded plugin, the way the events were
+/// originally sent to its client
+fn send_state_events(
+    instance: &Instance,
+    plugin_env: &PluginEnv,
+    state_events: &HashMap<(EventType, Option<ClientId>), Event>,
+    event_types: &[EventType],
+) -> Result<()> {
+    let err_context = || format!("failed to send state to plugin {}", plugin_env.plugin_id);
+
+    for &event_type in event_types {
+        let is_subscribed = plugin_env
+            .subscriptions
+            .lock()
+            .to_anyhow()
+            .with_context(err_context)?
+            .contains(&event_type);
+        let event = state_events
+            .get(&(event_type, Some(plugin_env.client_id())))
+            .or_else(|| state_events.get(&(event_type, None)));
+        if let (true, Some(event)) = (is_subscribed, event) {
+            apply_event_to_plugin(instance, plugin_env, event).with_context(err_context)?;
+        }
+    }
     Ok(())
 }
 
+/// The cli clients streaming events (see `zellij subscribe`), with the event types they subscribe to
+/// and the last line sent to them per type and client the event was sent to
+type Subscribers = HashMap<ClientId, HashMap<EventType, HashMap<Option<ClientId>, String>>>;
+
+/// Sends an event as a line of JSON to the cli clients subscribed to its type. Events sent to a
+/// single client carry its id as `client_id`, so subscribers can tell the state of each client
+/// apart.
+///
+/// Events sent to each client carry the same state, so an event identical to the last one a
+/// subscriber received for the same client is skipped.
+fn send_to_subscribers(
+    subscribers: &mut Subscribers,
+    event_type: EventType,
+    cid: Option<ClientId>,
+    event: &Event,
+    senders: &ThreadSenders,
+) {
+    if !subscribers
+        .values()
+        .any(|event_types| event_types.contains_key(&event_type))
+    {
+        return;
+    }
+    let line = match serde_json::to_value(event) {
+        Ok(mut line) => {
+            if let (Some(cid), Some(fields)) = (cid, line.as_object_mut()) {
+                fields.insert("client_id".to_string(), cid.into());
+            }
+            line.to_string()
+        },
+        Err(e) => {
+            log::error!("Failed to serialize event for subscribers: {}", e);
+            return;
+        },
+    };
+    for (client_id, event_types) in subscribers.iter_mut() {
+        if let Some(last_lines) = event_types.get_mut(&event_type) {
+            if last_lines.get(&cid) != Some(&line) {
+                let _ = senders.send_to_server(ServerInstruction::SubscriptionEvent(
+                    *client_id,
+                    line.clone(),
+                ));
+                last_lines.insert(cid, line.clone());
+            }
+        }
+    }
+}
+
+/// What the pane of a plugin unloaded for going over its limits displays instead of the plugin
+fn render_plugin_error(error: &str) -> String {
+    format!("\u{1b}[1;31mERROR\u{1b}[m\n\n{}\n", error)
+}
+
 // Plugin API ---------------------------------------------------------------------------------------------------------
 
 pub(crate) fn zellij_exports(store: &Store, plugin_env: &PluginEnv) -> ImportObject {
@@ -494,12 +1632,30 @@ pub(crate) fn zellij_exports(store: &Store, plugin_env: &PluginEnv) -> ImportObj
         host_subscribe,
         host_unsubscribe,
         host_set_selectable,
+        host_set_pane_title,
+        host_set_borderless,
+        host_set_preferred_size,
         host_get_plugin_ids,
         host_get_zellij_version,
+        host_get_plugin_configuration,
         host_open_file,
+        host_open_terminal,
+        host_open_terminal_floating,
+        host_open_terminal_in_new_tab,
         host_switch_tab_to,
         host_set_timeout,
         host_exec_cmd,
+        host_run_command,
+        host_write,
+        host_write_chars,
+        host_write_to_pane_id,
+        host_write_chars_to_pane_id,
+        host_post_message_to,
+        host_run_action,
+        host_pipe_output,
+        host_switch_session,
+        host_request_pane_contents,
+        host_request_permission,
         host_report_panic,
     }
 }
@@ -538,6 +1694,63 @@ fn host_set_selectable(plugin_env: &PluginEnv, selectable: i32) {
     }
 }
 
+fn host_set_pane_title(plugin_env: &PluginEnv) {
+    let title: String = wasi_read_object(&plugin_env.wasi_env);
+    match plugin_env.plugin.run {
+        PluginType::Pane(_) => plugin_env
+            .senders
+            .send_to_screen(ScreenInstruction::SetPaneTitle(
+                PaneId::Plugin(plugin_env.plugin_id),
+                title,
+            ))
+            .unwrap(),
+        _ => {
+            debug!(
+                "{} - Calling method 'host_set_pane_title' does nothing for headless plugins",
+                plugin_env.plugin.location
+            )
+        },
+    }
+}
+
+fn host_set_borderless(plugin_env: &PluginEnv, borderless: i32) {
+    match plugin_env.plugin.run {
+        PluginType::Pane(_) => plugin_env
+            .senders
+            .send_to_screen(ScreenInstruction::SetPaneBorderless(
+                PaneId::Plugin(plugin_env.plugin_id),
+                borderless != 0,
+            ))
+            .unwrap(),
+        _ => {
+            debug!(
+                "{} - Calling method 'host_set_borderless' does nothing for headless plugins",
+                plugin_env.plugin.location
+            )
+        },
+    }
+}
+
+fn host_set_preferred_size(plugin_env: &PluginEnv) {
+    let (rows, cols): (usize, usize) = wasi_read_object(&plugin_env.wasi_env);
+    match plugin_env.plugin.run {
+        PluginType::Pane(_) => plugin_env
+            .senders
+            .send_to_screen(ScreenInstruction::SetPanePreferredSize(
+                PaneId::Plugin(plugin_env.plugin_id),
+                rows,
+                cols,
+            ))
+            .unwrap(),
+        _ => {
+            debug!(
+                "{} - Calling method 'host_set_preferred_size' does nothing for headless plugins",
+                plugin_env.plugin.location
+            )
+        },
+    }
+}
+
 fn host_get_plugin_ids(plugin_env: &PluginEnv) {
     let ids = PluginIds {
         plugin_id: plugin_env.plugin_id,
@@ -550,15 +1763,76 @@ fn host_get_zellij_version(plugin_env: &PluginEnv) {
     wasi_write_object(&plugin_env.wasi_env, VERSION);
 }
 
+fn host_get_plugin_configuration(plugin_env: &PluginEnv) {
+    wasi_write_object(
+        &plugin_env.wasi_env,
+        plugin_env.plugin.userspace_configuration.inner(),
+    );
+}
+
 fn host_open_file(plugin_env: &PluginEnv) {
     let path: PathBuf = wasi_read_object(&plugin_env.wasi_env);
+    // the file is opened in an editor pane, like `Action::EditFile`
+    if !has_permission(plugin_env, PermissionType::RunCommands) {
+        return;
+    }
     plugin_env
         .senders
         .send_to_pty(PtyInstruction::SpawnTerminal(
             Some(TerminalAction::OpenFile(path, None)),
             None,
             None,
-            ClientOrTabIndex::TabIndex(plugin_env.tab_index),
+            plugin_env.pane_destination(),
+        ))
+        .unwrap();
+}
+
+fn host_open_terminal(plugin_env: &PluginEnv) {
+    let should_float = false;
+    let (terminal_action, name) = read_terminal_to_open(plugin_env);
+    if !has_permission(plugin_env, PermissionType::RunCommands) {
+        return;
+    }
+    plugin_env
+        .senders
+        .send_to_pty(PtyInstruction::SpawnTerminal(
+            terminal_action,
+            Some(should_float),
+            name,
+            plugin_env.pane_destination(),
+        ))
+        .unwrap();
+}
+
+fn host_open_terminal_floating(plugin_env: &PluginEnv) {
+    let should_float = true;
+    let (terminal_action, name) = read_terminal_to_open(plugin_env);
+    if !has_permission(plugin_env, PermissionType::RunCommands) {
+        return;
+    }
+    plugin_env
+        .senders
+        .send_to_pty(PtyInstruction::SpawnTerminal(
+            terminal_action,
+            Some(should_float),
+            name,
+            plugin_env.pane_destination(),
+        ))
+        .unwrap();
+}
+
+fn host_open_terminal_in_new_tab(plugin_env: &PluginEnv) {
+    let (terminal_action, name) = read_terminal_to_open(plugin_env);
+    if !has_permission(plugin_env, PermissionType::RunCommands) {
+        return;
+    }
+    plugin_env
+        .senders
+        .send_to_pty(PtyInstruction::NewTab(
+            terminal_action,
+            None,
+            name,
+            plugin_env.client_id(),
         ))
         .unwrap();
 }
@@ -568,7 +1842,7 @@ fn host_switch_tab_to(plugin_env: &PluginEnv, tab_idx: u32) {
         .senders
         .send_to_screen(ScreenInstruction::GoToTab(
             tab_idx,
-            Some(plugin_env.client_id),
+            Some(plugin_env.client_id()),
         ))
         .unwrap();
 }
@@ -585,7 +1859,7 @@ fn host_set_timeout(plugin_env: &PluginEnv, secs: f64) {
     // But that's a lot of code, and this is a few lines:
     let send_plugin_instructions = plugin_env.senders.to_plugin.clone();
     let update_target = Some(plugin_env.plugin_id);
-    let client_id = plugin_env.client_id;
+    let client_id = plugin_env.client_id();
     thread::spawn(move || {
         let start_time = Instant::now();
         thread::sleep(Duration::from_secs_f64(secs));
@@ -609,7 +1883,7 @@ fn host_exec_cmd(plugin_env: &PluginEnv) {
     let command = cmdline.remove(0);
 
     // Bail out if we're forbidden to run command
-    if !plugin_env.plugin._allow_exec_host_cmd {
+    if !has_permission(plugin_env, PermissionType::RunCommands) {
         warn!("This plugin isn't allow to run command in host side, skip running this command: '{cmd} {args}'.",
         	cmd = command, args = cmdline.join(" "));
         return;
@@ -622,6 +1896,250 @@ fn host_exec_cmd(plugin_env: &PluginEnv) {
         .unwrap();
 }
 
+fn host_run_command(plugin_env: &PluginEnv) {
+    let (mut cmdline, context): (Vec<String>, BTreeMap<String, String>) =
+        wasi_read_object(&plugin_env.wasi_env);
+    if cmdline.is_empty() {
+        warn!(
+            "{} - Asked to run an empty command",
+            plugin_env.plugin.location
+        );
+        return;
+    }
+    let command = cmdline.remove(0);
+    if !has_permission(plugin_env, PermissionType::RunCommands) {
+        return;
+    }
+
+    // The command runs in the background, its result is sent to the plugin as an event once it
+    // exits. A command that can't be run gets a result without an exit code and the error as
+    // its stderr
+    let senders = plugin_env.senders.clone();
+    let plugin_id = plugin_env.plugin_id;
+    let client_id = plugin_env.client_id();
+    let send_result = move |event: Event| {
+        senders
+            .send_to_plugin(PluginInstruction::Update(
+                Some(plugin_id),
+                Some(client_id),
+                event,
+            ))
+            .with_context(|| format!("failed to send command result to plugin {plugin_id}"))
+            .non_fatal();
+    };
+    let failed_result = |error: String, context: BTreeMap<String, String>| {
+        Event::RunCommandResult(None, vec![], error.into_bytes(), context)
+    };
+    let spawned = thread::Builder::new()
+        .name("run_command".to_string())
+        .spawn({
+            let send_result = send_result.clone();
+            let context = context.clone();
+            move || {
+                let event = match process::Command::new(&command).args(cmdline).output() {
+                    Ok(output) => Event::RunCommandResult(
+                        output.status.code(),
+                        output.stdout,
+                        output.stderr,
+                        context,
+                    ),
+                    Err(e) => {
+                        log::error!("Failed to run command {}: {}", command, e);
+                        failed_result(e.to_string(), context)
+                    },
+                };
+                send_result(event);
+            }
+        });
+    if let Err(e) = spawned {
+        log::error!("Failed to spawn a thread to run command: {}", e);
+        send_result(failed_result(e.to_string(), context));
+    }
+}
+
+fn host_write(plugin_env: &PluginEnv) {
+    let bytes: Vec<u8> = wasi_read_object(&plugin_env.wasi_env);
+    if !has_permission(plugin_env, PermissionType::WriteToStdin) {
+        return;
+    }
+    plugin_env
+        .senders
+        .send_to_screen(ScreenInstruction::WriteFromPlugin(
+            bytes,
+            plugin_env.plugin_id,
+            plugin_env.client_id(),
+        ))
+        .unwrap();
+}
+
+fn host_write_chars(plugin_env: &PluginEnv) {
+    let chars: String = wasi_read_object(&plugin_env.wasi_env);
+    if !has_permission(plugin_env, PermissionType::WriteToStdin) {
+        return;
+    }
+    plugin_env
+        .senders
+        .send_to_screen(ScreenInstruction::WriteFromPlugin(
+            chars.into_bytes(),
+            plugin_env.plugin_id,
+            plugin_env.client_id(),
+        ))
+        .unwrap();
+}
+
+fn host_write_to_pane_id(plugin_env: &PluginEnv) {
+    let (bytes, pane_id): (Vec<u8>, PluginPaneId) = wasi_read_object(&plugin_env.wasi_env);
+    if !has_permission(plugin_env, PermissionType::WriteToStdin) {
+        return;
+    }
+    plugin_env
+        .senders
+        .send_to_screen(ScreenInstruction::WriteToPaneId(bytes, pane_id.into()))
+        .unwrap();
+}
+
+fn host_write_chars_to_pane_id(plugin_env: &PluginEnv) {
+    let (chars, pane_id): (String, PluginPaneId) = wasi_read_object(&plugin_env.wasi_env);
+    if !has_permission(plugin_env, PermissionType::WriteToStdin) {
+        return;
+    }
+    plugin_env
+        .senders
+        .send_to_screen(ScreenInstruction::WriteToPaneId(
+            chars.into_bytes(),
+            pane_id.into(),
+        ))
+        .unwrap();
+}
+
+fn host_post_message_to(plugin_env: &PluginEnv) {
+    let (plugin_name, message, payload): (String, String, String) =
+        wasi_read_object(&plugin_env.wasi_env);
+    plugin_env
+        .senders
+        .send_to_plugin(PluginInstruction::PostMessageTo(
+            plugin_name,
+            message,
+            payload,
+        ))
+        .unwrap();
+}
+
+fn host_run_action(plugin_env: &PluginEnv) {
+    let action: Action = wasi_read_object(&plugin_env.wasi_env);
+    if is_unavailable_to_plugins(&action) {
+        warn!(
+            "{} - Plugins cannot run the {:?} action, ignoring the request",
+            plugin_env.plugin.location, action
+        );
+        return;
+    }
+    if let Some(permission) = required_permission(&action) {
+        if !has_permission(plugin_env, permission) {
+            return;
+        }
+    }
+    plugin_env
+        .senders
+        .send_to_server(ServerInstruction::ActionFromPlugin(
+            action,
+            plugin_env.client_id(),
+        ))
+        .unwrap();
+}
+
+fn host_pipe_output(plugin_env: &PluginEnv) {
+    let (cli_client_id, output): (ClientId, String) = wasi_read_object(&plugin_env.wasi_env);
+    let is_piping_to_plugin = plugin_env
+        .open_pipes
+        .lock()
+        .unwrap()
+        .contains(&cli_client_id);
+    if !is_piping_to_plugin {
+        warn!(
+            "{} - Plugin tried to output to client {} which is not piping input to it, ignoring",
+            plugin_env.plugin.location, cli_client_id
+        );
+        return;
+    }
+    plugin_env
+        .senders
+        .send_to_server(ServerInstruction::PipeOutput(cli_client_id, output))
+        .unwrap();
+}
+
+fn host_switch_session(plugin_env: &PluginEnv) {
+    let session_name: String = wasi_read_object(&plugin_env.wasi_env);
+    if !has_permission(plugin_env, PermissionType::SwitchSessions) {
+        return;
+    }
+    // the client is detached before it switches, so it must be able to switch
+    if let Err(e) = validate_session_name(&session_name) {
+        warn!(
+            "{} - Plugin cannot switch sessions: {}",
+            plugin_env.plugin.location, e
+        );
+        return;
+    }
+    plugin_env
+        .senders
+        .send_to_server(ServerInstruction::SwitchSession(
+            session_name,
+            plugin_env.client_id(),
+        ))
+        .unwrap();
+}
+
+fn host_request_pane_contents(plugin_env: &PluginEnv) {
+    let (pane_id, full, with_styles): (PluginPaneId, bool, bool) =
+        wasi_read_object(&plugin_env.wasi_env);
+    if !has_permission(plugin_env, PermissionType::ReadPaneContents) {
+        return;
+    }
+    plugin_env
+        .senders
+        .send_to_screen(ScreenInstruction::DumpPaneContentsForPlugin(
+            pane_id.into(),
+            full,
+            with_styles,
+            plugin_env.plugin_id,
+            plugin_env.client_id(),
+        ))
+        .unwrap();
+}
+
+fn host_request_permission(plugin_env: &PluginEnv) {
+    let permissions: Vec<PermissionType> = wasi_read_object(&plugin_env.wasi_env);
+    let missing: Vec<PermissionType> = {
+        let granted = plugin_env.permissions.lock().unwrap();
+        permissions
+            .into_iter()
+            .filter(|p| !granted.contains(p))
+            .collect()
+    };
+    if missing.is_empty() {
+        plugin_env
+            .senders
+            .send_to_plugin(PluginInstruction::PermissionRequestResult(
+                plugin_env.plugin_id,
+                plugin_env.client_id(),
+                missing,
+                PermissionStatus::Granted,
+            ))
+            .unwrap();
+    } else {
+        plugin_env
+            .senders
+            .send_to_screen(ScreenInstruction::RequestPluginPermissions(
+                plugin_env.plugin_id,
+                plugin_env.client_id(),
+                plugin_env.plugin.location.to_string(),
+                missing,
+            ))
+            .unwrap();
+    }
+}
+
 // Custom panic handler for plugins.
 //
 // This is called when a panic occurs in a plugin. Since most panics will likely originate in the
@@ -634,6 +2152,259 @@ fn host_report_panic(plugin_env: &PluginEnv) {
 
 // Helper Functions ---------------------------------------------------------------------------------------------------
 
+// Actions able to run commands, open shells or type into panes need the same permissions as the
+// host functions doing so directly.
+fn required_permission(action: &Action) -> Option<PermissionType> {
+    match action {
+        Action::Write(_) | Action::WriteChars(_) => Some(PermissionType::WriteToStdin),
+        Action::Run(_)
+        // new panes without a command start the default shell
+        | Action::NewPane(..)
+        | Action::NewFloatingPane(..)
+        | Action::NewTiledPane(..)
+        | Action::ToggleFloatingPanes
+        // layouts can start commands in their panes, the default one starts the default shell
+        | Action::NewTab(..)
+        | Action::EditFile(..)
+        | Action::EditScrollback
+        // loads and runs the code of another plugin
+        | Action::LaunchOrFocusPlugin(..) => Some(PermissionType::RunCommands),
+        Action::DumpScreen(..) => Some(PermissionType::ChangeFilesystem),
+        Action::SkipConfirm(action) => required_permission(action),
+        _ => None,
+    }
+}
+
+// Some actions reply to (or keep streaming to) the CLI client that sent them, which a plugin is not.
+// Plugins can't answer prompts either, as this would let them grant their own permission requests.
+fn is_unavailable_to_plugins(action: &Action) -> bool {
+    match action {
+        Action::ListTabs(_)
+        | Action::ListPanes(_)
+        | Action::ListClients(_)
+        | Action::Subscribe(..)
+        | Action::WaitForCommand(_)
+        | Action::Confirm
+        | Action::Deny => true,
+        Action::SkipConfirm(action) => is_unavailable_to_plugins(action),
+        _ => false,
+    }
+}
+
+// Events revealing what the user does outside of the plugin need a permission of their own
+fn may_receive(plugin_env: &PluginEnv, event_type: EventType) -> bool {
+    match event_type {
+        EventType::KeyPress => plugin_env
+            .permissions
+            .lock()
+            .unwrap()
+            .contains(&PermissionType::ReadKeystrokes),
+        _ => true,
+    }
+}
+
+// Checks whether the user granted `permission` to this plugin, logging the refusal if they did not.
+fn has_permission(plugin_env: &PluginEnv, permission: PermissionType) -> bool {
+    let granted = plugin_env.permissions.lock().unwrap().contains(&permission);
+    if !granted {
+        warn!(
+            "{} - Plugin was not granted the {:?} permission, ignoring the request",
+            plugin_env.plugin.location, permission
+        );
+    }
+    granted
+}
+
+fn report_input_subscriptions(
+    senders: &ThreadSenders,
+    client_id: ClientId,
+    (key_presses, mouse_motion): (bool, bool),
+) {
+    senders
+        .send_to_server(ServerInstruction::InputSubscriptions(
+            client_id,
+            key_presses,
+            mouse_motion,
+        ))
+        .with_context(|| format!("failed to report input subscriptions to client {client_id}"))
+        .non_fatal();
+}
+
+// Plugins can no longer output to the cli client once its pipe is closed
+fn close_pipe<'a>(
+    plugins: impl Iterator<Item = &'a (Instance, PluginEnv)>,
+    cli_client_id: ClientId,
+) {
+    for (_, plugin_env) in plugins {
+        plugin_env.open_pipes.lock().unwrap().remove(&cli_client_id);
+    }
+}
+
+fn is_subscribed_to(plugin_env: &PluginEnv, event_type: EventType) -> bool {
+    plugin_env
+        .subscriptions
+        .lock()
+        .map(|subscriptions| subscriptions.contains(&event_type))
+        .unwrap_or(false)
+}
+
+fn is_subscribed_to_filesystem(plugin_env: &PluginEnv) -> bool {
+    plugin_env
+        .subscriptions
+        .lock()
+        .map(|subscriptions| {
+            subscriptions.contains(&EventType::FileSystemCreate)
+                || subscriptions.contains(&EventType::FileSystemUpdate)
+                || subscriptions.contains(&EventType::FileSystemDelete)
+        })
+        .unwrap_or(false)
+}
+
+// Watches the directory Zellij was started in (mounted as /host for plugins) and broadcasts
+// changes to it to the plugins as FileSystem* events. Only the directories down to
+// `FILESYSTEM_WATCH_DEPTH` levels below it are watched, as each one uses up an inotify watch.
+fn watch_filesystem(senders: ThreadSenders) -> Result<RecommendedWatcher> {
+    let zellij_cwd = std::env::current_dir().context("failed to get current directory")?;
+    let host_root = zellij_cwd.clone();
+    let (changes_tx, changes_rx) = channel();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<FsEvent>| match res {
+        Ok(event) => {
+            let _ = changes_tx.send(event);
+        },
+        Err(e) => log::error!("Error while watching the filesystem: {}", e),
+    })
+    .context("failed to create filesystem watcher")?;
+    watcher
+        .watch(&zellij_cwd, RecursiveMode::NonRecursive)
+        .with_context(|| format!("failed to watch {zellij_cwd:?}"))?;
+    for dir in subdirectories(&zellij_cwd, FILESYSTEM_WATCH_DEPTH) {
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .with_context(|| format!("failed to watch {dir:?}"))
+            .non_fatal();
+    }
+    // stops once the watcher is dropped, along with the sender of its callback
+    thread::Builder::new()
+        .name("filesystem_watcher".to_string())
+        .spawn(move || send_filesystem_changes(changes_rx, &host_root, &senders))
+        .context("failed to spawn filesystem watcher thread")?;
+    Ok(watcher)
+}
+
+// Sends the changes to the filesystem to the plugins. Changes made shortly after one another are
+// sent together, so that eg. a build writing many files doesn't flood the plugins with events.
+fn send_filesystem_changes(changes: Receiver<FsEvent>, host_root: &Path, senders: &ThreadSenders) {
+    while let Ok(first_change) = changes.recv() {
+        let deadline = Instant::now() + FILESYSTEM_DEBOUNCE;
+        let (mut created, mut updated, mut deleted) =
+            (BTreeSet::new(), BTreeSet::new(), BTreeSet::new());
+        let mut next_change = Some(first_change);
+        while let Some(change) = next_change {
+            // paths are reported relative to the host mount, as this is what plugins see
+            let paths = change
+                .paths
+                .iter()
+                .filter_map(|p| p.strip_prefix(host_root).ok())
+                .map(|p| PathBuf::from("/host").join(p));
+            match change.kind {
+                EventKind::Create(_) => created.extend(paths),
+                EventKind::Modify(_) => updated.extend(paths),
+                EventKind::Remove(_) => deleted.extend(paths),
+                _ => {},
+            }
+            next_change = changes
+                .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                .ok();
+        }
+        let events = [
+            (
+                created,
+                Event::FileSystemCreate as fn(Vec<PathBuf>) -> Event,
+            ),
+            (updated, Event::FileSystemUpdate),
+            (deleted, Event::FileSystemDelete),
+        ];
+        for (paths, event) in events {
+            // plugins have nothing to update without paths
+            if !paths.is_empty() {
+                let event = event(paths.into_iter().collect());
+                let _ = senders.send_to_plugin(PluginInstruction::Update(None, None, event));
+            }
+        }
+    }
+}
+
+// The directories below `root`, down to `depth` levels. Symlinks aren't followed.
+fn subdirectories(root: &Path, depth: usize) -> Vec<PathBuf> {
+    let mut subdirectories = vec![];
+    let mut current_level = vec![root.to_path_buf()];
+    for _ in 0..depth {
+        current_level = current_level
+            .iter()
+            .filter_map(|dir| fs::read_dir(dir).ok())
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().map(|t| t.is_dir()).unwrap_or(false))
+            .map(|entry| entry.path())
+            .collect();
+        subdirectories.extend(current_level.iter().cloned());
+    }
+    subdirectories
+}
+
+// Reloads the plugin at `location` whenever its wasm file is written. The directory of the file is
+// watched rather than the file itself, as build tools often replace it instead of writing to it
+fn watch_plugin_file(
+    senders: ThreadSenders,
+    wasm_path: &Path,
+    location: RunPluginLocation,
+) -> Result<RecommendedWatcher> {
+    let err_context = || format!("failed to watch plugin file {wasm_path:?}");
+
+    let watched_dir = wasm_path
+        .parent()
+        .context("plugin file has no parent directory")
+        .with_context(err_context)?;
+    let watched_path = wasm_path.to_path_buf();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<FsEvent>| {
+        let event = match res {
+            Ok(event) => event,
+            Err(e) => {
+                log::error!("Error while watching plugin file: {}", e);
+                return;
+            },
+        };
+        let is_written = matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_));
+        if is_written && event.paths.iter().any(|p| p == &watched_path) {
+            let _ = senders.send_to_plugin(PluginInstruction::Reload(location.clone(), false));
+        }
+    })
+    .with_context(err_context)?;
+    watcher
+        .watch(watched_dir, RecursiveMode::NonRecursive)
+        .with_context(err_context)?;
+    Ok(watcher)
+}
+
+// Reads the (command, cwd, name) triplet sent by the open_terminal* shim functions and turns it
+// into the terminal action the pty thread expects. Without a command, the default shell is used.
+fn read_terminal_to_open(plugin_env: &PluginEnv) -> (Option<TerminalAction>, Option<String>) {
+    let (run_command, cwd, name): (Option<RunCommand>, Option<PathBuf>, Option<String>) =
+        wasi_read_object(&plugin_env.wasi_env);
+    let mut terminal_action = run_command
+        .map(TerminalAction::RunCommand)
+        .or_else(|| plugin_env.default_shell.clone());
+    if let Some(cwd) = cwd {
+        match terminal_action.get_or_insert_with(|| get_default_terminal(None)) {
+            TerminalAction::RunCommand(run_command) if run_command.cwd.is_none() => {
+                run_command.cwd = Some(cwd);
+            },
+            _ => {},
+        }
+    }
+    (terminal_action, name)
+}
+
 pub fn wasi_read_string(wasi_env: &WasiEnv) -> String {
     let mut state = wasi_env.state();
     let wasi_file = state.fs.stdout_mut().unwrap().as_mut().unwrap();
@@ -656,3 +2427,7 @@ pub fn wasi_read_object<T: DeserializeOwned>(wasi_env: &WasiEnv) -> T {
     let json = wasi_read_string(wasi_env);
     serde_json::from_str(&json).unwrap()
 }
+
+#[cfg(test)]
+#[path = "./unit/wasm_vm_tests.rs"]
+mod wasm_vm_tests;
@@ -1,3 +1,5 @@
+#[cfg(all(feature = "mock", not(target_arch = "wasm32")))]
+pub mod mock;
 pub mod prelude;
 pub mod shim;
 
@@ -65,3 +67,27 @@ macro_rules! register_plugin {
         }
     };
 }
+
+// the mock print macros are exported under `zellij_tile::mock`, so that they don't clash with
+// the std ones when plugins import the whole prelude
+
+#[doc(hidden)]
+#[cfg(all(feature = "mock", not(target_arch = "wasm32")))]
+#[macro_export]
+macro_rules! __mock_print {
+    ($($arg:tt)*) => {
+        $crate::mock::write_output(format_args!($($arg)*))
+    };
+}
+
+#[doc(hidden)]
+#[cfg(all(feature = "mock", not(target_arch = "wasm32")))]
+#[macro_export]
+macro_rules! __mock_println {
+    () => {
+        $crate::mock::write_output(format_args!("\n"))
+    };
+    ($($arg:tt)*) => {
+        $crate::mock::write_output(format_args!("{}\n", format_args!($($arg)*)))
+    };
+}
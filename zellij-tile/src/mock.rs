@@ -0,0 +1,365 @@
+//! A native stand-in for the Zellij host, to unit-test plugins with `cargo test`
+//!
+//! Enabled by the `mock` feature (which has no effect when building to wasm), it replaces the
+//! host functions of the [`shim`](crate::shim) with ones that record the calls made by the plugin
+//! instead of sending them to Zellij. Tests drive the plugin through a [`MockHost`]:
+//!
+//! ```ignore
+//! let mut host = MockHost::<State>::new();
+//! host.load();
+//! host.send_event(Event::Mouse(Mouse::ScrollUp(1)));
+//! assert_eq!(host.take_calls(), vec![HostCall::SwitchTabTo(2)]);
+//! assert!(host.render(1, 80).contains("Tab #2"));
+//! ```
+//!
+//! Plugins print what they render to stdout, which the std `print!` and `println!` macros write
+//! to the terminal rather than to the host. The [`print!`](print) and [`println!`](println)
+//! macros of this module write to the output returned by [`MockHost::render`] instead, plugins
+//! asserting on their output import them in their test builds so that they shadow the std ones
+//! (eg. `#[cfg(test)] use zellij_tile::mock::println;`).
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashSet, VecDeque},
+    fmt::{self, Write},
+    path::PathBuf,
+    str::FromStr,
+};
+use zellij_utils::consts::VERSION;
+use zellij_utils::data::*;
+use zellij_utils::input::actions::Action;
+use zellij_utils::input::command::RunCommand;
+
+use crate::ZellijPlugin;
+
+/// Prints to the output captured by [`MockHost::render`]
+pub use crate::__mock_print as print;
+/// Prints a line to the output captured by [`MockHost::render`]
+pub use crate::__mock_println as println;
+
+thread_local! {
+    // every test runs in its own thread, so they each get their own host
+    static HOST: RefCell<HostState> = RefCell::new(HostState::default());
+}
+
+/// A call the plugin made to the host, along with its arguments
+#[derive(Debug, Clone, PartialEq)]
+pub enum HostCall {
+    Subscribe(Vec<EventType>),
+    Unsubscribe(Vec<EventType>),
+    SetSelectable(bool),
+    SetPaneTitle(String),
+    SetBorderless(bool),
+    SetPreferredSize(usize, usize), // rows, cols
+    OpenFile(PathBuf),
+    OpenTerminal(Option<RunCommand>, Option<PathBuf>, Option<String>), // command, cwd, name
+    OpenTerminalFloating(Option<RunCommand>, Option<PathBuf>, Option<String>), // command, cwd, name
+    OpenTerminalInNewTab(Option<RunCommand>, Option<PathBuf>, Option<String>), // command, cwd, tab name
+    SwitchTabTo(u32),
+    SetTimeout(f64),
+    ExecCmd(Vec<String>),
+    RunCommand(Vec<String>, BTreeMap<String, String>), // command, context
+    Write(Vec<u8>),
+    WriteChars(String),
+    WriteToPaneId(Vec<u8>, PaneId),
+    WriteCharsToPaneId(String, PaneId),
+    PostMessageTo(String, String, String), // plugin name, message, payload
+    RunAction(Action),
+    PipeOutput(ClientId, String), // cli client id, output
+    SwitchSession(String),
+    RequestPaneContents(PaneId, bool, bool), // pane id, full, with styles
+    RequestPermission(Vec<PermissionType>),
+    ReportPanic,
+}
+
+struct HostState {
+    from_plugin: VecDeque<String>,
+    to_plugin: VecDeque<String>,
+    calls: Vec<HostCall>,
+    subscriptions: HashSet<EventType>,
+    selectable: bool,
+    plugin_ids: PluginIds,
+    configuration: BTreeMap<String, String>,
+    output: Option<String>, // captured while rendering
+}
+
+impl Default for HostState {
+    fn default() -> Self {
+        HostState {
+            from_plugin: VecDeque::new(),
+            to_plugin: VecDeque::new(),
+            calls: vec![],
+            subscriptions: HashSet::new(),
+            selectable: true,
+            plugin_ids: PluginIds {
+                plugin_id: 0,
+                zellij_pid: 0,
+            },
+            configuration: BTreeMap::new(),
+            output: None,
+        }
+    }
+}
+
+/// Runs a plugin natively, playing the part of Zellij
+pub struct MockHost<P: ZellijPlugin> {
+    plugin: P,
+}
+
+impl<P: ZellijPlugin + Default> MockHost<P> {
+    /// Creates a host running a fresh instance of the plugin, forgetting everything recorded
+    /// earlier on this thread
+    pub fn new() -> Self {
+        HOST.with(|host| *host.borrow_mut() = HostState::default());
+        MockHost {
+            plugin: P::default(),
+        }
+    }
+}
+
+impl<P: ZellijPlugin + Default> Default for MockHost<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: ZellijPlugin> MockHost<P> {
+    /// Sets what `get_plugin_configuration` returns, as if configured in the layout
+    pub fn with_configuration(self, configuration: BTreeMap<String, String>) -> Self {
+        HOST.with(|host| host.borrow_mut().configuration = configuration);
+        self
+    }
+
+    /// Sets what `get_plugin_ids` returns
+    pub fn with_plugin_ids(self, plugin_ids: PluginIds) -> Self {
+        HOST.with(|host| host.borrow_mut().plugin_ids = plugin_ids);
+        self
+    }
+
+    pub fn load(&mut self) {
+        self.plugin.load();
+    }
+
+    /// Sends `event` to the plugin if it subscribed to it, like Zellij would. Returns whether the
+    /// event was sent
+    pub fn send_event(&mut self, event: Event) -> bool {
+        let is_subscribed = EventType::from_str(&event.to_string())
+            .map(|event_type| HOST.with(|host| host.borrow().subscriptions.contains(&event_type)))
+            .unwrap_or(false);
+        if is_subscribed {
+            self.plugin.update(event);
+        }
+        is_subscribed
+    }
+
+    /// Renders the plugin at the given size and returns everything it printed
+    pub fn render(&mut self, rows: usize, cols: usize) -> String {
+        HOST.with(|host| host.borrow_mut().output = Some(String::new()));
+        self.plugin.render(rows, cols);
+        HOST.with(|host| host.borrow_mut().output.take().unwrap_or_default())
+    }
+
+    /// The host calls made by the plugin so far
+    pub fn calls(&self) -> Vec<HostCall> {
+        HOST.with(|host| host.borrow().calls.clone())
+    }
+
+    /// The host calls made by the plugin since the last time they were taken
+    pub fn take_calls(&mut self) -> Vec<HostCall> {
+        HOST.with(|host| host.borrow_mut().calls.drain(..).collect())
+    }
+
+    pub fn subscriptions(&self) -> HashSet<EventType> {
+        HOST.with(|host| host.borrow().subscriptions.clone())
+    }
+
+    pub fn is_selectable(&self) -> bool {
+        HOST.with(|host| host.borrow().selectable)
+    }
+
+    pub fn plugin(&self) -> &P {
+        &self.plugin
+    }
+
+    pub fn plugin_mut(&mut self) -> &mut P {
+        &mut self.plugin
+    }
+}
+
+#[doc(hidden)]
+pub fn write_output(args: fmt::Arguments) {
+    let printed = HOST.with(|host| match host.borrow_mut().output.as_mut() {
+        Some(output) => output.write_fmt(args).is_ok(),
+        None => false,
+    });
+    if !printed {
+        std::print!("{}", args);
+    }
+}
+
+#[doc(hidden)]
+pub fn object_to_host(object: &impl Serialize) {
+    let json = serde_json::to_string(object).unwrap();
+    HOST.with(|host| host.borrow_mut().from_plugin.push_back(json));
+}
+
+#[doc(hidden)]
+pub fn object_from_host() -> Option<String> {
+    HOST.with(|host| host.borrow_mut().to_plugin.pop_front())
+}
+
+// Host Functions, with the same signatures as the ones Zellij exports to plugins
+
+pub(crate) mod host {
+    use super::*;
+
+    fn read_object<T: DeserializeOwned>() -> T {
+        HOST.with(|host| host.borrow_mut().from_plugin.pop_front())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .expect("the plugin did not send the object this host function expects")
+    }
+
+    fn write_object(object: &impl Serialize) {
+        let json = serde_json::to_string(object).unwrap();
+        HOST.with(|host| host.borrow_mut().to_plugin.push_back(json));
+    }
+
+    fn record(call: HostCall) {
+        HOST.with(|host| host.borrow_mut().calls.push(call));
+    }
+
+    type TerminalToOpen = (Option<RunCommand>, Option<PathBuf>, Option<String>);
+
+    pub unsafe fn host_subscribe() {
+        let event_types: Vec<EventType> = read_object();
+        HOST.with(|host| host.borrow_mut().subscriptions.extend(&event_types));
+        record(HostCall::Subscribe(event_types));
+    }
+
+    pub unsafe fn host_unsubscribe() {
+        let event_types: Vec<EventType> = read_object();
+        HOST.with(|host| {
+            let subscriptions = &mut host.borrow_mut().subscriptions;
+            for event_type in &event_types {
+                subscriptions.remove(event_type);
+            }
+        });
+        record(HostCall::Unsubscribe(event_types));
+    }
+
+    pub unsafe fn host_set_selectable(selectable: i32) {
+        let selectable = selectable != 0;
+        HOST.with(|host| host.borrow_mut().selectable = selectable);
+        record(HostCall::SetSelectable(selectable));
+    }
+
+    pub unsafe fn host_set_pane_title() {
+        record(HostCall::SetPaneTitle(read_object()));
+    }
+
+    pub unsafe fn host_set_borderless(borderless: i32) {
+        record(HostCall::SetBorderless(borderless != 0));
+    }
+
+    pub unsafe fn host_set_preferred_size() {
+        let (rows, cols) = read_object();
+        record(HostCall::SetPreferredSize(rows, cols));
+    }
+
+    pub unsafe fn host_get_plugin_ids() {
+        write_object(&HOST.with(|host| host.borrow().plugin_ids));
+    }
+
+    pub unsafe fn host_get_zellij_version() {
+        write_object(&VERSION);
+    }
+
+    pub unsafe fn host_get_plugin_configuration() {
+        write_object(&HOST.with(|host| host.borrow().configuration.clone()));
+    }
+
+    pub unsafe fn host_open_file() {
+        record(HostCall::OpenFile(read_object()));
+    }
+
+    pub unsafe fn host_open_terminal() {
+        let (command, cwd, name): TerminalToOpen = read_object();
+        record(HostCall::OpenTerminal(command, cwd, name));
+    }
+
+    pub unsafe fn host_open_terminal_floating() {
+        let (command, cwd, name): TerminalToOpen = read_object();
+        record(HostCall::OpenTerminalFloating(command, cwd, name));
+    }
+
+    pub unsafe fn host_open_terminal_in_new_tab() {
+        let (command, cwd, name): TerminalToOpen = read_object();
+        record(HostCall::OpenTerminalInNewTab(command, cwd, name));
+    }
+
+    pub unsafe fn host_switch_tab_to(tab_idx: u32) {
+        record(HostCall::SwitchTabTo(tab_idx));
+    }
+
+    pub unsafe fn host_set_timeout(secs: f64) {
+        record(HostCall::SetTimeout(secs));
+    }
+
+    pub unsafe fn host_exec_cmd() {
+        record(HostCall::ExecCmd(read_object()));
+    }
+
+    pub unsafe fn host_run_command() {
+        let (command, context) = read_object();
+        record(HostCall::RunCommand(command, context));
+    }
+
+    pub unsafe fn host_write() {
+        record(HostCall::Write(read_object()));
+    }
+
+    pub unsafe fn host_write_chars() {
+        record(HostCall::WriteChars(read_object()));
+    }
+
+    pub unsafe fn host_write_to_pane_id() {
+        let (bytes, pane_id) = read_object();
+        record(HostCall::WriteToPaneId(bytes, pane_id));
+    }
+
+    pub unsafe fn host_write_chars_to_pane_id() {
+        let (chars, pane_id) = read_object();
+        record(HostCall::WriteCharsToPaneId(chars, pane_id));
+    }
+
+    pub unsafe fn host_post_message_to() {
+        let (plugin_name, message, payload) = read_object();
+        record(HostCall::PostMessageTo(plugin_name, message, payload));
+    }
+
+    pub unsafe fn host_run_action() {
+        record(HostCall::RunAction(read_object()));
+    }
+
+    pub unsafe fn host_pipe_output() {
+        let (cli_client_id, output) = read_object();
+        record(HostCall::PipeOutput(cli_client_id, output));
+    }
+
+    pub unsafe fn host_switch_session() {
+        record(HostCall::SwitchSession(read_object()));
+    }
+
+    pub unsafe fn host_request_pane_contents() {
+        let (pane_id, full, with_styles) = read_object();
+        record(HostCall::RequestPaneContents(pane_id, full, with_styles));
+    }
+
+    pub unsafe fn host_request_permission() {
+        record(HostCall::RequestPermission(read_object()));
+    }
+
+    pub unsafe fn host_report_panic() {
+        record(HostCall::ReportPanic);
+    }
+}
@@ -4,3 +4,4 @@ pub use zellij_utils::consts::VERSION;
 pub use zellij_utils::data::*;
 pub use zellij_utils::errors::prelude::*;
 pub use zellij_utils::input::actions;
+pub use zellij_utils::input::command::RunCommand;
@@ -1,7 +1,12 @@
 use serde::{de::DeserializeOwned, Serialize};
-use std::{io, path::Path};
+use std::{collections::BTreeMap, path::Path};
 use zellij_utils::data::*;
 use zellij_utils::errors::prelude::*;
+use zellij_utils::input::actions::Action;
+use zellij_utils::input::command::RunCommand;
+
+#[cfg(all(feature = "mock", not(target_arch = "wasm32")))]
+use crate::mock::host::*;
 
 // Subscription Handling
 
@@ -21,6 +26,24 @@ pub fn set_selectable(selectable: bool) {
     unsafe { host_set_selectable(selectable as i32) };
 }
 
+/// Set the title shown in the frame of the plugin's pane, unless the user renamed it
+pub fn set_pane_title(title: &str) {
+    object_to_stdout(&title);
+    unsafe { host_set_pane_title() };
+}
+
+/// Draw the plugin's pane without a frame, or with one again
+pub fn set_borderless(borderless: bool) {
+    unsafe { host_set_borderless(borderless as i32) };
+}
+
+/// Resize the plugin's pane so that its content has the given size. Only floating panes can be
+/// resized, tiled panes keep the size given to them by the layout
+pub fn set_preferred_size(rows: usize, cols: usize) {
+    object_to_stdout(&(rows, cols));
+    unsafe { host_set_preferred_size() };
+}
+
 // Query Functions
 pub fn get_plugin_ids() -> PluginIds {
     unsafe { host_get_plugin_ids() };
@@ -32,13 +55,43 @@ pub fn get_zellij_version() -> String {
     object_from_stdin().unwrap()
 }
 
+/// Get the key/value pairs the user configured for this plugin instance in the layout or in the
+/// `plugins` block of the config
+pub fn get_plugin_configuration() -> BTreeMap<String, String> {
+    unsafe { host_get_plugin_configuration() };
+    object_from_stdin().unwrap()
+}
+
 // Host Functions
 
+/// Open the file in an editor pane in the plugin's tab. Requires the `RunCommands` permission
 pub fn open_file(path: &Path) {
     object_to_stdout(&path);
     unsafe { host_open_file() };
 }
 
+/// Open a new tiled terminal pane in the plugin's tab, optionally running a command
+pub fn open_terminal(command: Option<RunCommand>, cwd: Option<&Path>, name: Option<&str>) {
+    object_to_stdout(&(command, cwd, name));
+    unsafe { host_open_terminal() };
+}
+
+/// Open a new floating terminal pane in the plugin's tab, optionally running a command
+pub fn open_terminal_floating(command: Option<RunCommand>, cwd: Option<&Path>, name: Option<&str>) {
+    object_to_stdout(&(command, cwd, name));
+    unsafe { host_open_terminal_floating() };
+}
+
+/// Open a new tab with a terminal pane, optionally running a command. `name` is the tab name
+pub fn open_terminal_in_new_tab(
+    command: Option<RunCommand>,
+    cwd: Option<&Path>,
+    name: Option<&str>,
+) {
+    object_to_stdout(&(command, cwd, name));
+    unsafe { host_open_terminal_in_new_tab() };
+}
+
 pub fn switch_tab_to(tab_idx: u32) {
     unsafe { host_switch_tab_to(tab_idx) };
 }
@@ -51,6 +104,82 @@ pub fn exec_cmd(cmd: &[&str]) {
     unsafe { host_exec_cmd() };
 }
 
+/// Run a command in the background, its exit code, STDOUT and STDERR are delivered to the plugin
+/// as an `Event::RunCommandResult` along with the given context
+pub fn run_command(cmd: &[&str], context: BTreeMap<String, String>) {
+    object_to_stdout(&(cmd, context));
+    unsafe { host_run_command() };
+}
+
+/// Write raw bytes to the focused pane, as if they were typed by the user
+pub fn write(bytes: Vec<u8>) {
+    object_to_stdout(&bytes);
+    unsafe { host_write() };
+}
+
+/// Write characters to the focused pane, as if they were typed by the user
+pub fn write_chars(chars: &str) {
+    object_to_stdout(&chars);
+    unsafe { host_write_chars() };
+}
+
+/// Write raw bytes to the pane with the given id
+pub fn write_to_pane_id(bytes: Vec<u8>, pane_id: PaneId) {
+    object_to_stdout(&(bytes, pane_id));
+    unsafe { host_write_to_pane_id() };
+}
+
+/// Write characters to the pane with the given id
+pub fn write_chars_to_pane_id(chars: &str, pane_id: PaneId) {
+    object_to_stdout(&(chars, pane_id));
+    unsafe { host_write_chars_to_pane_id() };
+}
+
+/// Send a message to all instances of the plugin with the given name (eg. its location),
+/// which will receive it as an `Event::CustomMessage`
+pub fn post_message_to(plugin_name: &str, message: &str, payload: &str) {
+    object_to_stdout(&(plugin_name, message, payload));
+    unsafe { host_post_message_to() };
+}
+
+/// Run an action for the plugin's client, as if it was triggered by one of its keybindings.
+/// Actions running commands, opening panes or writing to panes require the same permissions as
+/// the functions doing so directly, and prompts can only be answered by the user
+pub fn run_action(action: Action) {
+    object_to_stdout(&action);
+    unsafe { host_run_action() };
+}
+
+/// Write `output` to the STDOUT of the `zellij pipe` client with the given id, as received in an
+/// `Event::PipeMessage`. The client exits once the plugin received the end of its input
+pub fn pipe_output(cli_client_id: ClientId, output: &str) {
+    object_to_stdout(&(cli_client_id, output));
+    unsafe { host_pipe_output() };
+}
+
+/// Detach the plugin's client from this session and attach it to the session with the given name,
+/// starting a new session by that name if none is running. The running sessions are listed in
+/// `Event::SessionUpdate`. Requires the `SwitchSessions` permission
+pub fn switch_session(name: &str) {
+    object_to_stdout(&name);
+    unsafe { host_switch_session() };
+}
+
+/// Request the contents of the pane with the given id, either only its viewport or its full
+/// scrollback, as plain text or with its styling as ANSI escape codes. The contents are delivered
+/// to the plugin as an `Event::PaneContents`. Requires the `ReadPaneContents` permission
+pub fn request_pane_contents(pane_id: PaneId, full: bool, with_styles: bool) {
+    object_to_stdout(&(pane_id, full, with_styles));
+    unsafe { host_request_pane_contents() };
+}
+
+/// Ask the user to grant the plugin the given permissions. The answer is delivered to the plugin
+/// as an `Event::PermissionRequestResult`, permissions granted once are remembered across sessions
+pub fn request_permission(permissions: &[PermissionType]) {
+    object_to_stdout(&permissions);
+    unsafe { host_request_permission() };
+}
+
 pub fn report_panic(info: &std::panic::PanicInfo) {
     println!("");
     println!("A panic occured in a plugin");
@@ -61,29 +190,65 @@ pub fn report_panic(info: &std::panic::PanicInfo) {
 // Internal Functions
 
 #[doc(hidden)]
+#[cfg(any(not(feature = "mock"), target_arch = "wasm32"))]
 pub fn object_from_stdin<T: DeserializeOwned>() -> Result<T> {
     let err_context = || "failed to deserialize object from stdin".to_string();
 
     let mut json = String::new();
-    io::stdin().read_line(&mut json).with_context(err_context)?;
+    std::io::stdin()
+        .read_line(&mut json)
+        .with_context(err_context)?;
     serde_json::from_str(&json).with_context(err_context)
 }
 
 #[doc(hidden)]
+#[cfg(any(not(feature = "mock"), target_arch = "wasm32"))]
 pub fn object_to_stdout(object: &impl Serialize) {
     println!("{}", serde_json::to_string(object).unwrap());
 }
 
+#[doc(hidden)]
+#[cfg(all(feature = "mock", not(target_arch = "wasm32")))]
+pub fn object_from_stdin<T: DeserializeOwned>() -> Result<T> {
+    let json = crate::mock::object_from_host().context("the mock host sent no object")?;
+    serde_json::from_str(&json).context("failed to deserialize object from the mock host")
+}
+
+#[doc(hidden)]
+#[cfg(all(feature = "mock", not(target_arch = "wasm32")))]
+pub fn object_to_stdout(object: &impl Serialize) {
+    crate::mock::object_to_host(object);
+}
+
+#[cfg(any(not(feature = "mock"), target_arch = "wasm32"))]
 #[link(wasm_import_module = "zellij")]
 extern "C" {
     fn host_subscribe();
     fn host_unsubscribe();
     fn host_set_selectable(selectable: i32);
+    fn host_set_pane_title();
+    fn host_set_borderless(borderless: i32);
+    fn host_set_preferred_size();
     fn host_get_plugin_ids();
     fn host_get_zellij_version();
+    fn host_get_plugin_configuration();
     fn host_open_file();
+    fn host_open_terminal();
+    fn host_open_terminal_floating();
+    fn host_open_terminal_in_new_tab();
     fn host_switch_tab_to(tab_idx: u32);
     fn host_set_timeout(secs: f64);
     fn host_exec_cmd();
+    fn host_run_command();
+    fn host_write();
+    fn host_write_chars();
+    fn host_write_to_pane_id();
+    fn host_write_chars_to_pane_id();
+    fn host_post_message_to();
+    fn host_run_action();
+    fn host_pipe_output();
+    fn host_switch_session();
+    fn host_request_pane_contents();
+    fn host_request_permission();
     fn host_report_panic();
 }
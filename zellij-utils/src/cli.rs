@@ -1,8 +1,8 @@
-use crate::data::InputMode;
+use crate::data::{InputMode, PaneId, SubscriptionKind};
 use crate::setup::Setup;
 use crate::{
     consts::{ZELLIJ_CONFIG_DIR_ENV, ZELLIJ_CONFIG_FILE_ENV},
-    input::actions::{Direction, ResizeDirection},
+    input::actions::{Direction, ResizeDirection, TabSelector},
     input::options::CliOptions,
 };
 use clap::{Parser, Subcommand};
@@ -122,8 +122,25 @@ pub enum Sessions {
     },
     /// Send actions to a specific session
     #[clap(visible_alias = "ac")]
-    #[clap(subcommand)]
-    Action(CliAction),
+    Action {
+        /// Run the action on this pane instead of the focused one, eg. 3, terminal_3 or plugin_1.
+        /// Terminal panes find their own id in $ZELLIJ_PANE_ID
+        #[clap(long, value_parser, global(true))]
+        pane_id: Option<PaneId>,
+
+        /// Run the action on this tab instead of the focused one, either its position as shown
+        /// by list-tabs, its index as index_<n> (terminal panes find the index of their tab in
+        /// $ZELLIJ_TAB_INDEX) or its name. Use --tab-name for tabs named like a position or index
+        #[clap(long, value_parser, global(true))]
+        tab: Option<TabSelector>,
+
+        /// Run the action on the tab with this name instead of the focused one
+        #[clap(long, value_parser, global(true), conflicts_with("tab"))]
+        tab_name: Option<String>,
+
+        #[clap(subcommand)]
+        action: CliAction,
+    },
     /// Run a command in a new pane
     #[clap(visible_alias = "r")]
     Run {
@@ -150,6 +167,20 @@ pub enum Sessions {
         /// Close the pane immediately when its command exits
         #[clap(short, long, value_parser, default_value("false"), takes_value(false))]
         close_on_exit: bool,
+
+        /// Block until the command exits, then exit with its exit code
+        #[clap(short, long, value_parser, default_value("false"), takes_value(false))]
+        wait: bool,
+
+        /// Also print the output of the command while waiting for it to exit
+        #[clap(
+            long,
+            value_parser,
+            default_value("false"),
+            takes_value(false),
+            requires("wait")
+        )]
+        print_output: bool,
     },
     /// Edit file with default $EDITOR / $VISUAL
     #[clap(visible_alias = "e")]
@@ -168,6 +199,32 @@ pub enum Sessions {
         #[clap(short, long, value_parser, default_value("false"), takes_value(false))]
         floating: bool,
     },
+    /// Stream stdin to a plugin line by line, printing whatever the plugin replies to stdout
+    #[clap(visible_alias = "p")]
+    Pipe {
+        /// Location of the plugin, eg. file:/path/to/plugin.wasm or zellij:strider
+        #[clap(short, long, value_parser)]
+        plugin: String,
+
+        /// Name of the message, for plugins that listen to several pipes
+        #[clap(short, long, value_parser)]
+        name: Option<String>,
+    },
+    /// Print the events of the session as newline-delimited JSON until interrupted
+    ///
+    /// Events describing what a single client sees, such as its focused tab or input mode, carry
+    /// the id of the client as "client_id"
+    #[clap(visible_alias = "sub")]
+    Subscribe {
+        /// Events to print: tab, pane, mode, session or pane-output. Defaults to all but
+        /// pane-output, or to pane-output alone when --output-pane-id is given
+        #[clap(short, long, value_parser, value_delimiter(','))]
+        events: Vec<SubscriptionKind>,
+
+        /// Terminal panes to print the output of, eg. 3 or terminal_3
+        #[clap(short, long, value_parser, value_delimiter(','))]
+        output_pane_id: Vec<PaneId>,
+    },
     ConvertConfig {
         old_config_file: PathBuf,
     },
@@ -261,6 +318,27 @@ pub enum CliAction {
             requires("command")
         )]
         close_on_exit: bool,
+
+        /// Block until the command exits, then exit with its exit code
+        #[clap(
+            short,
+            long,
+            value_parser,
+            default_value("false"),
+            takes_value(false),
+            requires("command")
+        )]
+        wait: bool,
+
+        /// Also print the output of the command while waiting for it to exit
+        #[clap(
+            long,
+            value_parser,
+            default_value("false"),
+            takes_value(false),
+            requires("wait")
+        )]
+        print_output: bool,
     },
     /// Open the specified file in a new zellij pane with your default EDITOR
     Edit {
@@ -316,4 +394,68 @@ pub enum CliAction {
         #[clap(short, long, value_parser, requires("layout"))]
         cwd: Option<PathBuf>,
     },
+    /// Reload a running plugin in place, picking up changes to its wasm file
+    ReloadPlugin {
+        /// Location of the plugin, eg. file:/path/to/plugin.wasm or zellij:strider
+        url: String,
+
+        /// Keep watching the wasm file of the plugin and reload it whenever it changes
+        #[clap(short, long, value_parser, default_value("false"), takes_value(false))]
+        watch: bool,
+    },
+    /// Focus the pane of a plugin, or open the plugin in a new pane if it isn't running
+    LaunchOrFocusPlugin {
+        /// Location of the plugin, eg. file:/path/to/plugin.wasm or zellij:strider
+        url: String,
+
+        /// Open the plugin in a floating pane
+        #[clap(short, long, value_parser, default_value("false"), takes_value(false))]
+        floating: bool,
+    },
+    /// Stream stdin to a plugin line by line, printing whatever the plugin replies to stdout
+    Pipe {
+        /// Location of the plugin, eg. file:/path/to/plugin.wasm or zellij:strider
+        #[clap(short, long, value_parser)]
+        plugin: String,
+
+        /// Name of the message, for plugins that listen to several pipes
+        #[clap(short, long, value_parser)]
+        name: Option<String>,
+    },
+    /// Print the events of the session as newline-delimited JSON until interrupted
+    ///
+    /// Events describing what a single client sees, such as its focused tab or input mode, carry
+    /// the id of the client as "client_id"
+    Subscribe {
+        /// Events to print: tab, pane, mode, session or pane-output. Defaults to all but
+        /// pane-output, or to pane-output alone when --output-pane-id is given
+        #[clap(short, long, value_parser, value_delimiter(','))]
+        events: Vec<SubscriptionKind>,
+
+        /// Terminal panes to print the output of, eg. 3 or terminal_3
+        #[clap(short, long, value_parser, value_delimiter(','))]
+        output_pane_id: Vec<PaneId>,
+    },
+    /// List the tabs of the session
+    ListTabs {
+        /// Print the tabs as JSON
+        #[clap(short, long, value_parser, default_value("false"), takes_value(false))]
+        json: bool,
+    },
+    /// List the panes of every tab, along with their geometry, command and working directory
+    ListPanes {
+        /// Print the panes as JSON
+        #[clap(short, long, value_parser, default_value("false"), takes_value(false))]
+        json: bool,
+    },
+    /// List the clients connected to the session and what they have focused
+    ListClients {
+        /// Print the clients as JSON
+        #[clap(short, long, value_parser, default_value("false"), takes_value(false))]
+        json: bool,
+    },
 }
+
+#[cfg(test)]
+#[path = "./unit/cli_test.rs"]
+mod cli_test;
@@ -2,7 +2,9 @@ use crate::input::actions::Action;
 use crate::input::config::ConversionError;
 use clap::ArgEnum;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
+use std::path::PathBuf;
 use std::str::FromStr;
 use strum_macros::{EnumDiscriminants, EnumIter, EnumString, ToString};
 
@@ -178,6 +180,15 @@ impl fmt::Display for Key {
     }
 }
 
+/// Modifier keys held down while a key was pressed
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct KeyModifiers {
+    pub shift: bool,
+    pub alt: bool,
+    pub ctrl: bool,
+    pub super_key: bool,
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, PartialOrd, Ord)]
 #[serde(untagged)]
 pub enum CharOrArrow {
@@ -241,6 +252,125 @@ pub enum Event {
     SystemClipboardFailure,
     InputReceived,
     Visible(bool),
+    PaneUpdate(PaneManifest),
+    CustomMessage(String, String), // message, payload
+    PermissionRequestResult(PermissionStatus),
+    RunCommandResult(Option<i32>, Vec<u8>, Vec<u8>, BTreeMap<String, String>), // exit_code, STDOUT, STDERR, context
+    /// Paths (under `/host`) that were created, changed or removed in the directory Zellij was
+    /// started in or the directories right below it. Changes made shortly after one another are
+    /// sent together.
+    FileSystemCreate(Vec<PathBuf>),
+    FileSystemUpdate(Vec<PathBuf>),
+    FileSystemDelete(Vec<PathBuf>),
+    PaneContents(PaneId, String),
+    /// A line piped to the plugin with `zellij pipe`, or `None` once the input ended. Replies can
+    /// be sent to the pipe with `pipe_output` and the id of its CLI client
+    PipeMessage(Option<String>, Option<String>, ClientId), // name, payload, cli client id
+    /// The pane of the plugin was moved or resized, or its frame was toggled
+    Resize(PaneGeometry),
+    /// The sessions running on this machine, sent whenever one of them changes
+    SessionUpdate(Vec<SessionInfo>),
+    /// Every key pressed by the client of the plugin, whether or not the plugin is focused and
+    /// whether or not the key is bound in the current mode (including `Locked`). Requires the
+    /// `ReadKeystrokes` permission
+    KeyPress(Key, KeyModifiers),
+    /// The mouse moved over the pane of the plugin without any button held. Clients only report
+    /// mouse motion while a plugin subscribes to this event
+    MouseHover(isize, usize), // line and column
+}
+
+/// What `zellij subscribe` prints, either plugin events describing the session or the output of
+/// chosen terminal panes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SubscriptionKind {
+    Tab,
+    Pane,
+    Mode,
+    Session,
+    PaneOutput,
+}
+
+impl SubscriptionKind {
+    /// The plugin event streamed for this kind, pane output is not a plugin event
+    pub fn event_type(&self) -> Option<EventType> {
+        match self {
+            SubscriptionKind::Tab => Some(EventType::TabUpdate),
+            SubscriptionKind::Pane => Some(EventType::PaneUpdate),
+            SubscriptionKind::Mode => Some(EventType::ModeUpdate),
+            SubscriptionKind::Session => Some(EventType::SessionUpdate),
+            SubscriptionKind::PaneOutput => None,
+        }
+    }
+}
+
+impl FromStr for SubscriptionKind {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tab" => Ok(SubscriptionKind::Tab),
+            "pane" => Ok(SubscriptionKind::Pane),
+            "mode" => Ok(SubscriptionKind::Mode),
+            "session" => Ok(SubscriptionKind::Session),
+            "pane-output" => Ok(SubscriptionKind::PaneOutput),
+            _ => Err(format!(
+                "Unknown event: {}, expected one of tab, pane, mode, session or pane-output",
+                s
+            )),
+        }
+    }
+}
+
+/// Capabilities a plugin has to be granted by the user before it can use the matching parts of
+/// the plugin API.
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    Copy,
+    Clone,
+    EnumIter,
+    EnumString,
+    ToString,
+    Serialize,
+    Deserialize,
+    PartialOrd,
+    Ord,
+)]
+pub enum PermissionType {
+    /// Run commands on the host, either in new panes or in the background
+    RunCommands,
+    /// Write bytes and characters to the STDIN of panes
+    WriteToStdin,
+    /// Read the viewport and scrollback of panes
+    ReadPaneContents,
+    /// Read and write files under the directory Zellij was started in
+    ChangeFilesystem,
+    /// Switch to other sessions
+    SwitchSessions,
+    /// Receive every key typed by the user, including the ones meant for other panes
+    ReadKeystrokes,
+}
+
+impl PermissionType {
+    pub fn display_name(&self) -> String {
+        match self {
+            PermissionType::RunCommands => "Run commands".to_owned(),
+            PermissionType::WriteToStdin => "Write to the STDIN of panes".to_owned(),
+            PermissionType::ReadPaneContents => "Read the contents of panes".to_owned(),
+            PermissionType::ChangeFilesystem => {
+                "Change files in the directory Zellij was started in".to_owned()
+            },
+            PermissionType::SwitchSessions => "Switch sessions".to_owned(),
+            PermissionType::ReadKeystrokes => "Read every key typed in any pane".to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PermissionStatus {
+    Granted,
+    Denied,
 }
 
 /// Describes the different input modes, which change the way that keystrokes will be interpreted.
@@ -460,12 +590,138 @@ pub struct TabInfo {
     pub other_focused_clients: Vec<ClientId>,
 }
 
+/// The panes of every tab in the session, keyed by tab position
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PaneManifest {
+    pub panes: BTreeMap<usize, Vec<PaneInfo>>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct PaneInfo {
+    /* subset of fields to publish to plugins */
+    pub id: u32,
+    pub is_plugin: bool,
+    pub is_focused: bool,
+    pub is_floating: bool,
+    pub is_suppressed: bool,
+    pub is_selectable: bool,
+    pub title: String,
+    pub exited: bool,
+    pub exit_status: Option<i32>,
+    pub terminal_command: Option<String>,
+    pub pane_x: usize,
+    pub pane_content_x: usize,
+    pub pane_y: usize,
+    pub pane_content_y: usize,
+    pub pane_rows: usize,
+    pub pane_content_rows: usize,
+    pub pane_columns: usize,
+    pub pane_content_columns: usize,
+}
+
+/// A tab, as listed by `zellij action list-tabs`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TabListing {
+    /// Starts at 1, like the tab numbers of `zellij action go-to-tab`
+    pub position: usize,
+    pub name: String,
+    pub pane_count: usize,
+    pub is_fullscreen_active: bool,
+    pub is_sync_panes_active: bool,
+    pub are_floating_panes_visible: bool,
+    pub focused_by_clients: Vec<ClientId>,
+}
+
+/// A pane, as listed by `zellij action list-panes`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PaneListing {
+    pub id: u32,
+    pub is_plugin: bool,
+    /// Starts at 1, like the tab numbers of `zellij action go-to-tab`
+    pub tab_position: usize,
+    pub title: String,
+    pub is_focused: bool,
+    pub is_floating: bool,
+    pub is_suppressed: bool,
+    pub exited: bool,
+    pub exit_status: Option<i32>,
+    pub x: usize,
+    pub y: usize,
+    pub rows: usize,
+    pub columns: usize,
+    /// The command running in a terminal pane, or the location of a plugin
+    pub command: Option<String>,
+    pub cwd: Option<PathBuf>,
+}
+
+/// A client connected to the session, as listed by `zellij action list-clients`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ClientListing {
+    pub id: ClientId,
+    /// Starts at 1, like the tab numbers of `zellij action go-to-tab`
+    pub tab_position: Option<usize>,
+    pub focused_pane_id: Option<u32>,
+    pub focused_pane_is_plugin: bool,
+}
+
+/// A running session, as listed in `Event::SessionUpdate`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct SessionInfo {
+    pub name: String,
+    pub tabs: usize,
+    pub connected_clients: usize,
+    pub is_current_session: bool,
+}
+
+/// Position and size of the pane of a plugin, with and without its frame
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct PaneGeometry {
+    pub x: usize,
+    pub y: usize,
+    pub rows: usize,
+    pub columns: usize,
+    pub content_x: usize,
+    pub content_y: usize,
+    pub content_rows: usize,
+    pub content_columns: usize,
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct PluginIds {
     pub plugin_id: u32,
     pub zellij_pid: u32,
 }
 
+/// Identifies a pane, as seen by plugins
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+pub enum PaneId {
+    Terminal(u32),
+    Plugin(u32),
+}
+
+impl FromStr for PaneId {
+    type Err = String;
+    /// Parses the pane ids printed by `zellij action list-panes`, eg. `terminal_3` or `plugin_1`.
+    /// A bare number is taken to be a terminal pane.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_id = |id: &str| {
+            id.parse::<u32>().map_err(|_| {
+                format!(
+                    "Failed to parse pane id: {}, expected eg. 3, terminal_3 or plugin_1",
+                    s
+                )
+            })
+        };
+        if let Some(id) = s.strip_prefix("plugin_") {
+            Ok(PaneId::Plugin(parse_id(id)?))
+        } else if let Some(id) = s.strip_prefix("terminal_") {
+            Ok(PaneId::Terminal(parse_id(id)?))
+        } else {
+            Ok(PaneId::Terminal(parse_id(s)?))
+        }
+    }
+}
+
 /// Tag used to identify the plugin in layout and config yaml files
 #[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
 pub struct PluginTag(String);
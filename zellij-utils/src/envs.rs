@@ -26,6 +26,12 @@ pub fn set_session_name(v: String) {
     set_var(SESSION_NAME_ENV_KEY, v);
 }
 
+/// Set in every terminal pane to the id of that pane, for use with `zellij action --pane-id`
+pub const PANE_ID_ENV_KEY: &str = "ZELLIJ_PANE_ID";
+/// Set in terminal panes to the index of their tab, for use with `zellij action --tab index_<n>`.
+/// Unlike its position, the index of a tab doesn't change when other tabs are closed
+pub const TAB_INDEX_ENV_KEY: &str = "ZELLIJ_TAB_INDEX";
+
 pub fn set_initial_environment_vars() {
     set_var("COLORTERM", "24bit");
 }
@@ -227,6 +227,10 @@ pub enum ScreenContext {
     HorizontalSplit,
     VerticalSplit,
     WriteCharacter,
+    WriteFromPlugin,
+    WriteToPaneId,
+    DumpPaneContentsForPlugin,
+    RequestPluginPermissions,
     ResizeLeft,
     ResizeRight,
     ResizeDown,
@@ -266,6 +270,10 @@ pub enum ScreenContext {
     ToggleActiveTerminalFullscreen,
     TogglePaneFrames,
     SetSelectable,
+    SetPaneTitle,
+    SetPaneBorderless,
+    SetPanePreferredSize,
+    LaunchOrFocusPlugin,
     SetInvisibleBorders,
     SetFixedHeight,
     SetFixedWidth,
@@ -296,6 +304,7 @@ pub enum ScreenContext {
     MouseHoldLeft,
     MouseHoldRight,
     MouseHoldMiddle,
+    MouseHover,
     Copy,
     ToggleTab,
     AddClient,
@@ -310,6 +319,14 @@ pub enum ScreenContext {
     SearchToggleCaseSensitivity,
     SearchToggleWholeWord,
     SearchToggleWrap,
+    QuerySessionInfo,
+    ListTabs,
+    ListPanes,
+    ListClients,
+    TargetedAction,
+    SubscribeToPaneOutput,
+    CopyCommandOutput,
+    CommandExited,
 }
 
 /// Stack call representations corresponding to the different types of [`PtyInstruction`]s.
@@ -325,6 +342,8 @@ pub enum PtyContext {
     ClosePane,
     CloseTab,
     ReRunCommandInPane,
+    ListPanes,
+    WaitForCommand,
     Exit,
 }
 
@@ -338,6 +357,11 @@ pub enum PluginContext {
     Exit,
     AddClient,
     RemoveClient,
+    PostMessageTo,
+    PermissionRequestResult,
+    Reload,
+    Pipe,
+    Subscribe,
 }
 
 /// Stack call representations corresponding to the different types of [`ClientInstruction`]s.
@@ -352,6 +376,16 @@ pub enum ClientContext {
     Connected,
     ActiveClients,
     OwnClientId,
+    PipeOutput,
+    PipeClosed,
+    SessionInfo,
+    QueryResponse,
+    ActionFailed,
+    SubscriptionEvent,
+    CommandOutput,
+    CommandExited,
+    SwitchSession,
+    InputSubscriptions,
 }
 
 /// Stack call representations corresponding to the different types of [`ServerInstruction`]s.
@@ -368,6 +402,19 @@ pub enum ServerContext {
     AttachClient,
     ConnStatus,
     ActiveClients,
+    PluginPermissionsResponse,
+    ActionFromPlugin,
+    PipeOutput,
+    ClosePipe,
+    QuerySessionInfo,
+    SessionInfo,
+    SwitchSession,
+    QueryResponse,
+    ActionFailed,
+    SubscriptionEvent,
+    CommandOutput,
+    CommandExited,
+    InputSubscriptions,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -1,16 +1,18 @@
 //! Definition of the actions that can be bound to keys.
 
 use super::command::RunCommandAction;
-use super::layout::{Layout, PaneLayout};
+use super::layout::{Layout, PaneLayout, RunPluginLocation};
 use crate::cli::CliAction;
-use crate::data::InputMode;
+use crate::data::{InputMode, PaneId, SubscriptionKind};
 use crate::input::config::{ConfigError, KdlError};
 use crate::input::options::OnForceClose;
 use miette::{NamedSource, Report};
 use serde::{Deserialize, Serialize};
 
+use std::convert::TryFrom;
 use std::path::PathBuf;
 use std::str::FromStr;
+use url::Url;
 
 use crate::position::Position;
 
@@ -109,6 +111,44 @@ impl FromStr for SearchOption {
     }
 }
 
+/// A tab, either by its position as shown by `zellij action list-tabs`, by its index (as found in
+/// `$ZELLIJ_TAB_INDEX`, it doesn't change when other tabs close) or by its name
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum TabSelector {
+    Position(usize),
+    Index(usize),
+    Name(String),
+}
+impl FromStr for TabSelector {
+    type Err = String;
+    /// Numbers are taken to be positions and `index_<n>` to be indices, so a tab named eg. `2`
+    /// can only be selected by constructing a [`TabSelector::Name`] (`--tab-name` on the command
+    /// line)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(Ok(index)) = s.strip_prefix("index_").map(str::parse::<usize>) {
+            return Ok(TabSelector::Index(index));
+        }
+        match s.parse::<usize>() {
+            Ok(position) => Ok(TabSelector::Position(position)),
+            Err(_) => Ok(TabSelector::Name(s.to_owned())),
+        }
+    }
+}
+
+/// The pane and/or tab that actions sent from the command line run against, instead of the ones
+/// focused by the user
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ActionTarget {
+    pub pane_id: Option<PaneId>,
+    pub tab: Option<TabSelector>,
+}
+
+impl ActionTarget {
+    pub fn is_empty(&self) -> bool {
+        self.pane_id.is_none() && self.tab.is_none()
+    }
+}
+
 // As these actions are bound to the default config, please
 // do take care when refactoring - or renaming.
 // They might need to be adjusted in the default config
@@ -211,6 +251,8 @@ pub enum Action {
     MouseHoldLeft(Position),
     MouseHoldRight(Position),
     MouseHoldMiddle(Position),
+    /// The mouse moved without any of its buttons held
+    MouseHover(Position),
     Copy,
     /// Confirm a prompt
     Confirm,
@@ -224,9 +266,52 @@ pub enum Action {
     Search(SearchDirection),
     /// Toggle case sensitivity of search
     SearchToggleOption(SearchOption),
+    /// Reload the plugin with the given location in place, optionally watching its wasm file to
+    /// reload it again whenever it changes
+    ReloadPlugin(RunPluginLocation, bool), // bool is watch true/false
+    /// Send a line of piped input to the plugin with the given location, `None` marking the end
+    /// of the input
+    PipeToPlugin(RunPluginLocation, Option<String>, Option<String>), // name, payload
+    /// Focus the pane of the plugin with the given location, or open it in a new pane if it isn't
+    /// running yet
+    LaunchOrFocusPlugin(RunPluginLocation, bool), // bool is floating true/false
+    /// Reply to the client with the tabs, panes or connected clients of the session, as JSON or
+    /// as a human readable table
+    ListTabs(bool), // bool is json true/false
+    ListPanes(bool),   // bool is json true/false
+    ListClients(bool), // bool is json true/false
+    /// Stream the given kinds of events to the client, along with the output of the given panes,
+    /// until it disconnects
+    Subscribe(Vec<SubscriptionKind>, Vec<PaneId>),
+    /// Report the exit status of the next command pane opened by the client once its command
+    /// exits, copying the output of the command to the client in the meantime if true
+    WaitForCommand(bool), // bool is print_output
 }
 
 impl Action {
+    /// Whether this action can run against an [`ActionTarget`] rather than the focused pane or tab
+    pub fn is_targetable(&self) -> bool {
+        matches!(
+            self,
+            Action::Write(_)
+                | Action::WriteChars(_)
+                | Action::Resize(_)
+                | Action::DumpScreen(..)
+                | Action::ScrollUp
+                | Action::ScrollDown
+                | Action::ScrollToBottom
+                | Action::PageScrollUp
+                | Action::PageScrollDown
+                | Action::HalfPageScrollUp
+                | Action::HalfPageScrollDown
+                | Action::CloseFocus
+                | Action::PaneNameInput(_)
+                | Action::UndoRenamePane
+                | Action::CloseTab
+                | Action::TabNameInput(_)
+                | Action::UndoRenameTab
+        )
+    }
     pub fn actions_from_cli(cli_action: CliAction) -> Result<Vec<Action>, String> {
         match cli_action {
             CliAction::Write { bytes } => Ok(vec![Action::Write(bytes)]),
@@ -259,6 +344,8 @@ impl Action {
                 floating,
                 name,
                 close_on_exit,
+                wait,
+                print_output,
             } => {
                 if !command.is_empty() {
                     let mut command = command.clone();
@@ -272,17 +359,16 @@ impl Action {
                         direction,
                         hold_on_close,
                     };
-                    if floating {
-                        Ok(vec![Action::NewFloatingPane(
-                            Some(run_command_action),
-                            name,
-                        )])
+                    let new_pane = if floating {
+                        Action::NewFloatingPane(Some(run_command_action), name)
+                    } else {
+                        Action::NewTiledPane(direction, Some(run_command_action), name)
+                    };
+                    if wait {
+                        // has to reach the server before the pane is opened
+                        Ok(vec![Action::WaitForCommand(print_output), new_pane])
                     } else {
-                        Ok(vec![Action::NewTiledPane(
-                            direction,
-                            Some(run_command_action),
-                            name,
-                        )])
+                        Ok(vec![new_pane])
                     }
                 } else {
                     if floating {
@@ -383,6 +469,47 @@ impl Action {
                     Ok(vec![Action::NewTab(None, name)])
                 }
             },
+            CliAction::ReloadPlugin { url, watch } => {
+                let url = Url::parse(&url).map_err(|e| format!("Failed to parse url: {}", e))?;
+                let location = RunPluginLocation::try_from(url).map_err(|e| e.to_string())?;
+                Ok(vec![Action::ReloadPlugin(location, watch)])
+            },
+            CliAction::LaunchOrFocusPlugin { url, floating } => {
+                let url = Url::parse(&url).map_err(|e| format!("Failed to parse url: {}", e))?;
+                let location = RunPluginLocation::try_from(url).map_err(|e| e.to_string())?;
+                Ok(vec![Action::LaunchOrFocusPlugin(location, floating)])
+            },
+            CliAction::Pipe { plugin, name } => {
+                let url = Url::parse(&plugin).map_err(|e| format!("Failed to parse url: {}", e))?;
+                let location = RunPluginLocation::try_from(url).map_err(|e| e.to_string())?;
+                Ok(vec![Action::PipeToPlugin(location, name, None)])
+            },
+            CliAction::Subscribe {
+                mut events,
+                output_pane_id,
+            } => {
+                if events.is_empty() {
+                    events = if output_pane_id.is_empty() {
+                        vec![
+                            SubscriptionKind::Tab,
+                            SubscriptionKind::Pane,
+                            SubscriptionKind::Mode,
+                            SubscriptionKind::Session,
+                        ]
+                    } else {
+                        vec![SubscriptionKind::PaneOutput]
+                    };
+                }
+                if events.contains(&SubscriptionKind::PaneOutput) && output_pane_id.is_empty() {
+                    return Err(
+                        "pane-output needs the panes to print given with --output-pane-id".into(),
+                    );
+                }
+                Ok(vec![Action::Subscribe(events, output_pane_id)])
+            },
+            CliAction::ListTabs { json } => Ok(vec![Action::ListTabs(json)]),
+            CliAction::ListPanes { json } => Ok(vec![Action::ListPanes(json)]),
+            CliAction::ListClients { json } => Ok(vec![Action::ListClients(json)]),
         }
     }
 }
@@ -231,11 +231,11 @@ impl Config {
 mod config_test {
     use super::*;
     use crate::data::{InputMode, Palette, PaletteColor, PluginTag};
-    use crate::input::layout::RunPluginLocation;
+    use crate::input::layout::{PluginUserConfiguration, RunPlugin, RunPluginLocation};
     use crate::input::options::{Clipboard, OnForceClose};
     use crate::input::plugins::{PluginConfig, PluginType, PluginsConfig};
     use crate::input::theme::{FrameConfig, Theme, Themes, UiConfig};
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
     use std::io::Write;
     use tempfile::tempdir;
 
@@ -597,6 +597,9 @@ mod config_test {
                 run: PluginType::Pane(None),
                 location: RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
                 _allow_exec_host_cmd: false,
+                userspace_configuration: Default::default(),
+                fuel_limit: None,
+                memory_limit: None,
             },
         );
         expected_plugin_configuration.insert(
@@ -606,6 +609,9 @@ mod config_test {
                 run: PluginType::Pane(None),
                 location: RunPluginLocation::Zellij(PluginTag::new("status-bar")),
                 _allow_exec_host_cmd: false,
+                userspace_configuration: Default::default(),
+                fuel_limit: None,
+                memory_limit: None,
             },
         );
         expected_plugin_configuration.insert(
@@ -615,6 +621,9 @@ mod config_test {
                 run: PluginType::Pane(None),
                 location: RunPluginLocation::Zellij(PluginTag::new("strider")),
                 _allow_exec_host_cmd: true,
+                userspace_configuration: Default::default(),
+                fuel_limit: None,
+                memory_limit: None,
             },
         );
         expected_plugin_configuration.insert(
@@ -624,6 +633,9 @@ mod config_test {
                 run: PluginType::Pane(None),
                 location: RunPluginLocation::Zellij(PluginTag::new("compact-bar")),
                 _allow_exec_host_cmd: false,
+                userspace_configuration: Default::default(),
+                fuel_limit: None,
+                memory_limit: None,
             },
         );
         assert_eq!(
@@ -633,6 +645,104 @@ mod config_test {
         );
     }
 
+    #[test]
+    fn can_define_plugin_user_configuration_in_configfile() {
+        let config_contents = r#"
+            plugins {
+                status-bar {
+                    path "status-bar"
+                    hide_hints true
+                    theme "dark"
+                }
+            }
+        "#;
+        let config = Config::from_kdl(config_contents, None).unwrap();
+        let mut expected_user_configuration = BTreeMap::new();
+        expected_user_configuration.insert("hide_hints".to_owned(), "true".to_owned());
+        expected_user_configuration.insert("theme".to_owned(), "dark".to_owned());
+        let status_bar = config
+            .plugins
+            .get(RunPlugin {
+                _allow_exec_host_cmd: false,
+                location: RunPluginLocation::Zellij(PluginTag::new("status-bar")),
+                configuration: Default::default(),
+            })
+            .unwrap();
+        assert_eq!(
+            status_bar.userspace_configuration,
+            PluginUserConfiguration::new(expected_user_configuration),
+            "Plugin user configuration defined in config"
+        );
+    }
+
+    #[test]
+    fn can_define_headless_plugins_in_configfile() {
+        let config_contents = r#"
+            plugins {
+                tab-namer {
+                    path "/path/to/tab-namer.wasm"
+                    headless true
+                }
+                status-bar { path "status-bar"; }
+            }
+        "#;
+        let config = Config::from_kdl(config_contents, None).unwrap();
+        let headless_plugins: Vec<&PathBuf> = config
+            .plugins
+            .iter()
+            .filter(|p| p.run == PluginType::Headless)
+            .map(|p| &p.path)
+            .collect();
+        assert_eq!(
+            headless_plugins,
+            vec![&PathBuf::from("/path/to/tab-namer.wasm")],
+            "Headless plugins defined in config"
+        );
+    }
+
+    #[test]
+    fn can_define_plugin_limits_in_configfile() {
+        let config_contents = r#"
+            plugins {
+                strider {
+                    path "strider"
+                    fuel_limit 1000000
+                    memory_limit 64
+                }
+            }
+        "#;
+        let config = Config::from_kdl(config_contents, None).unwrap();
+        let strider = config
+            .plugins
+            .get(RunPlugin {
+                _allow_exec_host_cmd: false,
+                location: RunPluginLocation::Zellij(PluginTag::new("strider")),
+                configuration: Default::default(),
+            })
+            .unwrap();
+        assert_eq!(strider.fuel_limit, Some(1000000), "Fuel limit defined");
+        assert_eq!(strider.memory_limit, Some(64), "Memory limit defined");
+        assert_eq!(
+            strider.userspace_configuration,
+            PluginUserConfiguration::default(),
+            "Limits are not passed on to the plugin"
+        );
+    }
+
+    #[test]
+    fn plugin_limits_must_be_positive() {
+        let config_contents = r#"
+            plugins {
+                strider {
+                    path "strider"
+                    fuel_limit -1
+                }
+            }
+        "#;
+        let config = Config::from_kdl(config_contents, None);
+        assert!(config.is_err(), "Negative fuel limit is rejected");
+    }
+
     #[test]
     fn can_define_ui_configuration_in_configfile() {
         let config_contents = r#"
@@ -40,8 +40,35 @@ impl Keybinds {
             .get(mode)
             .and_then(|normal_mode_keybindings| normal_mode_keybindings.get(key))
             .cloned()
+            .or_else(|| self.built_in_prompt_actions(mode, key))
             .unwrap_or_else(|| vec![self.default_action_for_mode(mode, raw_bytes)])
     }
+    /// Prompts can be answered with y/n (or denied with Esc) even when the prompt mode keybindings
+    /// (eg. from a config predating the prompt mode) have no way to confirm or deny them
+    fn built_in_prompt_actions(&self, mode: &InputMode, key: &Key) -> Option<Vec<Action>> {
+        if *mode != InputMode::Prompt {
+            return None;
+        }
+        let can_answer = self.0.get(mode).map_or(false, |prompt_keybindings| {
+            prompt_keybindings
+                .values()
+                .flatten()
+                .any(|action| matches!(action, Action::Confirm) || matches!(action, Action::Deny))
+        });
+        if can_answer {
+            return None;
+        }
+        match key {
+            Key::Char('y') => Some(vec![
+                Action::Confirm,
+                Action::SwitchToMode(InputMode::Normal),
+            ]),
+            Key::Char('n') | Key::Esc => {
+                Some(vec![Action::Deny, Action::SwitchToMode(InputMode::Normal)])
+            },
+            _ => None,
+        }
+    }
     pub fn get_input_mode_mut(&mut self, input_mode: &InputMode) -> &mut HashMap<Key, Vec<Action>> {
         self.0.entry(*input_mode).or_insert_with(HashMap::new)
     }
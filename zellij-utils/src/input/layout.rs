@@ -24,6 +24,7 @@ use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 use std::vec::Vec;
 use std::{
+    collections::BTreeMap,
     fmt,
     ops::Not,
     path::{Path, PathBuf},
@@ -157,6 +158,28 @@ pub struct RunPlugin {
     #[serde(default)]
     pub _allow_exec_host_cmd: bool,
     pub location: RunPluginLocation,
+    #[serde(default)]
+    pub configuration: PluginUserConfiguration,
+}
+
+/// Arbitrary key/value pairs configured by the user for a plugin instance, these are passed on to
+/// the plugin as is
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PluginUserConfiguration(BTreeMap<String, String>);
+
+impl PluginUserConfiguration {
+    pub fn new(configuration: BTreeMap<String, String>) -> Self {
+        PluginUserConfiguration(configuration)
+    }
+    pub fn inner(&self) -> &BTreeMap<String, String> {
+        &self.0
+    }
+    /// Merges two configurations, the values of `other` override those of `self`
+    pub fn merge(&self, other: &PluginUserConfiguration) -> Self {
+        let mut configuration = self.0.clone();
+        configuration.extend(other.0.clone());
+        PluginUserConfiguration(configuration)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
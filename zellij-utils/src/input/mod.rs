@@ -4,6 +4,7 @@ pub mod config;
 pub mod keybinds;
 pub mod layout;
 pub mod options;
+pub mod permission;
 pub mod plugins;
 pub mod theme;
 
@@ -17,7 +18,9 @@ pub use not_wasm::*;
 #[cfg(not(target_family = "wasm"))]
 mod not_wasm {
     use crate::{
-        data::{CharOrArrow, Direction, InputMode, Key, ModeInfo, PluginCapabilities},
+        data::{
+            CharOrArrow, Direction, InputMode, Key, KeyModifiers, ModeInfo, PluginCapabilities,
+        },
         envs,
         ipc::ClientAttributes,
     };
@@ -55,6 +58,15 @@ mod not_wasm {
         ret
     }
 
+    pub fn cast_termwiz_modifiers(modifiers: Modifiers) -> KeyModifiers {
+        KeyModifiers {
+            shift: modifiers.contains(Modifiers::SHIFT),
+            alt: modifiers.contains(Modifiers::ALT),
+            ctrl: modifiers.contains(Modifiers::CTRL),
+            super_key: modifiers.contains(Modifiers::SUPER),
+        }
+    }
+
     // FIXME: This is an absolutely cursed function that should be destroyed as soon
     // as an alternative that doesn't touch zellij-tile can be developed...
     pub fn cast_termwiz_key(event: KeyEvent, raw_bytes: &[u8]) -> Key {
@@ -0,0 +1,68 @@
+//! Permissions granted by the user to plugins, persisted between sessions
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::Write,
+    path::PathBuf,
+};
+
+use crate::{consts::ZELLIJ_CACHE_DIR, data::PermissionType};
+
+pub type GrantedPermission = HashMap<String, Vec<PermissionType>>;
+
+#[derive(Default, Debug)]
+pub struct PermissionCache {
+    path: PathBuf,
+    granted: GrantedPermission,
+}
+
+impl PermissionCache {
+    /// Where the granted permissions are kept unless another path is given
+    pub fn default_path() -> PathBuf {
+        ZELLIJ_CACHE_DIR.join("permissions.kdl")
+    }
+
+    /// Loads the granted permissions from `path`, or from the default location in the Zellij
+    /// cache directory. A missing or unparsable file is treated as "nothing granted yet".
+    pub fn from_path_or_default(path: Option<PathBuf>) -> Self {
+        let path = path.unwrap_or_else(PermissionCache::default_path);
+        let granted = match fs::read_to_string(&path) {
+            Ok(raw_string) => PermissionCache::from_string(raw_string).unwrap_or_else(|e| {
+                log::error!("Failed to parse permissions file {:?}: {}", path, e);
+                GrantedPermission::default()
+            }),
+            Err(_) => GrantedPermission::default(),
+        };
+        PermissionCache { path, granted }
+    }
+
+    pub fn get_permissions(&self, plugin_id: &str) -> Option<&Vec<PermissionType>> {
+        self.granted.get(plugin_id)
+    }
+
+    /// Adds `permissions` to the ones granted to `plugin_id` and writes the result to disk
+    pub fn cache(
+        &mut self,
+        plugin_id: String,
+        permissions: Vec<PermissionType>,
+    ) -> std::io::Result<()> {
+        let granted = self.granted.entry(plugin_id).or_insert_with(Vec::new);
+        for permission in permissions {
+            if !granted.contains(&permission) {
+                granted.push(permission);
+            }
+        }
+        granted.sort();
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut f = File::create(&self.path)?;
+        write!(f, "{}", PermissionCache::to_string(&self.granted))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[path = "./unit/permission_test.rs"]
+mod permission_test;
@@ -8,7 +8,7 @@ use thiserror::Error;
 use serde::{Deserialize, Serialize};
 use url::Url;
 
-use super::layout::{RunPlugin, RunPluginLocation};
+use super::layout::{PluginUserConfiguration, RunPlugin, RunPluginLocation};
 pub use crate::data::PluginTag;
 
 use std::collections::BTreeMap;
@@ -45,9 +45,16 @@ impl PluginsConfig {
                 run: PluginType::Pane(None),
                 _allow_exec_host_cmd: run._allow_exec_host_cmd,
                 location: run.location.clone(),
+                userspace_configuration: run.configuration.clone(),
+                // limits can only be set in the `plugins` block of the config
+                fuel_limit: None,
+                memory_limit: None,
             }),
             RunPluginLocation::Zellij(tag) => self.0.get(tag).cloned().map(|plugin| PluginConfig {
+                // plugins referenced from a layout always run in a pane
+                run: PluginType::Pane(None),
                 _allow_exec_host_cmd: run._allow_exec_host_cmd,
+                userspace_configuration: plugin.userspace_configuration.merge(&run.configuration),
                 ..plugin
             }),
         }
@@ -83,6 +90,17 @@ pub struct PluginConfig {
     pub _allow_exec_host_cmd: bool,
     /// Original location of the
     pub location: RunPluginLocation,
+    /// Custom configuration for this plugin, passed on to it as is
+    #[serde(default)]
+    pub userspace_configuration: PluginUserConfiguration,
+    /// Maximum number of wasm instructions the plugin may run while handling a single call, only
+    /// set for plugins configured in the `plugins` block of the config
+    #[serde(default)]
+    pub fuel_limit: Option<u64>,
+    /// Maximum size of the plugin's linear memory, in MiB, only set for plugins configured in the
+    /// `plugins` block of the config
+    #[serde(default)]
+    pub memory_limit: Option<u64>,
 }
 
 impl PluginConfig {
@@ -105,6 +123,18 @@ impl PluginConfig {
             .ok()
     }
 
+    /// Resolve the path of the wasm file of the plugin, following the same lookup chain as
+    /// [`Self::resolve_wasm_bytes`]
+    pub fn resolve_wasm_path(&self, plugin_dir: &Path) -> Option<PathBuf> {
+        [
+            self.path.clone(),
+            self.path.with_extension("wasm"),
+            plugin_dir.join(&self.path).with_extension("wasm"),
+        ]
+        .into_iter()
+        .find(|path| path.is_file())
+    }
+
     /// Sets the tab index inside of the plugin type of the run field.
     pub fn set_tab_index(&mut self, tab_index: usize) {
         match self.run {
@@ -2,7 +2,10 @@ use super::super::actions::*;
 use super::super::keybinds::*;
 use crate::data::{self, CharOrArrow, Key};
 use crate::input::config::Config;
+use crate::input::layout::RunPluginLocation;
+use crate::input::plugins::PluginTag;
 use insta::assert_snapshot;
+use std::path::PathBuf;
 use strum::IntoEnumIterator;
 
 #[test]
@@ -25,6 +28,41 @@ fn can_define_keybindings_in_configfile() {
     );
 }
 
+#[test]
+fn can_define_launch_or_focus_plugin_keybinding_in_configfile() {
+    let config_contents = r#"
+        keybinds {
+            normal {
+                bind "Ctrl y" { LaunchOrFocusPlugin "zellij:strider" { floating true; }; }
+                bind "Ctrl u" { LaunchOrFocusPlugin "file:/path/to/plugin.wasm"; }
+            }
+        }
+    "#;
+    let config = Config::from_kdl(config_contents, None).unwrap();
+    let ctrl_y_normal_mode_action = config
+        .keybinds
+        .get_actions_for_key_in_mode(&InputMode::Normal, &Key::Ctrl('y'));
+    let ctrl_u_normal_mode_action = config
+        .keybinds
+        .get_actions_for_key_in_mode(&InputMode::Normal, &Key::Ctrl('u'));
+    assert_eq!(
+        ctrl_y_normal_mode_action,
+        Some(&vec![Action::LaunchOrFocusPlugin(
+            RunPluginLocation::Zellij(PluginTag::new("strider")),
+            true
+        )]),
+        "Floating plugin keybinding successfully defined in config"
+    );
+    assert_eq!(
+        ctrl_u_normal_mode_action,
+        Some(&vec![Action::LaunchOrFocusPlugin(
+            RunPluginLocation::File(PathBuf::from("/path/to/plugin.wasm")),
+            false
+        )]),
+        "Tiled plugin keybinding successfully defined in config"
+    );
+}
+
 #[test]
 fn can_define_multiple_keybinds_for_same_action() {
     let config_contents = r#"
@@ -542,3 +580,49 @@ fn error_received_on_unknown_key_instruction() {
     let config_error = Config::from_kdl(config_contents, None).unwrap_err();
     assert_snapshot!(format!("{:?}", config_error));
 }
+
+#[test]
+fn prompts_can_be_answered_without_prompt_keybindings() {
+    let config_contents = r#"
+        keybinds {
+            normal {
+                bind "Ctrl g" { SwitchToMode "Locked"; }
+            }
+        }
+    "#;
+    let config = Config::from_kdl(config_contents, None).unwrap();
+    let actions_for_key = |key| {
+        config
+            .keybinds
+            .get_actions_for_key_in_mode_or_default_action(&InputMode::Prompt, &key, vec![])
+    };
+    assert_eq!(
+        actions_for_key(Key::Char('y')),
+        vec![Action::Confirm, Action::SwitchToMode(InputMode::Normal)]
+    );
+    assert_eq!(
+        actions_for_key(Key::Char('n')),
+        vec![Action::Deny, Action::SwitchToMode(InputMode::Normal)]
+    );
+    assert_eq!(
+        actions_for_key(Key::Esc),
+        vec![Action::Deny, Action::SwitchToMode(InputMode::Normal)]
+    );
+    assert_eq!(actions_for_key(Key::Char('x')), vec![Action::NoOp]);
+}
+
+#[test]
+fn prompt_keybindings_answering_prompts_replace_the_built_in_ones() {
+    let config_contents = r#"
+        keybinds {
+            prompt {
+                bind "Enter" { Confirm; SwitchToMode "Normal"; }
+            }
+        }
+    "#;
+    let config = Config::from_kdl(config_contents, None).unwrap();
+    let y_in_prompt_mode = config
+        .keybinds
+        .get_actions_for_key_in_mode_or_default_action(&InputMode::Prompt, &Key::Char('y'), vec![]);
+    assert_eq!(y_in_prompt_mode, vec![Action::NoOp]);
+}
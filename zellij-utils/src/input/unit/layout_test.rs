@@ -301,6 +301,7 @@ fn layout_with_plugin_panes() {
                     run: Some(Run::Plugin(RunPlugin {
                         location: RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
                         _allow_exec_host_cmd: false,
+                        configuration: Default::default(),
                     })),
                     ..Default::default()
                 },
@@ -308,6 +309,7 @@ fn layout_with_plugin_panes() {
                     run: Some(Run::Plugin(RunPlugin {
                         location: RunPluginLocation::File(PathBuf::from("/path/to/my/plugin.wasm")),
                         _allow_exec_host_cmd: false,
+                        configuration: Default::default(),
                     })),
                     ..Default::default()
                 },
@@ -319,6 +321,41 @@ fn layout_with_plugin_panes() {
     assert_eq!(layout, expected_layout);
 }
 
+#[test]
+fn layout_with_plugin_panes_and_user_configuration() {
+    let kdl_layout = r#"
+        layout {
+            pane {
+                plugin location="zellij:status-bar" {
+                    hide_hints true
+                    theme "dark"
+                    max_items 3
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None).unwrap();
+    let mut expected_configuration = BTreeMap::new();
+    expected_configuration.insert("hide_hints".to_owned(), "true".to_owned());
+    expected_configuration.insert("theme".to_owned(), "dark".to_owned());
+    expected_configuration.insert("max_items".to_owned(), "3".to_owned());
+    let expected_layout = Layout {
+        template: Some(PaneLayout {
+            children: vec![PaneLayout {
+                run: Some(Run::Plugin(RunPlugin {
+                    location: RunPluginLocation::Zellij(PluginTag::new("status-bar")),
+                    _allow_exec_host_cmd: false,
+                    configuration: PluginUserConfiguration::new(expected_configuration),
+                })),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    assert_eq!(layout, expected_layout);
+}
+
 #[test]
 fn layout_with_borderless_panes() {
     let kdl_layout = r#"
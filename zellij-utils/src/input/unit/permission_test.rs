@@ -0,0 +1,37 @@
+use super::*;
+use tempfile::tempdir;
+
+#[test]
+fn granted_permissions_round_trip_through_kdl() {
+    let mut granted = GrantedPermission::default();
+    granted.insert(
+        "file:/path/to/my/plugin.wasm".to_owned(),
+        vec![PermissionType::RunCommands, PermissionType::WriteToStdin],
+    );
+    granted.insert(
+        "zellij:strider".to_owned(),
+        vec![PermissionType::ChangeFilesystem],
+    );
+    let serialized = PermissionCache::to_string(&granted);
+    let deserialized = PermissionCache::from_string(serialized).unwrap();
+    assert_eq!(deserialized, granted);
+}
+
+#[test]
+fn cache_persists_permissions_to_disk() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("permissions.kdl");
+    let mut cache = PermissionCache::from_path_or_default(Some(path.clone()));
+    assert_eq!(cache.get_permissions("zellij:strider"), None);
+    cache
+        .cache(
+            "zellij:strider".to_owned(),
+            vec![PermissionType::ChangeFilesystem],
+        )
+        .unwrap();
+    let cache = PermissionCache::from_path_or_default(Some(path));
+    assert_eq!(
+        cache.get_permissions("zellij:strider"),
+        Some(&vec![PermissionType::ChangeFilesystem])
+    );
+}
@@ -1,14 +1,21 @@
 //! IPC stuff for starting to split things into a client and server model.
 use crate::{
     cli::CliArgs,
-    data::{ClientId, InputMode, Style},
+    data::{ClientId, InputMode, Key, KeyModifiers, SessionInfo, Style},
     errors::{get_current_ctx, ErrorContext},
     input::keybinds::Keybinds,
-    input::{actions::Action, layout::Layout, options::Options, plugins::PluginsConfig},
+    input::{
+        actions::{Action, ActionTarget},
+        layout::Layout,
+        options::Options,
+        plugins::PluginsConfig,
+    },
     pane_size::{Size, SizeInPixels},
 };
 use interprocess::local_socket::LocalSocketStream;
 use log::warn;
+use nix::sys::socket::{setsockopt, sockopt};
+use nix::sys::time::{TimeVal, TimeValLike};
 use nix::unistd::dup;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -16,6 +23,7 @@ use std::{
     io::{self, Write},
     marker::PhantomData,
     os::unix::io::{AsRawFd, FromRawFd},
+    path::Path,
 };
 
 type SessionId = u64;
@@ -90,10 +98,15 @@ pub enum ClientToServerMsg {
     ),
     AttachClient(ClientAttributes, Options),
     Action(Action, Option<ClientId>),
+    TargetedAction(Action, ActionTarget), // run against a pane or tab other than the focused one
     ClientExited,
     KillSession,
     ConnStatus,
     ListClients,
+    QuerySessionInfo,
+    /// A key pressed by the client, sent along with the actions it triggers for the plugins
+    /// subscribed to `KeyPress`
+    KeyPress(Key, KeyModifiers),
 }
 
 // Types of messages sent from the server to the client
@@ -109,6 +122,25 @@ pub enum ServerToClientMsg {
     SwitchToMode(InputMode),
     Connected,
     ActiveClients(Vec<ClientId>),
+    /// Output of a plugin for a `zellij pipe` client
+    PipeOutput(String),
+    /// The plugins are done with the input of a `zellij pipe` client, which can exit
+    PipeClosed,
+    SessionInfo(SessionInfo),
+    /// The answer to one of the `list-*` CLI actions, ready to be printed
+    QueryResponse(String),
+    /// An action a CLI client targeted at a specific pane or tab could not be run
+    ActionFailed(String),
+    SubscriptionEvent(String), // a line of JSON
+    /// Output of the command a `zellij run --wait` client waits for
+    CommandOutput(Vec<u8>),
+    /// The command a `zellij run --wait` client waits for exited, with this exit status if known
+    CommandExited(Option<i32>),
+    /// Detach from this session and attach to the one with the given name, starting it if needed
+    SwitchSession(String),
+    /// Whether plugins subscribe to `KeyPress` and `MouseHover`, the client only sends every key
+    /// press and reports mouse motion while they do
+    InputSubscriptions(bool, bool), // key presses, mouse motion
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -138,6 +170,29 @@ impl Display for ExitReason {
     }
 }
 
+/// How long [`query_session_info`] waits for a server to answer
+const SESSION_QUERY_TIMEOUT_MS: i64 = 500;
+
+/// Asks the server listening on `socket_path` about its session, returns `None` if no server
+/// answers in time (eg. the socket was left behind by a server that crashed, or the server hangs)
+pub fn query_session_info(socket_path: &Path) -> Option<SessionInfo> {
+    let stream = LocalSocketStream::connect(socket_path).ok()?;
+    // shared with the receiver below, which dups the socket
+    setsockopt(
+        stream.as_raw_fd(),
+        sockopt::ReceiveTimeout,
+        &TimeVal::milliseconds(SESSION_QUERY_TIMEOUT_MS),
+    )
+    .ok()?;
+    let mut sender: IpcSenderWithContext<ClientToServerMsg> = IpcSenderWithContext::new(stream);
+    sender.send(ClientToServerMsg::QuerySessionInfo).ok()?;
+    let mut receiver: IpcReceiverWithContext<ServerToClientMsg> = sender.get_receiver();
+    match receiver.recv() {
+        Some((ServerToClientMsg::SessionInfo(session_info), _)) => Some(session_info),
+        _ => None,
+    }
+}
+
 /// Sends messages on a stream socket, along with an [`ErrorContext`].
 pub struct IpcSenderWithContext<T: Serialize> {
     sender: io::BufWriter<LocalSocketStream>,
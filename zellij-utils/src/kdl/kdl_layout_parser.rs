@@ -1,7 +1,10 @@
 use crate::input::{
     command::RunCommand,
     config::ConfigError,
-    layout::{Layout, PaneLayout, Run, RunPlugin, RunPluginLocation, SplitDirection, SplitSize},
+    layout::{
+        Layout, PaneLayout, PluginUserConfiguration, Run, RunPlugin, RunPluginLocation,
+        SplitDirection, SplitSize,
+    },
 };
 
 use kdl::*;
@@ -193,9 +196,12 @@ impl<'a> KdlLayoutParser<'a> {
             )
         })?;
         let location = RunPluginLocation::try_from(url)?;
+        let configuration =
+            PluginUserConfiguration::from_kdl(plugin_block, &["location", "_allow_exec_host_cmd"]);
         Ok(Some(Run::Plugin(RunPlugin {
             _allow_exec_host_cmd,
             location,
+            configuration,
         })))
     }
     fn parse_args(&self, pane_node: &KdlNode) -> Result<Option<Vec<String>>, ConfigError> {
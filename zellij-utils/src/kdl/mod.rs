@@ -1,15 +1,16 @@
 mod kdl_layout_parser;
-use crate::data::{InputMode, Key, Palette, PaletteColor};
+use crate::data::{InputMode, Key, Palette, PaletteColor, PermissionType};
 use crate::envs::EnvironmentVariables;
 use crate::input::command::RunCommand;
 use crate::input::config::{Config, ConfigError, KdlError};
 use crate::input::keybinds::Keybinds;
-use crate::input::layout::{Layout, RunPlugin, RunPluginLocation};
+use crate::input::layout::{Layout, PluginUserConfiguration, RunPlugin, RunPluginLocation};
 use crate::input::options::{Clipboard, OnForceClose, Options};
+use crate::input::permission::{GrantedPermission, PermissionCache};
 use crate::input::plugins::{PluginConfig, PluginTag, PluginType, PluginsConfig};
 use crate::input::theme::{FrameConfig, Theme, Themes, UiConfig};
 use kdl_layout_parser::KdlLayoutParser;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
 use std::io::Read;
 use strum::IntoEnumIterator;
@@ -746,6 +747,32 @@ impl TryFrom<&KdlNode> for Action {
                 };
                 Ok(Action::Run(run_command_action))
             },
+            "LaunchOrFocusPlugin" => {
+                let arguments = action_arguments.iter().copied();
+                let mut args = kdl_arguments_that_are_strings(arguments)?;
+                if args.is_empty() {
+                    return Err(ConfigError::new_kdl_error(
+                        "No plugin found in LaunchOrFocusPlugin action".into(),
+                        kdl_action.span().offset(),
+                        kdl_action.span().len(),
+                    ));
+                }
+                let url = Url::parse(&args.remove(0)).map_err(|e| {
+                    ConfigError::new_kdl_error(
+                        format!("Failed to parse url: {:?}", e),
+                        kdl_action.span().offset(),
+                        kdl_action.span().len(),
+                    )
+                })?;
+                let location = RunPluginLocation::try_from(url)?;
+                let floating = action_children
+                    .first()
+                    .and_then(|c_m| c_m.get("floating"))
+                    .and_then(|f| f.entries().iter().next())
+                    .and_then(|f| f.value().as_bool())
+                    .unwrap_or(false);
+                Ok(Action::LaunchOrFocusPlugin(location, floating))
+            },
             _ => Err(ConfigError::new_kdl_error(
                 format!("Unsupported action: {}", action_name).into(),
                 kdl_action.span().offset(),
@@ -1259,12 +1286,40 @@ impl RunPlugin {
             )
         })?;
         let location = RunPluginLocation::try_from(url)?;
+        let configuration =
+            PluginUserConfiguration::from_kdl(kdl_node, &["location", "_allow_exec_host_cmd"]);
         Ok(RunPlugin {
             _allow_exec_host_cmd,
             location,
+            configuration,
         })
     }
 }
+
+impl PluginUserConfiguration {
+    /// Collects the children of a plugin node as key/value pairs, skipping the ones Zellij itself
+    /// interprets (eg. the plugin's location)
+    pub fn from_kdl(kdl_plugin_node: &KdlNode, reserved_names: &[&str]) -> Self {
+        let mut configuration = BTreeMap::new();
+        for child in kdl_children_nodes!(kdl_plugin_node).unwrap_or(&[]) {
+            let name = kdl_name!(child);
+            if reserved_names.contains(&name) {
+                continue;
+            }
+            let value = child
+                .entries()
+                .iter()
+                .next()
+                .map(|entry| match entry.value().as_string() {
+                    Some(string_value) => string_value.to_owned(),
+                    None => entry.value().to_string(),
+                })
+                .unwrap_or_default();
+            configuration.insert(name.to_owned(), value);
+        }
+        PluginUserConfiguration::new(configuration)
+    }
+}
 impl Layout {
     pub fn from_kdl(
         raw_layout: &str,
@@ -1535,11 +1590,45 @@ impl PluginsConfig {
             let allow_exec_host_cmd =
                 kdl_children_property_first_arg_as_bool!(plugin_config, "_allow_exec_host_cmd")
                     .unwrap_or(false);
+            let headless = kdl_children_property_first_arg_as_bool!(plugin_config, "headless")
+                .unwrap_or(false);
+            let get_limit = |limit_name: &str| -> Result<Option<u64>, ConfigError> {
+                match kdl_get_child!(plugin_config, limit_name) {
+                    Some(limit) => match kdl_first_entry_as_i64!(limit) {
+                        Some(value) if value > 0 => Ok(Some(value as u64)),
+                        _ => Err(kdl_parsing_error!(
+                            format!("'{}' must be a positive integer", limit_name),
+                            limit
+                        )),
+                    },
+                    None => Ok(None),
+                }
+            };
+            let fuel_limit = get_limit("fuel_limit")?;
+            let memory_limit = get_limit("memory_limit")?;
+            let userspace_configuration = PluginUserConfiguration::from_kdl(
+                plugin_config,
+                &[
+                    "path",
+                    "_allow_exec_host_cmd",
+                    "headless",
+                    "fuel_limit",
+                    "memory_limit",
+                ],
+            );
+            let run = if headless {
+                PluginType::Headless
+            } else {
+                PluginType::Pane(None)
+            };
             let plugin_config = PluginConfig {
                 path,
-                run: PluginType::Pane(None),
+                run,
                 location: RunPluginLocation::Zellij(plugin_tag.clone()),
                 _allow_exec_host_cmd: allow_exec_host_cmd,
+                userspace_configuration,
+                fuel_limit,
+                memory_limit,
             };
             plugins.insert(plugin_tag, plugin_config);
         }
@@ -1603,3 +1692,48 @@ impl Themes {
         Ok(all_themes_in_file)
     }
 }
+
+impl PermissionCache {
+    pub fn from_string(raw_string: String) -> Result<GrantedPermission, ConfigError> {
+        let kdl_document: KdlDocument = raw_string.parse()?;
+
+        let mut granted_permission = GrantedPermission::default();
+
+        for node in kdl_document.nodes() {
+            if let Some(children) = node.children() {
+                let key = kdl_name!(node);
+                let permissions: Vec<PermissionType> = children
+                    .nodes()
+                    .iter()
+                    .filter_map(|p| PermissionType::from_str(kdl_name!(p)).ok())
+                    .collect();
+
+                granted_permission.insert(key.into(), permissions);
+            }
+        }
+
+        Ok(granted_permission)
+    }
+
+    pub fn to_string(granted: &GrantedPermission) -> String {
+        let mut kdl_document = KdlDocument::new();
+
+        // sort the plugins so that the file is stable between writes
+        let mut granted: Vec<(&String, &Vec<PermissionType>)> = granted.iter().collect();
+        granted.sort_by(|a, b| a.0.cmp(b.0));
+        for (k, v) in granted {
+            let mut children = KdlDocument::new();
+            for permission in v {
+                children
+                    .nodes_mut()
+                    .push(KdlNode::new(permission.to_string().as_str()));
+            }
+            let mut plugin_node = KdlNode::new(k.as_str());
+            plugin_node.set_children(children);
+            kdl_document.nodes_mut().push(plugin_node);
+        }
+
+        kdl_document.fmt();
+        kdl_document.to_string()
+    }
+}
@@ -17,6 +17,8 @@ pub mod channels; // Requires async_std
 pub mod ipc; // Requires interprocess
 #[cfg(not(target_family = "wasm"))]
 pub mod logging; // Requires log4rs
+#[cfg(not(target_family = "wasm"))]
+pub mod sessions; // Requires interprocess
 
 #[cfg(not(target_family = "wasm"))]
 pub use ::{
@@ -0,0 +1,136 @@
+//! The sessions running on this machine, whose servers each listen on a socket named after their
+//! session in [`ZELLIJ_SOCK_DIR`].
+use std::os::unix::fs::FileTypeExt;
+use std::time::SystemTime;
+use std::{fs, io};
+
+use crate::{
+    consts::ZELLIJ_SOCK_DIR,
+    data::SessionInfo,
+    interprocess::local_socket::LocalSocketStream,
+    ipc::{
+        query_session_info, ClientToServerMsg, IpcReceiverWithContext, IpcSenderWithContext,
+        ServerToClientMsg,
+    },
+};
+
+// The names of the sockets in ZELLIJ_SOCK_DIR, including those left behind by crashed servers
+fn get_socket_names() -> Result<Vec<String>, io::ErrorKind> {
+    match fs::read_dir(&*ZELLIJ_SOCK_DIR) {
+        Ok(files) => {
+            let mut socket_names = Vec::new();
+            files.for_each(|file| {
+                let file = file.unwrap();
+                let file_name = file.file_name().into_string().unwrap();
+                if file.file_type().unwrap().is_socket() {
+                    socket_names.push(file_name);
+                }
+            });
+            Ok(socket_names)
+        },
+        Err(err) if io::ErrorKind::NotFound != err.kind() => Err(err.kind()),
+        Err(_) => Ok(Vec::with_capacity(0)),
+    }
+}
+
+pub fn get_sessions() -> Result<Vec<String>, io::ErrorKind> {
+    let mut sessions = get_socket_names()?;
+    sessions.retain(|name| assert_socket(name));
+    Ok(sessions)
+}
+
+/// What the servers of the running sessions report about them, sorted by session name
+pub fn get_sessions_info() -> Result<Vec<SessionInfo>, io::ErrorKind> {
+    let mut sessions: Vec<SessionInfo> = get_socket_names()?
+        .iter()
+        .filter_map(|name| query_session_info(&ZELLIJ_SOCK_DIR.join(name)))
+        .collect();
+    sessions.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(sessions)
+}
+
+pub fn get_sessions_sorted_by_mtime() -> anyhow::Result<Vec<String>> {
+    match fs::read_dir(&*ZELLIJ_SOCK_DIR) {
+        Ok(files) => {
+            let mut sessions_with_mtime: Vec<(String, SystemTime)> = Vec::new();
+            for file in files {
+                let file = file?;
+                let file_name = file.file_name().into_string().unwrap();
+                let file_modified_at = file.metadata()?.modified()?;
+                if file.file_type()?.is_socket() && assert_socket(&file_name) {
+                    sessions_with_mtime.push((file_name, file_modified_at));
+                }
+            }
+            sessions_with_mtime.sort_by_key(|x| x.1); // the oldest one will be the first
+
+            let sessions = sessions_with_mtime.iter().map(|x| x.0.clone()).collect();
+            Ok(sessions)
+        },
+        Err(err) if io::ErrorKind::NotFound != err.kind() => Err(err.into()),
+        Err(_) => Ok(Vec::with_capacity(0)),
+    }
+}
+
+/// Whether a server answers on the socket of the session, removes the socket if none does
+pub fn assert_socket(name: &str) -> bool {
+    let path = &*ZELLIJ_SOCK_DIR.join(name);
+    match LocalSocketStream::connect(path) {
+        Ok(stream) => {
+            let mut sender = IpcSenderWithContext::new(stream);
+            let _ = sender.send(ClientToServerMsg::ConnStatus);
+            let mut receiver: IpcReceiverWithContext<ServerToClientMsg> = sender.get_receiver();
+            match receiver.recv() {
+                Some((ServerToClientMsg::Connected, _)) => true,
+                None | Some((_, _)) => false,
+            }
+        },
+        Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => {
+            drop(fs::remove_file(path));
+            false
+        },
+        Err(_) => false,
+    }
+}
+
+/// Sessions are named after their socket, so their name must not lead out of [`ZELLIJ_SOCK_DIR`]
+pub fn validate_session_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name == "." || name == ".." {
+        Err(format!("{:?} is not a valid session name", name))
+    } else if name.contains('/') {
+        Err(format!("session name {:?} cannot contain '/'", name))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod sessions_test {
+    use super::*;
+
+    #[test]
+    fn session_names_cannot_lead_out_of_the_socket_dir() {
+        assert!(validate_session_name("my-session").is_ok());
+        assert!(validate_session_name("..my.session..").is_ok());
+        assert!(validate_session_name("").is_err());
+        assert!(validate_session_name(".").is_err());
+        assert!(validate_session_name("..").is_err());
+        assert!(validate_session_name("../other-user-session").is_err());
+        assert!(validate_session_name("/tmp/session").is_err());
+        assert!(validate_session_name("nested/session").is_err());
+    }
+
+    #[test]
+    fn sessions_that_do_not_answer_are_skipped() {
+        let socket_path = std::env::temp_dir().join(format!(
+            "zellij-test-unresponsive-session-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&socket_path);
+        // connections are queued by the listener, but never answered
+        let _listener = std::os::unix::net::UnixListener::bind(&socket_path).unwrap();
+        let started_at = std::time::Instant::now();
+        assert!(query_session_info(&socket_path).is_none());
+        assert!(started_at.elapsed() < std::time::Duration::from_secs(5));
+        let _ = fs::remove_file(&socket_path);
+    }
+}
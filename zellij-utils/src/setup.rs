@@ -16,8 +16,9 @@ use clap_complete::Shell;
 use directories_next::BaseDirs;
 use serde::{Deserialize, Serialize};
 use std::{
-    convert::TryFrom, fmt::Write as FmtWrite, io::Write, path::Path, path::PathBuf, process,
+    convert::TryFrom, fmt::Write as FmtWrite, fs, io::Write, path::Path, path::PathBuf, process,
 };
+use url::Url;
 
 const CONFIG_LOCATION: &str = ".config/zellij";
 const CONFIG_NAME: &str = "config.kdl";
@@ -84,6 +85,56 @@ pub fn get_layout_dir(config_dir: Option<PathBuf>) -> Option<PathBuf> {
 pub fn get_theme_dir(config_dir: Option<PathBuf>) -> Option<PathBuf> {
     config_dir.map(|dir| dir.join("themes"))
 }
+
+/// The directory under which plugins keep their persistent data
+pub fn get_plugins_data_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("plugins").join("data")
+}
+
+/// The persistent data directory of the plugin at `location` (mounted as `/data` inside the
+/// plugin), it survives restarts and is only removed by `zellij setup --clean-plugin-data`
+pub fn get_plugin_data_dir(data_dir: &Path, location: &Url) -> PathBuf {
+    let key: String = url::form_urlencoded::byte_serialize(location.as_str().as_bytes()).collect();
+    get_plugins_data_dir(data_dir).join(key)
+}
+
+/// Plugins used to keep their data in the cache directory, under their location. Moves it to the
+/// persistent data directory of the plugin, unless that one already exists.
+pub fn migrate_plugin_data_dir(
+    cache_dir: &Path,
+    data_dir: &Path,
+    location: &Url,
+) -> std::io::Result<()> {
+    let old_plugin_data_dir = cache_dir.join(location.to_string());
+    let plugin_data_dir = get_plugin_data_dir(data_dir, location);
+    if !old_plugin_data_dir.is_dir() || plugin_data_dir.exists() {
+        return Ok(());
+    }
+    if let Some(parent) = plugin_data_dir.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // renaming fails if the cache and data directories are on different filesystems
+    if fs::rename(&old_plugin_data_dir, &plugin_data_dir).is_err() {
+        copy_dir_all(&old_plugin_data_dir, &plugin_data_dir)?;
+        fs::remove_dir_all(&old_plugin_data_dir)?;
+    }
+    Ok(())
+}
+
+fn copy_dir_all(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let path = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &path)?;
+        } else {
+            fs::copy(entry.path(), path)?;
+        }
+    }
+    Ok(())
+}
+
 pub fn dump_asset(asset: &[u8]) -> std::io::Result<()> {
     std::io::stdout().write_all(asset)?;
     Ok(())
@@ -199,6 +250,11 @@ pub struct Setup {
     /// Generates auto-start script for the specified shell
     #[clap(long, value_name = "SHELL", value_parser)]
     pub generate_auto_start: Option<String>,
+
+    /// Removes the persistent data of the specified plugin (eg. "zellij:strider"),
+    /// or of all plugins if none is specified
+    #[clap(long, value_name = "PLUGIN", value_parser)]
+    pub clean_plugin_data: Option<Option<String>>,
 }
 
 impl Setup {
@@ -306,6 +362,33 @@ impl Setup {
             Setup::check_defaults_config(opts, config_options)?;
             std::process::exit(0);
         }
+        if let Some(plugin) = &self.clean_plugin_data {
+            Setup::clean_plugin_data(opts, plugin.as_deref())?;
+            std::process::exit(0);
+        }
+        Ok(())
+    }
+
+    pub fn clean_plugin_data(opts: &CliArgs, plugin: Option<&str>) -> std::io::Result<()> {
+        let data_dir = opts.data_dir.clone().unwrap_or_else(get_default_data_dir);
+        let dir_to_remove = match plugin {
+            Some(plugin) => {
+                let location = Url::parse(plugin).map_err(|e| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        format!("Failed to parse plugin location {:?}: {}", plugin, e),
+                    )
+                })?;
+                get_plugin_data_dir(&data_dir, &location)
+            },
+            None => get_plugins_data_dir(&data_dir),
+        };
+        if dir_to_remove.exists() {
+            fs::remove_dir_all(&dir_to_remove)?;
+            println!("Removed plugin data in {:?}", dir_to_remove);
+        } else {
+            println!("No plugin data found in {:?}", dir_to_remove);
+        }
         Ok(())
     }
 
@@ -504,11 +587,92 @@ impl Setup {
 
 #[cfg(test)]
 mod setup_test {
-    use super::Setup;
+    use super::{get_plugin_data_dir, migrate_plugin_data_dir, Setup};
     use crate::cli::{CliArgs, Command};
     use crate::input::options::{CliOptions, Options};
     use insta::assert_snapshot;
+    use std::fs;
     use std::path::PathBuf;
+    use tempfile::tempdir;
+    use url::Url;
+
+    #[test]
+    fn plugin_data_dirs_are_keyed_by_plugin_location() {
+        let data_dir = PathBuf::from("/tmp/zellij-data");
+        let strider = get_plugin_data_dir(&data_dir, &Url::parse("zellij:strider").unwrap());
+        let file_plugin =
+            get_plugin_data_dir(&data_dir, &Url::parse("file:/path/to/plugin.wasm").unwrap());
+        assert_eq!(
+            strider,
+            PathBuf::from("/tmp/zellij-data/plugins/data/zellij%3Astrider")
+        );
+        assert_eq!(
+            file_plugin,
+            PathBuf::from("/tmp/zellij-data/plugins/data/file%3A%2F%2F%2Fpath%2Fto%2Fplugin.wasm")
+        );
+    }
+
+    #[test]
+    fn plugin_data_is_moved_from_the_cache_dir_on_first_use() {
+        let cache_dir = tempdir().unwrap();
+        let data_dir = tempdir().unwrap();
+        let location = Url::parse("zellij:strider").unwrap();
+        let old_plugin_data_dir = cache_dir.path().join("zellij:strider");
+        fs::create_dir_all(old_plugin_data_dir.join("nested")).unwrap();
+        fs::write(old_plugin_data_dir.join("state.json"), "{}").unwrap();
+        fs::write(old_plugin_data_dir.join("nested/notes.txt"), "hi").unwrap();
+
+        migrate_plugin_data_dir(cache_dir.path(), data_dir.path(), &location).unwrap();
+
+        let plugin_data_dir = get_plugin_data_dir(data_dir.path(), &location);
+        assert_eq!(
+            fs::read_to_string(plugin_data_dir.join("state.json")).unwrap(),
+            "{}"
+        );
+        assert_eq!(
+            fs::read_to_string(plugin_data_dir.join("nested/notes.txt")).unwrap(),
+            "hi"
+        );
+        assert!(
+            !old_plugin_data_dir.exists(),
+            "the old directory is removed"
+        );
+    }
+
+    #[test]
+    fn existing_plugin_data_is_not_overwritten_by_the_old_one() {
+        let cache_dir = tempdir().unwrap();
+        let data_dir = tempdir().unwrap();
+        let location = Url::parse("zellij:strider").unwrap();
+        let old_plugin_data_dir = cache_dir.path().join("zellij:strider");
+        fs::create_dir_all(&old_plugin_data_dir).unwrap();
+        fs::write(old_plugin_data_dir.join("state.json"), "old").unwrap();
+        let plugin_data_dir = get_plugin_data_dir(data_dir.path(), &location);
+        fs::create_dir_all(&plugin_data_dir).unwrap();
+        fs::write(plugin_data_dir.join("state.json"), "new").unwrap();
+
+        migrate_plugin_data_dir(cache_dir.path(), data_dir.path(), &location).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(plugin_data_dir.join("state.json")).unwrap(),
+            "new"
+        );
+        assert!(
+            old_plugin_data_dir.exists(),
+            "the old directory is left alone"
+        );
+    }
+
+    #[test]
+    fn migrating_plugin_data_that_was_never_written_does_nothing() {
+        let cache_dir = tempdir().unwrap();
+        let data_dir = tempdir().unwrap();
+        let location = Url::parse("file:/path/to/plugin.wasm").unwrap();
+
+        migrate_plugin_data_dir(cache_dir.path(), data_dir.path(), &location).unwrap();
+
+        assert!(!get_plugin_data_dir(data_dir.path(), &location).exists());
+    }
 
     #[test]
     fn default_config_with_no_cli_arguments() {
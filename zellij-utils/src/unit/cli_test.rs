@@ -0,0 +1,30 @@
+use super::*;
+
+fn parse_action(args: &[&str]) -> (Option<PaneId>, CliAction) {
+    let cli_args = CliArgs::try_parse_from(["zellij", "action"].iter().chain(args)).unwrap();
+    match cli_args.command {
+        Some(Command::Sessions(Sessions::Action {
+            pane_id, action, ..
+        })) => (pane_id, action),
+        command => panic!("not an action: {:?}", command),
+    }
+}
+
+#[test]
+fn subscribing_to_the_output_of_a_pane_does_not_target_it() {
+    let (target_pane_id, action) = parse_action(&["subscribe", "--output-pane-id", "3"]);
+    assert_eq!(target_pane_id, None, "the action has no target pane");
+    match action {
+        CliAction::Subscribe { output_pane_id, .. } => {
+            assert_eq!(output_pane_id, vec![PaneId::Terminal(3)])
+        },
+        action => panic!("not a subscription: {:?}", action),
+    }
+}
+
+#[test]
+fn actions_can_target_a_pane() {
+    let (target_pane_id, action) = parse_action(&["--pane-id", "plugin_2", "list-tabs"]);
+    assert_eq!(target_pane_id, Some(PaneId::Plugin(2)));
+    assert!(matches!(action, CliAction::ListTabs { json: false }));
+}